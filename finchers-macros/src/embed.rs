@@ -0,0 +1,161 @@
+use {
+    flate2::{write::GzEncoder, Compression, Crc},
+    proc_macro::TokenStream,
+    proc_macro2::Span,
+    quote::*,
+    std::{
+        env, fs,
+        io::{self, Write},
+        path::{Path, PathBuf},
+    },
+    syn::{DeriveInput, LitByteStr, LitStr},
+};
+
+#[allow(nonstandard_style)]
+pub fn derive(input: DeriveInput) -> syn::parse::Result<TokenStream> {
+    let mut dir: Option<LitStr> = None;
+    for attr in &input.attrs {
+        if attr.path.is_ident("dir") {
+            match attr.parse_meta()? {
+                syn::Meta::NameValue(syn::MetaNameValue {
+                    lit: syn::Lit::Str(lit),
+                    ..
+                }) => dir = Some(lit),
+                _ => {
+                    return Err(syn::parse::Error::new_spanned(
+                        attr,
+                        "the attribute must be a `#[dir = \"..\"]`",
+                    ));
+                }
+            }
+        }
+    }
+    let dir = dir.ok_or_else(|| {
+        syn::parse::Error::new_spanned(&input, "missing attribute: #[dir = \"path/to/dir\"]")
+    })?;
+
+    let manifest_dir = env::var_os("CARGO_MANIFEST_DIR").map_or_else(PathBuf::new, PathBuf::from);
+    let root = manifest_dir.join(dir.value());
+    if !root.is_dir() {
+        return Err(syn::parse::Error::new_spanned(
+            &dir,
+            format!("`{}` is not a directory", root.display()),
+        ));
+    }
+
+    let mut files = vec![];
+    collect_files(&root, "", &mut files).map_err(|err| {
+        syn::parse::Error::new_spanned(
+            &dir,
+            format!("failed to read `{}`: {}", root.display(), err),
+        )
+    })?;
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let EmbeddedDir: syn::Path = syn::parse_quote!(finchers::endpoints::fs::EmbeddedDir);
+    let EmbeddedFile: syn::Path = syn::parse_quote!(finchers::endpoints::fs::EmbeddedFile);
+
+    let mut entries = vec![];
+    for (path, abs_path) in &files {
+        let data = fs::read(abs_path).map_err(|err| {
+            syn::parse::Error::new_spanned(
+                &dir,
+                format!("failed to read `{}`: {}", abs_path.display(), err),
+            )
+        })?;
+
+        // The checksum is used so that the entity tag is stable across the builds.
+        let mut crc = Crc::new();
+        crc.update(&data);
+        let etag = format!("\"{:08x}-{:x}\"", crc.sum(), data.len());
+
+        let gzip = compressed_lit(encode_gzip(&data), &data);
+        let br = compressed_lit(encode_brotli(&data), &data);
+
+        // `include_bytes!` is used so that the crate is rebuilt when the file changes.
+        let abs_path = abs_path.to_string_lossy();
+        entries.push(quote! {
+            #EmbeddedFile {
+                path: #path,
+                etag: #etag,
+                data: include_bytes!(#abs_path),
+                gzip: #gzip,
+                br: #br,
+            }
+        });
+    }
+
+    let Self_ = &input.ident;
+    let num_files = entries.len();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(TokenStream::from(quote! {
+        impl #impl_generics #EmbeddedDir for #Self_ #ty_generics #where_clause {
+            fn files() -> &'static [#EmbeddedFile] {
+                static FILES: [#EmbeddedFile; #num_files] = [#(#entries,)*];
+                &FILES
+            }
+        }
+    }))
+}
+
+/// Collects the non-hidden files under `dir` recursively.
+fn collect_files(dir: &Path, prefix: &str, files: &mut Vec<(String, PathBuf)>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().into_string().map_err(|name| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("the file name is not valid UTF-8: {:?}", name),
+            )
+        })?;
+        if name.starts_with('.') {
+            continue;
+        }
+
+        let path = if prefix.is_empty() {
+            name
+        } else {
+            format!("{}/{}", prefix, name)
+        };
+        let file_type = fs::metadata(entry.path())?.file_type();
+        if file_type.is_dir() {
+            collect_files(&entry.path(), &path, files)?;
+        } else if file_type.is_file() {
+            files.push((path, entry.path()));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the expression of the compressed content, if it is smaller than the original.
+fn compressed_lit(
+    compressed: io::Result<Option<Vec<u8>>>,
+    data: &[u8],
+) -> proc_macro2::TokenStream {
+    match compressed {
+        Ok(Some(ref compressed)) if compressed.len() < data.len() => {
+            let compressed = LitByteStr::new(compressed, Span::call_site());
+            quote!(Some(#compressed))
+        }
+        _ => quote!(None),
+    }
+}
+
+fn encode_gzip(data: &[u8]) -> io::Result<Option<Vec<u8>>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(data)?;
+    encoder.finish().map(Some)
+}
+
+#[cfg(feature = "brotli")]
+fn encode_brotli(data: &[u8]) -> io::Result<Option<Vec<u8>>> {
+    let mut encoder = brotli::CompressorWriter::new(Vec::new(), 4096, 11, 22);
+    encoder.write_all(data)?;
+    Ok(Some(encoder.into_inner()))
+}
+
+#[cfg(not(feature = "brotli"))]
+fn encode_brotli(_: &[u8]) -> io::Result<Option<Vec<u8>>> {
+    Ok(None)
+}
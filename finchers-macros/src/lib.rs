@@ -1,12 +1,17 @@
 extern crate proc_macro;
 
+mod embed;
+mod schema;
+
 use {
     proc_macro::TokenStream,
     proc_macro2::Span,
     quote::*,
     syn::{
         parse_macro_input, //
+        Data,
         DeriveInput,
+        Fields,
         Ident,
         LitStr,
         Type,
@@ -56,10 +61,22 @@ pub fn ExtractPath(input: TokenStream) -> TokenStream {
     };
 
     let path_value = path.value();
-    let components = match parse_path(&path_value, &path) {
+    let mut components = match parse_path(&path_value, &path) {
         Ok(components) => components,
         Err(err) => return err.to_compile_error().into(),
     };
+
+    // The placeholders are bound to the fields if the struct has named fields.
+    let field_idents = match input.data {
+        Data::Struct(syn::DataStruct {
+            fields: Fields::Named(ref fields),
+            ..
+        }) => match bind_fields(&mut components, fields, &path) {
+            Ok(idents) => Some(idents),
+            Err(err) => return err.to_compile_error().into(),
+        },
+        _ => None,
+    };
     let components = &components; // anchored
 
     let Self_ = &input.ident;
@@ -68,14 +85,20 @@ pub fn ExtractPath(input: TokenStream) -> TokenStream {
         syn::parse_quote!(finchers::endpoint::syntax::ExtractPathError);
     let FromEncodedStr: syn::Path =
         syn::parse_quote!(finchers::endpoint::syntax::encoded::FromEncodedStr);
+    let ToEncodedStr: syn::Path =
+        syn::parse_quote!(finchers::endpoint::syntax::encoded::ToEncodedStr);
     let PreflightContext: syn::Path = syn::parse_quote!(finchers::action::PreflightContext);
+    let private: syn::Path = syn::parse_quote!(finchers::endpoint::syntax::__private);
+    let Description: syn::Path = syn::parse_quote!(finchers::endpoint::describe::Description);
 
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
 
+    let where_clause_orig = where_clause;
     let mut where_clause = where_clause.cloned();
     for component in components {
         match component {
-            Component::SingleParam(ty) | Component::CatchAllParam(ty) => {
+            Component::SingleParam(Param { ty, .. }, _)
+            | Component::CatchAllParam(Param { ty, .. }) => {
                 let predicates = &mut where_clause
                     .get_or_insert_with(|| syn::WhereClause {
                         where_token: Default::default(),
@@ -91,17 +114,21 @@ pub fn ExtractPath(input: TokenStream) -> TokenStream {
         }
     }
 
-    let output_types = components.into_iter().filter_map(|c| match c {
-        Component::Static(..) => None,
-        Component::SingleParam(ty) => Some(ty),
-        Component::CatchAllParam(ty) => Some(ty),
-    });
+    let output_types: Vec<&Type> = components
+        .into_iter()
+        .filter_map(|c| match c {
+            Component::Static(..) | Component::Alternation(..) => None,
+            Component::SingleParam(param, _) | Component::CatchAllParam(param) => Some(&param.ty),
+        })
+        .collect();
+    let output_types = &output_types; // anchored
 
     let mut output_idents: Vec<syn::Ident> = vec![];
     let mut extracts: Vec<syn::Stmt> = vec![];
     for component in components {
         match component {
             Component::Static(s) => {
+                let s = encode_segment(s);
                 extracts.push(syn::parse_quote! {
                     match cx.cursor().next() {
                         Some(s) if s == #s => (),
@@ -110,19 +137,80 @@ pub fn ExtractPath(input: TokenStream) -> TokenStream {
                 });
             }
 
-            Component::SingleParam(ty) => {
+            Component::Alternation(alternatives) => {
+                let alternatives: Vec<String> =
+                    alternatives.iter().map(|a| encode_segment(a)).collect();
+                extracts.push(syn::parse_quote! {
+                    match cx.cursor().next() {
+                        Some(s) if [#(#alternatives),*].iter().any(|a| s == *a) => (),
+                        _ => return Err(#ExtractPathError::not_matched()),
+                    }
+                });
+            }
+
+            Component::SingleParam(Param { ty, .. }, constraint) => {
                 let ident = Ident::new(&format!("__x_{}", output_idents.len()), Span::call_site());
+                let (pre_check, post_check) = match constraint {
+                    None => (None, None),
+                    Some(Constraint::Regex(pattern)) => {
+                        let pre_check = quote! {
+                            {
+                                ::std::thread_local! {
+                                    static PATTERN: #private::Regex = #private::Regex::new(#pattern)
+                                        .expect("the pattern should be validated at compile time");
+                                }
+                                let matched = match s.percent_decode() {
+                                    Ok(s) => PATTERN.with(|re| re.is_match(&s)),
+                                    Err(..) => false,
+                                };
+                                if !matched {
+                                    return Err(#ExtractPathError::not_matched());
+                                }
+                            }
+                        };
+                        (Some(pre_check), None)
+                    }
+                    Some(Constraint::Length(range)) => {
+                        let condition = range_condition(range);
+                        let pre_check = quote! {
+                            {
+                                let x = match s.percent_decode() {
+                                    Ok(s) => s.chars().count(),
+                                    Err(..) => return Err(#ExtractPathError::not_matched()),
+                                };
+                                if !(#condition) {
+                                    return Err(#ExtractPathError::not_matched());
+                                }
+                            }
+                        };
+                        (Some(pre_check), None)
+                    }
+                    Some(Constraint::Range(range)) => {
+                        let condition = range_condition(range);
+                        let post_check = quote! {
+                            if !(#condition) {
+                                return Err(#ExtractPathError::not_matched());
+                            }
+                        };
+                        (None, Some(post_check))
+                    }
+                };
                 extracts.push(syn::parse_quote! {
                     let #ident = match cx.cursor().next() {
-                        Some(s) => <#ty as #FromEncodedStr>::from_encoded_str(s)
-                            .map_err(#ExtractPathError::new)?,
+                        Some(s) => {
+                            #pre_check
+                            let x = <#ty as #FromEncodedStr>::from_encoded_str(s)
+                                .map_err(#ExtractPathError::new)?;
+                            #post_check
+                            x
+                        }
                         None => return Err(#ExtractPathError::not_matched()),
                     };
                 });
                 output_idents.push(ident);
             }
 
-            Component::CatchAllParam(ty) => {
+            Component::CatchAllParam(Param { ty, .. }) => {
                 let ident = Ident::new(&format!("__x_{}", output_idents.len()), Span::call_site());
                 extracts.push(syn::parse_quote! {
                     let #ident = {
@@ -136,25 +224,209 @@ pub fn ExtractPath(input: TokenStream) -> TokenStream {
         }
     }
 
+    let descriptions = components.iter().map(|component| -> syn::Expr {
+        match component {
+            Component::Static(s) => {
+                let s = encode_segment(s);
+                syn::parse_quote!(#Description::Segment(#s.to_owned()))
+            }
+            Component::Alternation(alternatives) => {
+                let segments = alternatives.iter().map(|alternative| {
+                    let alternative = encode_segment(alternative);
+                    quote!(#Description::Segment(#alternative.to_owned()))
+                });
+                syn::parse_quote!(#Description::Or(vec![#(#segments),*]))
+            }
+            Component::SingleParam(param, _) => {
+                let (name, ty) = (param.name_expr(), &param.ty_str);
+                syn::parse_quote!(#Description::Param { name: #name, ty: Some(#ty) })
+            }
+            Component::CatchAllParam(param) => {
+                let (name, ty) = (param.name_expr(), &param.ty_str);
+                syn::parse_quote!(#Description::Remains { name: #name, ty: Some(#ty) })
+            }
+        }
+    });
+
+    // The path cannot be built if it contains an alternation, since which
+    // alternative to use is not determined from the values of parameters.
+    let is_reversible = components.iter().all(|component| match component {
+        Component::Alternation(..) => false,
+        _ => true,
+    });
+    let to_path = if is_reversible {
+        let mut pushes: Vec<syn::Stmt> = vec![];
+        let mut args: Vec<syn::Expr> = vec![];
+        for component in components {
+            match component {
+                Component::Static(s) => {
+                    let segment = format!("/{}", encode_segment(s));
+                    pushes.push(syn::parse_quote!(path.push_str(#segment);));
+                }
+                Component::SingleParam(..) | Component::CatchAllParam(..) => {
+                    // The values are taken from the fields bound to the placeholders,
+                    // or from the elements of tuple in order.
+                    let arg: syn::Expr = match field_idents {
+                        Some(ref field_idents) => {
+                            let field = &field_idents[args.len()];
+                            syn::parse_quote!(&self.#field)
+                        }
+                        None => {
+                            let index = syn::Index::from(args.len());
+                            syn::parse_quote!(&params.#index)
+                        }
+                    };
+                    pushes.push(syn::parse_quote! {
+                        path.push('/');
+                    });
+                    pushes.push(syn::parse_quote! {
+                        path.push_str(&#ToEncodedStr::to_encoded_str(#arg));
+                    });
+                    args.push(arg);
+                }
+                Component::Alternation(..) => unreachable!(),
+            }
+        }
+        let (doc, signature) = match field_idents {
+            Some(..) => (
+                "Generates the path from the values of the fields.",
+                quote!(pub fn to_path(&self) -> String),
+            ),
+            None => (
+                "Generates the path from the values of the parameters.",
+                quote!(pub fn to_path(params: &(#(#output_types,)*)) -> String),
+            ),
+        };
+        if field_idents.is_none() && args.is_empty() {
+            pushes.push(syn::parse_quote!(let _ = params;));
+        }
+        // The bounds are written with `for<'a>` so that they are checked
+        // where `to_path` is used, rather than in the derived code.
+        let bounds = output_types
+            .iter()
+            .map(|ty| quote!(for<'__a> #ty: #ToEncodedStr));
+        Some(quote! {
+            impl #impl_generics #Self_ #ty_generics #where_clause_orig {
+                #[doc = #doc]
+                ///
+                /// Each parameter is percent-encoded as a path segment.
+                #[allow(dead_code)]
+                #signature
+                where
+                    #(#bounds,)*
+                {
+                    let mut path = String::new();
+                    #(#pushes)*
+                    if path.is_empty() {
+                        path.push('/');
+                    }
+                    path
+                }
+            }
+        })
+    } else {
+        None
+    };
+
+    let (output_type, output) = match field_idents {
+        Some(ref field_idents) => (
+            quote!((#Self_ #ty_generics,)),
+            quote!((#Self_ { #(#field_idents: #output_idents),* },)),
+        ),
+        None => (quote!((#(#output_types,)*)), quote!((#(#output_idents,)*))),
+    };
+
     TokenStream::from(quote! {
+        #to_path
+
         impl #impl_generics #ExtractPath for #Self_ #ty_generics
         #where_clause
         {
-            type Output = (#(#output_types,)*);
+            type Output = #output_type;
 
             fn extract(cx: &mut #PreflightContext<'_>) -> Result<Self::Output, #ExtractPathError> {
                 #(#extracts)*
-                Ok((#(#output_idents,)*))
+                Ok(#output)
+            }
+
+            fn describe() -> #Description {
+                #Description::And(vec![#(#descriptions),*])
             }
         }
     })
 }
 
+/// A procedural macro to define code that embeds the files in the specified
+/// directory and implements `EmbeddedDir`.
+///
+/// This macro is used internally in `static_dir!()`.
+#[allow(nonstandard_style)]
+#[proc_macro_derive(EmbedDir, attributes(dir))]
+pub fn EmbedDir(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    embed::derive(input).unwrap_or_else(|err| err.to_compile_error().into())
+}
+
+/// A procedural macro to define code that implements `Schema`.
+#[allow(nonstandard_style)]
+#[proc_macro_derive(Schema)]
+pub fn Schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    schema::derive(input).unwrap_or_else(|err| err.to_compile_error().into())
+}
+
+/// Percent-encodes a static segment in the same way as `segment()`,
+/// so that it is compared with the segments in the request as they are.
+fn encode_segment(s: &str) -> String {
+    mod encode_set {
+        percent_encoding::define_encode_set! {
+            /// The same encode set as `finchers::endpoint::syntax::SEGMENT_ENCODE_SET`.
+            pub SEGMENT_ENCODE_SET = [percent_encoding::DEFAULT_ENCODE_SET] | {'/'}
+        }
+    }
+    percent_encoding::utf8_percent_encode(s, encode_set::SEGMENT_ENCODE_SET).to_string()
+}
+
 #[derive(Debug)]
 enum Component<'a> {
     Static(&'a str),
-    SingleParam(Type),
-    CatchAllParam(Type),
+    Alternation(Vec<&'a str>),
+    SingleParam(Param, Option<Constraint>),
+    CatchAllParam(Param),
+}
+
+#[derive(Debug)]
+struct Param {
+    ty: Type,
+    ty_str: String,
+    name: Option<String>,
+}
+
+impl Param {
+    fn parse(s: &str, lit: &LitStr) -> syn::parse::Result<Param> {
+        let (name, ty_str) = parse_name(s, lit)?;
+        let ty: syn::Type = syn::parse_str(ty_str) //
+            .map_err(|e| syn::parse::Error::new_spanned(lit, e))?;
+        Ok(Param {
+            ty,
+            ty_str: ty_str.to_owned(),
+            name: name.map(ToOwned::to_owned),
+        })
+    }
+
+    fn name_expr(&self) -> syn::Expr {
+        match self.name {
+            Some(ref name) => syn::parse_quote!(Some(#name)),
+            None => syn::parse_quote!(None),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Constraint {
+    Range(syn::ExprRange),
+    Length(syn::ExprRange),
+    Regex(String),
 }
 
 fn parse_path<'s>(s: &'s str, lit: &LitStr) -> syn::parse::Result<Vec<Component<'s>>> {
@@ -178,7 +450,7 @@ fn parse_path<'s>(s: &'s str, lit: &LitStr) -> syn::parse::Result<Vec<Component<
     }
 
     let mut components = vec![];
-    let mut iter = s.split('/').skip(1).peekable();
+    let mut iter = split_segments(&s[1..]).into_iter().peekable();
     while let Some(segment) = iter.next() {
         if segment.is_empty() {
             if iter.peek().is_some() {
@@ -197,18 +469,22 @@ fn parse_path<'s>(s: &'s str, lit: &LitStr) -> syn::parse::Result<Vec<Component<
                     "a segment that extracts a parameter must be end with '>'",
                 ));
             }
-            if !segment.is_ascii() {
+            let (ty_str, constraint) = parse_constraint(&segment[1..segment.len() - 1], lit)?;
+            if !ty_str.is_ascii() {
                 return Err(syn::parse::Error::new_spanned(
                     lit,
                     "non-ascii character(s) in the parameter position",
                 ));
             }
-            let ty_str = &segment[1..segment.len() - 1];
 
             if ty_str.starts_with("..") {
-                let ty: syn::Type = syn::parse_str(&ty_str[2..]) //
-                    .map_err(|e| syn::parse::Error::new_spanned(lit, e))?;
-                components.push(Component::CatchAllParam(ty));
+                if constraint.is_some() {
+                    return Err(syn::parse::Error::new_spanned(
+                        lit,
+                        "the catch-all parameter cannot have a constraint",
+                    ));
+                }
+                components.push(Component::CatchAllParam(Param::parse(&ty_str[2..], lit)?));
 
                 if iter.peek().is_some() {
                     return Err(syn::parse::Error::new_spanned(
@@ -219,10 +495,40 @@ fn parse_path<'s>(s: &'s str, lit: &LitStr) -> syn::parse::Result<Vec<Component<
 
                 break;
             } else {
-                let ty: syn::Type = syn::parse_str(ty_str) //
-                    .map_err(|e| syn::parse::Error::new_spanned(lit, e))?;
-                components.push(Component::SingleParam(ty));
+                components.push(Component::SingleParam(
+                    Param::parse(ty_str, lit)?,
+                    constraint,
+                ));
+            }
+        } else if segment.starts_with('(') {
+            if !segment.ends_with(')') {
+                return Err(syn::parse::Error::new_spanned(
+                    lit,
+                    "a segment that matches the alternatives must be end with ')'",
+                ));
             }
+            let alternatives: Vec<&str> = segment[1..segment.len() - 1].split('|').collect();
+            for (i, alternative) in alternatives.iter().enumerate() {
+                if alternative.is_empty() {
+                    return Err(syn::parse::Error::new_spanned(
+                        lit,
+                        "an alternative must not be empty",
+                    ));
+                }
+                if alternative.contains(|c: char| c == '<' || c == '>' || c == '(' || c == ')') {
+                    return Err(syn::parse::Error::new_spanned(
+                        lit,
+                        "an alternative must be a static segment",
+                    ));
+                }
+                if alternatives[..i].contains(alternative) {
+                    return Err(syn::parse::Error::new_spanned(
+                        lit,
+                        format!("duplicated alternative: {}", alternative),
+                    ));
+                }
+            }
+            components.push(Component::Alternation(alternatives));
         } else {
             components.push(Component::Static(segment));
         }
@@ -230,3 +536,221 @@ fn parse_path<'s>(s: &'s str, lit: &LitStr) -> syn::parse::Result<Vec<Component<
 
     Ok(components)
 }
+
+/// Binds the placeholders in the path to the fields with the same name,
+/// and returns the names of fields in the order of the placeholders.
+fn bind_fields(
+    components: &mut [Component<'_>],
+    fields: &syn::FieldsNamed,
+    lit: &LitStr,
+) -> syn::parse::Result<Vec<Ident>> {
+    let mut idents: Vec<Ident> = vec![];
+    for component in components {
+        let param = match component {
+            Component::SingleParam(param, _) | Component::CatchAllParam(param) => param,
+            _ => continue,
+        };
+        if param.name.is_some() {
+            return Err(syn::parse::Error::new_spanned(
+                lit,
+                "the placeholder must be a field name without the type",
+            ));
+        }
+        let name = &param.ty_str;
+
+        let field = fields
+            .named
+            .iter()
+            .find(|field| {
+                field
+                    .ident
+                    .as_ref()
+                    .map_or(false, |ident| ident == name.as_str())
+            })
+            .ok_or_else(|| {
+                syn::parse::Error::new_spanned(lit, format!("no field named `{}`", name))
+            })?;
+        let ident = field.ident.clone().expect("named field");
+        if idents.contains(&ident) {
+            return Err(syn::parse::Error::new_spanned(
+                lit,
+                format!("the field `{}` is bound more than once", ident),
+            ));
+        }
+
+        param.name = Some(ident.to_string().trim_start_matches("r#").to_owned());
+        param.ty = field.ty.clone();
+        param.ty_str = field
+            .ty
+            .clone()
+            .into_token_stream()
+            .to_string()
+            .replace(' ', "");
+        idents.push(ident);
+    }
+
+    for field in &fields.named {
+        let ident = field.ident.as_ref().expect("named field");
+        if !idents.contains(ident) {
+            return Err(syn::parse::Error::new_spanned(
+                field,
+                format!("the field `{}` is not bound to the path", ident),
+            ));
+        }
+    }
+
+    Ok(idents)
+}
+
+/// Splits the name of a parameter, e.g. `id: u32`, from its type.
+fn parse_name<'s>(s: &'s str, lit: &LitStr) -> syn::parse::Result<(Option<&'s str>, &'s str)> {
+    let bytes = s.as_bytes();
+    let pos = (0..bytes.len()).find(|&i| {
+        bytes[i] == b':' && bytes.get(i + 1) != Some(&b':') && (i == 0 || bytes[i - 1] != b':')
+    });
+    let pos = match pos {
+        Some(pos) => pos,
+        None => return Ok((None, s.trim())),
+    };
+    let name = s[..pos].trim();
+    if syn::parse_str::<Ident>(name).is_err() {
+        return Err(syn::parse::Error::new_spanned(
+            lit,
+            format!("invalid parameter name: {}", name),
+        ));
+    }
+    Ok((Some(name), s[pos + 1..].trim()))
+}
+
+/// Splits the path into the segments, without splitting the quoted patterns.
+fn split_segments(s: &str) -> Vec<&str> {
+    let mut segments = vec![];
+    let mut start = 0;
+    let mut in_quote = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            '"' => in_quote = !in_quote,
+            '/' if !in_quote => {
+                segments.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    segments.push(&s[start..]);
+    segments
+}
+
+/// Parses the constraint of a parameter, e.g. `u32 | 1..=9999`, `String # 8` or
+/// `String ~ "^[a-z0-9-]+$"`, into the type part and the constraint.
+fn parse_constraint<'s>(
+    s: &'s str,
+    lit: &LitStr,
+) -> syn::parse::Result<(&'s str, Option<Constraint>)> {
+    let pos = match s.find(|c: char| c == '|' || c == '#' || c == '~') {
+        Some(pos) => pos,
+        None => return Ok((s, None)),
+    };
+    let (ty_str, constraint) = (&s[..pos], s[pos + 1..].trim());
+
+    let constraint = if s[pos..].starts_with('|') {
+        Constraint::Range(parse_range(constraint, "range", lit)?)
+    } else if s[pos..].starts_with('#') {
+        // A fixed length `N` is treated as the range `N..=N`.
+        let range = match syn::parse_str::<syn::Expr>(constraint) {
+            Ok(syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Int(..),
+                ..
+            })) => format!("{0}..={0}", constraint),
+            _ => constraint.to_owned(),
+        };
+        Constraint::Length(parse_range(&range, "length", lit)?)
+    } else {
+        if constraint.len() < 2 || !constraint.starts_with('"') || !constraint.ends_with('"') {
+            return Err(syn::parse::Error::new_spanned(
+                lit,
+                "the pattern constraint must be a quoted regular expression",
+            ));
+        }
+        Constraint::Regex(parse_pattern(&constraint[1..constraint.len() - 1], lit)?)
+    };
+
+    Ok((ty_str, Some(constraint)))
+}
+
+fn parse_range(constraint: &str, kind: &str, lit: &LitStr) -> syn::parse::Result<syn::ExprRange> {
+    let range = match syn::parse_str::<syn::Expr>(constraint) {
+        Ok(syn::Expr::Range(range)) => range,
+        _ => {
+            return Err(syn::parse::Error::new_spanned(
+                lit,
+                format!("invalid {} constraint: {}", kind, constraint),
+            ));
+        }
+    };
+    if range.from.is_none() && range.to.is_none() {
+        return Err(syn::parse::Error::new_spanned(
+            lit,
+            format!("the {} constraint must have a bound", kind),
+        ));
+    }
+    if let (Some(from), Some(to)) = (int_literal(&range.from), int_literal(&range.to)) {
+        let is_empty = match range.limits {
+            syn::RangeLimits::HalfOpen(..) => from >= to,
+            syn::RangeLimits::Closed(..) => from > to,
+        };
+        if is_empty {
+            return Err(syn::parse::Error::new_spanned(
+                lit,
+                format!("the {} constraint is empty: {}", kind, constraint),
+            ));
+        }
+    }
+    Ok(range)
+}
+
+#[cfg(feature = "regex")]
+fn parse_pattern(pattern: &str, lit: &LitStr) -> syn::parse::Result<String> {
+    if let Err(err) = regex::Regex::new(pattern) {
+        return Err(syn::parse::Error::new_spanned(
+            lit,
+            format!("invalid regular expression: {}", err),
+        ));
+    }
+    Ok(pattern.to_owned())
+}
+
+#[cfg(not(feature = "regex"))]
+fn parse_pattern(_: &str, lit: &LitStr) -> syn::parse::Result<String> {
+    Err(syn::parse::Error::new_spanned(
+        lit,
+        "the pattern constraint requires the feature `path-regex` of finchers",
+    ))
+}
+
+/// Returns the condition that the value `x` is within the range.
+fn range_condition(range: &syn::ExprRange) -> syn::Expr {
+    let from: Option<syn::Expr> = range
+        .from
+        .as_ref()
+        .map(|from| syn::parse_quote!((#from) <= x));
+    let to: Option<syn::Expr> = range.to.as_ref().map(|to| match range.limits {
+        syn::RangeLimits::HalfOpen(..) => syn::parse_quote!(x < (#to)),
+        syn::RangeLimits::Closed(..) => syn::parse_quote!(x <= (#to)),
+    });
+    match (from, to) {
+        (Some(from), Some(to)) => syn::parse_quote!(#from && #to),
+        (Some(cond), None) | (None, Some(cond)) => cond,
+        (None, None) => unreachable!("the range constraint must have a bound"),
+    }
+}
+
+fn int_literal(expr: &Option<Box<syn::Expr>>) -> Option<u64> {
+    match expr.as_ref().map(|expr| &**expr) {
+        Some(syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(lit),
+            ..
+        })) => Some(lit.value()),
+        _ => None,
+    }
+}
@@ -0,0 +1,330 @@
+use {
+    proc_macro::TokenStream,
+    quote::*,
+    syn::{Attribute, Data, DeriveInput, Fields, Lit, Meta, NestedMeta},
+};
+
+#[allow(nonstandard_style)]
+pub fn derive(input: DeriveInput) -> syn::parse::Result<TokenStream> {
+    let Schema: syn::Path = syn::parse_quote!(finchers::openapi::Schema);
+    let private: syn::Path = syn::parse_quote!(finchers::openapi::__private);
+
+    let container = SerdeAttrs::parse(&input.attrs, AttrKind::Container)?;
+
+    let body = match input.data {
+        Data::Struct(syn::DataStruct {
+            fields: Fields::Named(ref fields),
+            ..
+        }) => {
+            let mut entries = vec![];
+            let mut flattened = vec![];
+            for field in &fields.named {
+                let attrs = SerdeAttrs::parse(&field.attrs, AttrKind::Field)?;
+                if attrs.skip {
+                    continue;
+                }
+                let ty = &field.ty;
+                if attrs.flatten {
+                    flattened
+                        .push(quote!((<#ty as #Schema>::schema(), <#ty as #Schema>::required())));
+                    continue;
+                }
+
+                let name = match attrs.rename {
+                    Some(name) => name,
+                    None => {
+                        let ident = field
+                            .ident
+                            .as_ref()
+                            .expect("named field")
+                            .to_string()
+                            .trim_start_matches("r#")
+                            .to_owned();
+                        match container.rename_all {
+                            Some(rule) => rule.apply_to_field(&ident),
+                            None => ident,
+                        }
+                    }
+                };
+                let required = if container.default || attrs.optional {
+                    quote!(false)
+                } else {
+                    quote!(<#ty as #Schema>::required())
+                };
+                entries.push(quote!((#name, <#ty as #Schema>::schema(), #required)));
+            }
+            quote! {
+                #private::object_schema(vec![#(#entries),*], vec![#(#flattened),*])
+            }
+        }
+
+        Data::Enum(ref data) => {
+            let mut names = vec![];
+            for variant in &data.variants {
+                match variant.fields {
+                    Fields::Unit => {}
+                    _ => {
+                        return Err(syn::parse::Error::new_spanned(
+                            variant,
+                            "#[derive(Schema)] supports only the enums with unit variants",
+                        ));
+                    }
+                }
+                let attrs = SerdeAttrs::parse(&variant.attrs, AttrKind::Variant)?;
+                if attrs.skip {
+                    continue;
+                }
+                names.push(match attrs.rename {
+                    Some(name) => name,
+                    None => match container.rename_all {
+                        Some(rule) => rule.apply_to_variant(&variant.ident.to_string()),
+                        None => variant.ident.to_string(),
+                    },
+                });
+            }
+            quote! {
+                #private::enum_schema(&[#(#names),*])
+            }
+        }
+
+        _ => {
+            return Err(syn::parse::Error::new_spanned(
+                &input,
+                "#[derive(Schema)] supports only the structs with named fields and the enums",
+            ));
+        }
+    };
+
+    let Self_ = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    // The generic types are written inline, since their schemas depend on the parameters.
+    let name = if input.generics.params.is_empty() {
+        let name = container.rename.unwrap_or_else(|| Self_.to_string());
+        quote!(Some(#name))
+    } else {
+        quote!(None)
+    };
+
+    Ok(TokenStream::from(quote! {
+        impl #impl_generics #Schema for #Self_ #ty_generics
+        #where_clause
+        {
+            fn schema() -> #private::Value {
+                #body
+            }
+
+            fn name() -> Option<&'static str> {
+                #name
+            }
+        }
+    }))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AttrKind {
+    Container,
+    Field,
+    Variant,
+}
+
+/// The attributes of Serde which affect the generated schema.
+///
+/// The attributes which change the representation in the way the schema
+/// cannot follow (e.g. `tag`, `with`) are rejected, so that the schema never
+/// differs from the JSON produced by Serde.
+#[derive(Debug, Default)]
+struct SerdeAttrs {
+    rename: Option<String>,
+    rename_all: Option<RenameRule>,
+    skip: bool,
+    flatten: bool,
+    /// The field may be absent (`default`, `skip_serializing_if`, etc.).
+    optional: bool,
+    /// `#[serde(default)]` on the container.
+    default: bool,
+}
+
+impl SerdeAttrs {
+    fn parse(attrs: &[Attribute], kind: AttrKind) -> syn::parse::Result<SerdeAttrs> {
+        let mut serde_attrs = SerdeAttrs::default();
+        for attr in attrs {
+            if !attr.path.is_ident("serde") {
+                continue;
+            }
+            let nested = match attr.parse_meta()? {
+                Meta::List(list) => list.nested,
+                meta => {
+                    return Err(syn::parse::Error::new_spanned(
+                        meta,
+                        "the attribute must be a `#[serde(..)]`",
+                    ));
+                }
+            };
+            for nested in nested {
+                let meta = match nested {
+                    NestedMeta::Meta(meta) => meta,
+                    NestedMeta::Literal(lit) => {
+                        return Err(syn::parse::Error::new_spanned(lit, "unexpected literal"));
+                    }
+                };
+                serde_attrs.apply(&meta, kind)?;
+            }
+        }
+        Ok(serde_attrs)
+    }
+
+    fn apply(&mut self, meta: &Meta, kind: AttrKind) -> syn::parse::Result<()> {
+        let name = meta.name().to_string();
+        match (kind, name.as_str()) {
+            (_, "rename") => self.rename = Some(string_value(meta)?),
+            (AttrKind::Container, "rename_all") => {
+                let value = string_value(meta)?;
+                let rule = RenameRule::from_str(&value)
+                    .ok_or_else(|| syn::parse::Error::new_spanned(meta, "unknown rename rule"))?;
+                self.rename_all = Some(rule);
+            }
+            (AttrKind::Container, "default") => self.default = true,
+            (AttrKind::Field, "default")
+            | (AttrKind::Field, "skip_serializing")
+            | (AttrKind::Field, "skip_deserializing")
+            | (AttrKind::Field, "skip_serializing_if") => self.optional = true,
+            (AttrKind::Field, "skip") | (AttrKind::Variant, "skip") => self.skip = true,
+            (AttrKind::Field, "flatten") => self.flatten = true,
+
+            // They do not affect the representation.
+            (AttrKind::Container, "deny_unknown_fields")
+            | (AttrKind::Container, "bound")
+            | (AttrKind::Container, "crate")
+            | (AttrKind::Container, "expecting")
+            | (AttrKind::Field, "alias")
+            | (AttrKind::Field, "bound")
+            | (AttrKind::Field, "borrow")
+            | (AttrKind::Variant, "alias")
+            | (AttrKind::Variant, "skip_serializing")
+            | (AttrKind::Variant, "skip_deserializing")
+            | (AttrKind::Variant, "other") => {}
+
+            _ => {
+                return Err(syn::parse::Error::new_spanned(
+                    meta,
+                    format!("#[serde({})] is not supported by #[derive(Schema)]", name),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn string_value(meta: &Meta) -> syn::parse::Result<String> {
+    match *meta {
+        Meta::NameValue(syn::MetaNameValue {
+            lit: Lit::Str(ref lit),
+            ..
+        }) => Ok(lit.value()),
+        _ => Err(syn::parse::Error::new_spanned(
+            meta,
+            format!(
+                "#[derive(Schema)] supports only the form `{} = \"...\"`",
+                meta.name()
+            ),
+        )),
+    }
+}
+
+/// The case conversions specified by `#[serde(rename_all = "...")]`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RenameRule {
+    LowerCase,
+    UpperCase,
+    PascalCase,
+    CamelCase,
+    SnakeCase,
+    ScreamingSnakeCase,
+    KebabCase,
+    ScreamingKebabCase,
+}
+
+impl RenameRule {
+    fn from_str(s: &str) -> Option<RenameRule> {
+        match s {
+            "lowercase" => Some(RenameRule::LowerCase),
+            "UPPERCASE" => Some(RenameRule::UpperCase),
+            "PascalCase" => Some(RenameRule::PascalCase),
+            "camelCase" => Some(RenameRule::CamelCase),
+            "snake_case" => Some(RenameRule::SnakeCase),
+            "SCREAMING_SNAKE_CASE" => Some(RenameRule::ScreamingSnakeCase),
+            "kebab-case" => Some(RenameRule::KebabCase),
+            "SCREAMING-KEBAB-CASE" => Some(RenameRule::ScreamingKebabCase),
+            _ => None,
+        }
+    }
+
+    /// Converts the name of a variant, written in `PascalCase`.
+    fn apply_to_variant(self, variant: &str) -> String {
+        match self {
+            RenameRule::PascalCase => variant.to_owned(),
+            RenameRule::LowerCase => variant.to_ascii_lowercase(),
+            RenameRule::UpperCase => variant.to_ascii_uppercase(),
+            RenameRule::CamelCase => {
+                let mut chars = variant.chars();
+                match chars.next() {
+                    Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+                    None => String::new(),
+                }
+            }
+            RenameRule::SnakeCase => {
+                let mut snake = String::new();
+                for (i, ch) in variant.char_indices() {
+                    if i > 0 && ch.is_uppercase() {
+                        snake.push('_');
+                    }
+                    snake.push(ch.to_ascii_lowercase());
+                }
+                snake
+            }
+            RenameRule::ScreamingSnakeCase => RenameRule::SnakeCase
+                .apply_to_variant(variant)
+                .to_ascii_uppercase(),
+            RenameRule::KebabCase => RenameRule::SnakeCase
+                .apply_to_variant(variant)
+                .replace('_', "-"),
+            RenameRule::ScreamingKebabCase => RenameRule::ScreamingSnakeCase
+                .apply_to_variant(variant)
+                .replace('_', "-"),
+        }
+    }
+
+    /// Converts the name of a field, written in `snake_case`.
+    fn apply_to_field(self, field: &str) -> String {
+        match self {
+            RenameRule::LowerCase | RenameRule::SnakeCase => field.to_owned(),
+            RenameRule::UpperCase | RenameRule::ScreamingSnakeCase => field.to_ascii_uppercase(),
+            RenameRule::PascalCase => {
+                let mut pascal = String::new();
+                let mut capitalize = true;
+                for ch in field.chars() {
+                    if ch == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        pascal.push(ch.to_ascii_uppercase());
+                        capitalize = false;
+                    } else {
+                        pascal.push(ch);
+                    }
+                }
+                pascal
+            }
+            RenameRule::CamelCase => {
+                let pascal = RenameRule::PascalCase.apply_to_field(field);
+                let mut chars = pascal.chars();
+                match chars.next() {
+                    Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+                    None => String::new(),
+                }
+            }
+            RenameRule::KebabCase => field.replace('_', "-"),
+            RenameRule::ScreamingKebabCase => field.to_ascii_uppercase().replace('_', "-"),
+        }
+    }
+}
@@ -1,16 +1,20 @@
 //! Components for constructing `Endpoint`.
 
 mod boxed;
+pub mod describe;
 pub mod ext;
 pub mod syntax;
+pub mod wrapper;
 
 // re-exports
 pub use self::{
     boxed::{EndpointObj, LocalEndpointObj},
     ext::EndpointExt,
+    wrapper::Wrapper,
 };
 
 use {
+    self::describe::Description,
     crate::{
         action::{
             EndpointAction, //
@@ -48,12 +52,41 @@ pub trait IsEndpoint {
     {
         LocalEndpointObj::new(self)
     }
+
+    /// Returns the descriptor of the requests which this endpoint can handle.
+    ///
+    /// The default implementation returns `Description::Opaque`.
+    /// The combinators such as `And` and `Or` compose the descriptions of
+    /// their inner endpoints, so that all routes of the endpoint tree can be
+    /// enumerated by `Description::routes`.
+    fn describe(&self) -> Description {
+        Description::Opaque
+    }
 }
 
-impl<'a, E: IsEndpoint + ?Sized> IsEndpoint for &'a E {}
-impl<E: IsEndpoint + ?Sized> IsEndpoint for Box<E> {}
-impl<E: IsEndpoint + ?Sized> IsEndpoint for Rc<E> {}
-impl<E: IsEndpoint + ?Sized> IsEndpoint for Arc<E> {}
+impl<'a, E: IsEndpoint + ?Sized> IsEndpoint for &'a E {
+    fn describe(&self) -> Description {
+        (**self).describe()
+    }
+}
+
+impl<E: IsEndpoint + ?Sized> IsEndpoint for Box<E> {
+    fn describe(&self) -> Description {
+        (**self).describe()
+    }
+}
+
+impl<E: IsEndpoint + ?Sized> IsEndpoint for Rc<E> {
+    fn describe(&self) -> Description {
+        (**self).describe()
+    }
+}
+
+impl<E: IsEndpoint + ?Sized> IsEndpoint for Arc<E> {
+    fn describe(&self) -> Description {
+        (**self).describe()
+    }
+}
 
 /// Trait representing an endpoint, the main trait for abstracting
 /// HTTP services in Finchers.
@@ -7,7 +7,7 @@ use {
             PreflightContext,
         },
         common::Tuple,
-        endpoint::{Endpoint, IsEndpoint},
+        endpoint::{describe::Description, Endpoint, IsEndpoint},
         error::Error,
     },
     futures::Poll,
@@ -18,6 +18,8 @@ trait BoxedEndpoint<Bd> {
     type Output: Tuple;
 
     fn action(&self) -> EndpointActionObj<Bd, Self::Output>;
+
+    fn describe(&self) -> Description;
 }
 
 impl<Bd, E> BoxedEndpoint<Bd> for E
@@ -32,6 +34,10 @@ where
             inner: Box::new(self.action()),
         }
     }
+
+    fn describe(&self) -> Description {
+        IsEndpoint::describe(self)
+    }
 }
 
 /// A type that holds an instance of `Endpoint<Bd>` as type-erased form.
@@ -65,7 +71,14 @@ where
     }
 }
 
-impl<Bd, T> IsEndpoint for EndpointObj<Bd, T> where T: Tuple {}
+impl<Bd, T> IsEndpoint for EndpointObj<Bd, T>
+where
+    T: Tuple,
+{
+    fn describe(&self) -> Description {
+        self.inner.describe()
+    }
+}
 
 impl<Bd, T> Endpoint<Bd> for EndpointObj<Bd, T>
 where
@@ -114,6 +127,8 @@ trait LocalBoxedEndpoint<Bd> {
     type Output: Tuple;
 
     fn action(&self) -> LocalEndpointActionObj<Bd, Self::Output>;
+
+    fn describe(&self) -> Description;
 }
 
 impl<Bd, E> LocalBoxedEndpoint<Bd> for E
@@ -128,6 +143,10 @@ where
             inner: Box::new(self.action()),
         }
     }
+
+    fn describe(&self) -> Description {
+        IsEndpoint::describe(self)
+    }
 }
 
 /// A type that holds an instance of `Endpoint<Bd>` as type-erased form.
@@ -164,7 +183,14 @@ where
     }
 }
 
-impl<Bd, T> IsEndpoint for LocalEndpointObj<Bd, T> where T: Tuple {}
+impl<Bd, T> IsEndpoint for LocalEndpointObj<Bd, T>
+where
+    T: Tuple,
+{
+    fn describe(&self) -> Description {
+        self.inner.describe()
+    }
+}
 
 impl<Bd, T> Endpoint<Bd> for LocalEndpointObj<Bd, T>
 where
@@ -0,0 +1,390 @@
+//! Components for the introspection of the routes handled by endpoints.
+//!
+//! # Example
+//!
+//! ```
+//! # use finchers::prelude::*;
+//! # use finchers::endpoint::syntax::path;
+//! # use finchers::endpoints::body;
+//! use finchers::endpoint::IsEndpoint;
+//!
+//! let endpoint = path!(@get "/posts/<u32>")
+//!     .or(path!(@post "/posts").and(body::text()));
+//!
+//! let routes: Vec<String> = endpoint
+//!     .describe()
+//!     .routes()
+//!     .iter()
+//!     .map(ToString::to_string)
+//!     .collect();
+//! assert_eq!(routes, vec!["GET /posts/<u32>", "POST /posts [body:text]"]);
+//! ```
+
+use {
+    crate::endpoint::syntax::{encoded::ToPathParams, verb::Verbs},
+    http::header::HeaderName,
+    serde_json::Value,
+    std::fmt,
+};
+
+/// A descriptor of the requests which an endpoint can handle.
+///
+/// The value of this type is returned from `IsEndpoint::describe`, and forms
+/// a tree that follows the structure of the combined endpoints.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Description {
+    /// The endpoint provides no information about the requests it handles.
+    ///
+    /// It is treated as an endpoint which accepts any request.
+    Opaque,
+
+    /// A path segment which is equal to the specified (percent-encoded) value.
+    Segment(String),
+
+    /// A path segment which is parsed into a parameter.
+    Param {
+        /// The name of parameter, if given.
+        name: Option<&'static str>,
+
+        /// The name of its type, if known.
+        ty: Option<&'static str>,
+    },
+
+    /// The remaining path segments parsed into a parameter.
+    Remains {
+        /// The name of parameter, if given.
+        name: Option<&'static str>,
+
+        /// The name of its type, if known.
+        ty: Option<&'static str>,
+    },
+
+    /// The end of path segments.
+    Eos,
+
+    /// The set of allowed HTTP methods.
+    Verbs(Verbs),
+
+    /// An extractor of the query string.
+    Query {
+        /// Whether the query string must be present.
+        required: bool,
+
+        /// The schema of the parsed value, if known.
+        schema: Option<TypeSchema>,
+    },
+
+    /// An extractor of a header field.
+    Header {
+        /// The name of header field.
+        name: HeaderName,
+
+        /// Whether the header field must be present.
+        required: bool,
+    },
+
+    /// An extractor of the message body.
+    Body {
+        /// The kind of its format (e.g. `"json"`).
+        kind: &'static str,
+
+        /// The schema of the parsed value, if known.
+        schema: Option<TypeSchema>,
+    },
+
+    /// The output of the endpoint, which is returned as a JSON value of the specified schema.
+    Output(TypeSchema),
+
+    /// The sequence of descriptions, all of which are applied in order.
+    And(Vec<Description>),
+
+    /// The alternative descriptions, one of which is applied.
+    Or(Vec<Description>),
+
+    /// The description of an endpoint associated with a route name.
+    Named(&'static str, Box<Description>),
+}
+
+/// The schema of a type carried by the route descriptors.
+///
+/// The schema is held as a function returning the JSON Schema, so that the
+/// descriptors do not depend on how it is generated (e.g. `openapi::Schema`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TypeSchema {
+    name: Option<&'static str>,
+    schema: fn() -> Value,
+}
+
+impl TypeSchema {
+    /// Create a `TypeSchema` from the name of the type and the function
+    /// which returns its Schema Object.
+    pub fn new(name: Option<&'static str>, schema: fn() -> Value) -> TypeSchema {
+        TypeSchema { name, schema }
+    }
+
+    /// Returns the name of the type used in `components`, if any.
+    pub fn name(&self) -> Option<&'static str> {
+        self.name
+    }
+
+    /// Returns the Schema Object of the type.
+    pub fn schema(&self) -> Value {
+        (self.schema)()
+    }
+}
+
+impl Description {
+    /// Combines two descriptions applied in order.
+    ///
+    /// The nested sequences are flattened and `Opaque` is omitted.
+    pub fn and(self, other: Description) -> Description {
+        let mut items = vec![];
+        for desc in Some(self).into_iter().chain(Some(other)) {
+            match desc {
+                Description::Opaque => {}
+                Description::And(inner) => items.extend(inner),
+                desc => items.push(desc),
+            }
+        }
+        match items.len() {
+            0 => Description::Opaque,
+            1 => items.pop().expect("should have an item"),
+            _ => Description::And(items),
+        }
+    }
+
+    /// Combines two alternative descriptions.
+    ///
+    /// The nested alternatives are flattened.
+    pub fn or(self, other: Description) -> Description {
+        let mut items = vec![];
+        for desc in Some(self).into_iter().chain(Some(other)) {
+            match desc {
+                Description::Or(inner) => items.extend(inner),
+                desc => items.push(desc),
+            }
+        }
+        Description::Or(items)
+    }
+
+    /// Enumerates all routes described by this value.
+    ///
+    /// The alternatives are expanded in the order of evaluation.
+    pub fn routes(&self) -> Vec<Route> {
+        match self {
+            Description::Opaque => vec![Route::default()],
+            Description::Segment(..)
+            | Description::Param { .. }
+            | Description::Remains { .. }
+            | Description::Eos => vec![Route {
+                path: vec![self.clone()],
+                ..Route::default()
+            }],
+            Description::Verbs(verbs) => vec![Route {
+                verbs: Some(*verbs),
+                ..Route::default()
+            }],
+            Description::Query { .. } | Description::Header { .. } | Description::Body { .. } => {
+                vec![Route {
+                    extractors: vec![self.clone()],
+                    ..Route::default()
+                }]
+            }
+            Description::Output(schema) => vec![Route {
+                output: Some(*schema),
+                ..Route::default()
+            }],
+            Description::And(items) => items.iter().fold(vec![Route::default()], |acc, item| {
+                let nexts = item.routes();
+                let mut routes = Vec::with_capacity(acc.len() * nexts.len());
+                for route in &acc {
+                    for next in &nexts {
+                        routes.push(route.clone().concat(next.clone()));
+                    }
+                }
+                routes
+            }),
+            Description::Or(items) => items.iter().flat_map(Description::routes).collect(),
+            Description::Named(name, inner) => inner
+                .routes()
+                .into_iter()
+                .map(|mut route| {
+                    // The innermost name takes precedence.
+                    route.name = route.name.or(Some(*name));
+                    route
+                })
+                .collect(),
+        }
+    }
+
+    /// Generates the path of the route with the specified name.
+    ///
+    /// The path parameters are percent-encoded and substituted in order.
+    /// It returns `None` if there is no route with the name and the same
+    /// number of parameters, or if the path is ambiguous, e.g. the route
+    /// contains an alternation such as `(png|jpg)`.
+    pub fn url_for(&self, name: &str, params: &impl ToPathParams) -> Option<String> {
+        let params = params.to_path_params();
+        let mut paths: Vec<String> = self
+            .routes()
+            .into_iter()
+            .filter(|route| route.name == Some(name))
+            .filter_map(|route| route.to_path(&params))
+            .collect();
+        paths.sort();
+        paths.dedup();
+        if paths.len() != 1 {
+            return None;
+        }
+        paths.pop()
+    }
+}
+
+/// A route described by an endpoint, with the alternatives expanded.
+///
+/// The `Display` implementation formats the route as a line of route table,
+/// e.g. `GET /posts/<u32> [query]`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Route {
+    /// The path segments to be matched, which consists of `Segment`, `Param`,
+    /// `Remains` and `Eos`.
+    pub path: Vec<Description>,
+
+    /// The set of allowed methods, or `None` if any method is allowed.
+    pub verbs: Option<Verbs>,
+
+    /// The extractors of the query, header fields and message body.
+    pub extractors: Vec<Description>,
+
+    /// The name of this route, if given by `EndpointExt::named`.
+    pub name: Option<&'static str>,
+
+    /// The schema of the output, if given by `openapi::json_output`.
+    pub output: Option<TypeSchema>,
+}
+
+impl Route {
+    fn concat(mut self, other: Route) -> Route {
+        self.path.extend(other.path);
+        self.verbs = match (self.verbs, other.verbs) {
+            (Some(l), Some(r)) => Some(l & r),
+            (l, r) => l.or(r),
+        };
+        self.extractors.extend(other.extractors);
+        self.name = self.name.or(other.name);
+        self.output = other.output.or(self.output);
+        self
+    }
+
+    /// Builds the path of this route from the percent-encoded parameters.
+    ///
+    /// It returns `None` if the number of parameters does not match.
+    pub fn to_path(&self, params: &[String]) -> Option<String> {
+        let mut params = params.iter();
+        let mut path = String::new();
+        for desc in &self.path {
+            let segment = match desc {
+                Description::Segment(s) => s,
+                Description::Param { .. } | Description::Remains { .. } => params.next()?,
+                _ => continue,
+            };
+            path.push('/');
+            path.push_str(segment);
+        }
+        if params.next().is_some() {
+            return None;
+        }
+        if path.is_empty() {
+            path.push('/');
+        }
+        Some(path)
+    }
+
+    /// Returns whether a request may be matched by both routes.
+    ///
+    /// Two routes overlap if they share an allowed method and consume the
+    /// same sequence of path segments, in which case the route evaluated first
+    /// always wins. The routes which consume different numbers of segments are
+    /// not considered as overlapping since the router prefers the longer match.
+    pub fn overlaps(&self, other: &Route) -> bool {
+        let verbs = match (self.verbs, other.verbs) {
+            (Some(l), Some(r)) => (l & r).into_iter().next().is_some(),
+            _ => true,
+        };
+        verbs && paths_overlap(&self.path, &other.path)
+    }
+}
+
+fn paths_overlap(left: &[Description], right: &[Description]) -> bool {
+    let is_segment = |desc: &&Description| **desc != Description::Eos;
+    let mut left = left.iter().filter(is_segment);
+    let mut right = right.iter().filter(is_segment);
+    loop {
+        match (left.next(), right.next()) {
+            (None, None) => return true,
+            (Some(Description::Remains { .. }), _) | (_, Some(Description::Remains { .. })) => {
+                return true;
+            }
+            (Some(Description::Segment(l)), Some(Description::Segment(r))) if l != r => {
+                return false;
+            }
+            (Some(..), Some(..)) => {}
+            (Some(..), None) | (None, Some(..)) => return false,
+        }
+    }
+}
+
+impl fmt::Display for Route {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.verbs {
+            Some(verbs) => write!(f, "{} ", verbs)?,
+            None => f.write_str("* ")?,
+        }
+
+        let mut is_empty = true;
+        for desc in &self.path {
+            match desc {
+                Description::Segment(s) => write!(f, "/{}", s)?,
+                Description::Param { ty, .. } => write!(f, "/<{}>", ty.unwrap_or("_"))?,
+                Description::Remains { ty, .. } => write!(f, "/<..{}>", ty.unwrap_or("_"))?,
+                _ => continue,
+            }
+            is_empty = false;
+        }
+        if is_empty {
+            f.write_str("/")?;
+        }
+
+        if !self.extractors.is_empty() {
+            f.write_str(" [")?;
+            for (i, desc) in self.extractors.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                match desc {
+                    Description::Query { required: true, .. } => f.write_str("query")?,
+                    Description::Query {
+                        required: false, ..
+                    } => f.write_str("query?")?,
+                    Description::Header {
+                        name,
+                        required: true,
+                    } => write!(f, "header:{}", name)?,
+                    Description::Header {
+                        name,
+                        required: false,
+                    } => write!(f, "header:{}?", name)?,
+                    Description::Body { kind, .. } => write!(f, "body:{}", kind)?,
+                    desc => write!(f, "{:?}", desc)?,
+                }
+            }
+            f.write_str("]")?;
+        }
+
+        if let Some(name) = self.name {
+            write!(f, " ({})", name)?;
+        }
+
+        Ok(())
+    }
+}
@@ -4,6 +4,7 @@ mod and;
 mod and_then;
 mod map;
 mod map_err;
+mod named;
 mod or;
 mod or_strict;
 mod recover;
@@ -13,14 +14,16 @@ pub use self::{
     and_then::AndThen,
     map::Map,
     map_err::MapErr,
+    named::Named,
     or::Or,
     or_strict::OrStrict,
     recover::Recover,
 };
 
 use {
-    super::IsEndpoint, //
+    super::{syntax::verb::MethodNotAllowed, IsEndpoint, Wrapper}, //
     crate::error::{Error, HttpError},
+    http::StatusCode,
 };
 
 /// A set of extension methods for combining the multiple endpoints.
@@ -79,10 +82,28 @@ pub trait EndpointExt: IsEndpoint + Sized {
         MapErr { endpoint: self, f }
     }
 
+    /// Create an endpoint which associates the routes of `self` with the specified name.
+    ///
+    /// The name is used to generate the path of the route by `Description::url_for`.
+    fn named(self, name: &'static str) -> Named<Self> {
+        Named {
+            endpoint: self,
+            name,
+        }
+    }
+
     #[allow(missing_docs)]
     fn recover<F>(self, f: F) -> Recover<Self, F> {
         Recover { endpoint: self, f }
     }
+
+    /// Wraps this endpoint with the specified `Wrapper`.
+    fn wrap<W>(self, wrapper: W) -> W::Endpoint
+    where
+        W: Wrapper<Self>,
+    {
+        wrapper.wrap(self)
+    }
 }
 
 impl<E: IsEndpoint> EndpointExt for E {}
@@ -103,7 +124,29 @@ pub struct NotMatched {
 }
 
 impl HttpError for NotMatched {
-    fn status_code(&self) -> http::StatusCode {
-        http::StatusCode::NOT_FOUND
+    fn status_code(&self) -> StatusCode {
+        StatusCode::NOT_FOUND
+    }
+}
+
+/// Merges the errors returned from both endpoints into a single error value.
+///
+/// If the path matched one of the endpoints but its method did not, the result
+/// is a `MethodNotAllowed` which contains all methods allowed by the endpoints.
+fn not_matched(left: Error, right: Error) -> Error {
+    let l = left.downcast_ref::<MethodNotAllowed>().map(|e| e.allowed());
+    let r = right
+        .downcast_ref::<MethodNotAllowed>()
+        .map(|e| e.allowed());
+    match (l, r) {
+        (Some(l), Some(r)) => MethodNotAllowed::new(l | r).into(),
+        (Some(..), None) if right.status_code() == StatusCode::NOT_FOUND => left,
+        (None, Some(..)) if left.status_code() == StatusCode::NOT_FOUND => right,
+        _ => NotMatched {
+            left,
+            right,
+            _priv: (),
+        }
+        .into(),
     }
 }
@@ -7,7 +7,7 @@ use {
             PreflightContext,
         },
         common::Combine,
-        endpoint::{Endpoint, IsEndpoint},
+        endpoint::{describe::Description, Endpoint, IsEndpoint},
         error::Error,
     },
     futures::{Async, Poll},
@@ -20,7 +20,11 @@ pub struct And<E1, E2> {
     pub(super) e2: E2,
 }
 
-impl<E1: IsEndpoint, E2: IsEndpoint> IsEndpoint for And<E1, E2> {}
+impl<E1: IsEndpoint, E2: IsEndpoint> IsEndpoint for And<E1, E2> {
+    fn describe(&self) -> Description {
+        self.e1.describe().and(self.e2.describe())
+    }
+}
 
 impl<E1, E2, Bd> Endpoint<Bd> for And<E1, E2>
 where
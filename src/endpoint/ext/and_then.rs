@@ -7,7 +7,7 @@ use {
             PreflightContext,
         },
         common::Func,
-        endpoint::{Endpoint, IsEndpoint},
+        endpoint::{describe::Description, Endpoint, IsEndpoint},
         error::Error,
     },
     futures::{Future, IntoFuture, Poll},
@@ -20,7 +20,11 @@ pub struct AndThen<E, F> {
     pub(super) f: F,
 }
 
-impl<E: IsEndpoint, F> IsEndpoint for AndThen<E, F> {}
+impl<E: IsEndpoint, F> IsEndpoint for AndThen<E, F> {
+    fn describe(&self) -> Description {
+        self.endpoint.describe()
+    }
+}
 
 impl<E, F, Bd, R> Endpoint<Bd> for AndThen<E, F>
 where
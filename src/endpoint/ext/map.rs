@@ -7,7 +7,7 @@ use {
             PreflightContext,
         },
         common::Func,
-        endpoint::{Endpoint, IsEndpoint},
+        endpoint::{describe::Description, Endpoint, IsEndpoint},
         error::Error,
     },
     futures::Poll,
@@ -20,7 +20,11 @@ pub struct Map<E, F> {
     pub(super) f: F,
 }
 
-impl<E: IsEndpoint, F> IsEndpoint for Map<E, F> {}
+impl<E: IsEndpoint, F> IsEndpoint for Map<E, F> {
+    fn describe(&self) -> Description {
+        self.endpoint.describe()
+    }
+}
 
 impl<E, F, Bd> Endpoint<Bd> for Map<E, F>
 where
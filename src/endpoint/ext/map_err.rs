@@ -6,7 +6,7 @@ use {
             Preflight,
             PreflightContext,
         },
-        endpoint::{Endpoint, IsEndpoint},
+        endpoint::{describe::Description, Endpoint, IsEndpoint},
         error::Error,
     },
     futures::Poll,
@@ -19,7 +19,11 @@ pub struct MapErr<E, F> {
     pub(super) f: F,
 }
 
-impl<E: IsEndpoint, F> IsEndpoint for MapErr<E, F> {}
+impl<E: IsEndpoint, F> IsEndpoint for MapErr<E, F> {
+    fn describe(&self) -> Description {
+        self.endpoint.describe()
+    }
+}
 
 impl<E, F, Bd, R> Endpoint<Bd> for MapErr<E, F>
 where
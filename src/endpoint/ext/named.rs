@@ -0,0 +1,27 @@
+use crate::endpoint::{describe::Description, Endpoint, IsEndpoint};
+
+#[allow(missing_docs)]
+#[derive(Debug, Copy, Clone)]
+pub struct Named<E> {
+    pub(super) endpoint: E,
+    pub(super) name: &'static str,
+}
+
+impl<E: IsEndpoint> IsEndpoint for Named<E> {
+    fn describe(&self) -> Description {
+        Description::Named(self.name, Box::new(self.endpoint.describe()))
+    }
+}
+
+impl<E, Bd> Endpoint<Bd> for Named<E>
+where
+    E: Endpoint<Bd>,
+{
+    type Output = E::Output;
+    type Action = E::Action;
+
+    #[inline]
+    fn action(&self) -> Self::Action {
+        self.endpoint.action()
+    }
+}
@@ -1,5 +1,5 @@
 use {
-    super::NotMatched,
+    super::not_matched,
     crate::{
         action::{
             ActionContext, //
@@ -7,7 +7,7 @@ use {
             Preflight,
             PreflightContext,
         },
-        endpoint::{Endpoint, IsEndpoint},
+        endpoint::{describe::Description, Endpoint, IsEndpoint},
         error::Error,
     },
     either::Either,
@@ -21,7 +21,11 @@ pub struct Or<E1, E2> {
     pub(super) e2: E2,
 }
 
-impl<E1: IsEndpoint, E2: IsEndpoint> IsEndpoint for Or<E1, E2> {}
+impl<E1: IsEndpoint, E2: IsEndpoint> IsEndpoint for Or<E1, E2> {
+    fn describe(&self) -> Description {
+        self.e1.describe().or(self.e2.describe())
+    }
+}
 
 impl<E1, E2, T1, T2, Bd> Endpoint<Bd> for Or<E1, E2>
 where
@@ -104,14 +108,7 @@ where
                         }
                     }
 
-                    (Err(left), Err(right)) => {
-                        return Err(NotMatched {
-                            left,
-                            right,
-                            _priv: (),
-                        }
-                        .into());
-                    }
+                    (Err(left), Err(right)) => return Err(not_matched(left, right)),
                 }
             }
             _ => panic!("unexpected condition"),
@@ -1,5 +1,5 @@
 use {
-    super::NotMatched,
+    super::not_matched,
     crate::{
         action::{
             ActionContext, //
@@ -7,7 +7,7 @@ use {
             Preflight,
             PreflightContext,
         },
-        endpoint::{Endpoint, IsEndpoint},
+        endpoint::{describe::Description, Endpoint, IsEndpoint},
         error::Error,
     },
     futures::Poll,
@@ -20,7 +20,11 @@ pub struct OrStrict<E1, E2> {
     pub(super) e2: E2,
 }
 
-impl<E1: IsEndpoint, E2: IsEndpoint> IsEndpoint for OrStrict<E1, E2> {}
+impl<E1: IsEndpoint, E2: IsEndpoint> IsEndpoint for OrStrict<E1, E2> {
+    fn describe(&self) -> Description {
+        self.e1.describe().or(self.e2.describe())
+    }
+}
 
 impl<E1, E2, Bd> Endpoint<Bd> for OrStrict<E1, E2>
 where
@@ -74,14 +78,7 @@ where
                             Ok(Preflight::Completed(output)) => {
                                 return Ok(Preflight::Completed(output));
                             }
-                            Err(e2) => {
-                                return Err(NotMatched {
-                                    left: e1,
-                                    right: e2,
-                                    _priv: (),
-                                }
-                                .into());
-                            }
+                            Err(e2) => return Err(not_matched(e1, e2)),
                         }
                     }
                 }
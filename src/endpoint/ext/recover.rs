@@ -6,7 +6,7 @@ use {
             Preflight,
             PreflightContext,
         },
-        endpoint::{Endpoint, IsEndpoint},
+        endpoint::{describe::Description, Endpoint, IsEndpoint},
         error::Error,
     },
     futures::{Future, IntoFuture, Poll},
@@ -19,7 +19,11 @@ pub struct Recover<E, F> {
     pub(super) f: F,
 }
 
-impl<E: IsEndpoint, F> IsEndpoint for Recover<E, F> {}
+impl<E: IsEndpoint, F> IsEndpoint for Recover<E, F> {
+    fn describe(&self) -> Description {
+        self.endpoint.describe()
+    }
+}
 
 impl<E, F, Bd, R> Endpoint<Bd> for Recover<E, F>
 where
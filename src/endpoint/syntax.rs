@@ -13,6 +13,7 @@ use {
     crate::{
         common::Tuple,
         endpoint::{
+            describe::Description,
             Endpoint, //
             IsEndpoint,
             Oneshot,
@@ -34,7 +35,46 @@ use {
 
 // ==== ExtractPath ====
 
+#[doc(hidden)]
+pub mod __private {
+    // used in #[derive(ExtractPath)]
+    #[cfg(feature = "path-regex")]
+    pub use regex::Regex;
+}
+
 /// A macro for creating an endpoint that matches to the specific HTTP path.
+///
+/// The segments of the path are written as follows:
+///
+/// * `foo` - a segment which is equal to `foo`. The static segments are
+///   percent-encoded as `segment()` does, e.g. `my docs` matches `my%20docs`.
+/// * `(png|jpg)` - a segment which is equal to one of the alternatives.
+/// * `<T>` - a segment parsed into a parameter of type `T`.
+/// * `<T | 1..=9999>` - a parameter whose value must be within the range.
+/// * `<T # 8>` - a parameter whose decoded segment must consist of exactly
+///   8 characters. A range such as `<T # 4..=8>` is also allowed.
+/// * `<T ~ "^[a-z0-9-]+$">` - a parameter whose decoded segment must match
+///   the regular expression. The pattern must not contain `"`, and this
+///   constraint requires the feature `path-regex`.
+/// * `<..T>` - the remaining segments parsed into a parameter of type `T`.
+///
+/// A parameter can be given a name, e.g. `<id: u32>` or `<..rest: String>`,
+/// which is used in the descriptions of the route such as the OpenAPI documents.
+///
+/// If a constraint is not satisfied, the endpoint returns a "not matched"
+/// error so that the other routes combined by `or` are tried.
+///
+/// # Example
+///
+/// ```
+/// # use finchers::prelude::*;
+/// use finchers::endpoint::syntax::path;
+///
+/// let endpoint = path!(@get "/posts/<u32 | 1..=9999>")
+///     .or(path!(@get "/posts/<String # 8>"))
+///     .or(path!(@get "/images/<String>/(png|jpg)"));
+/// # drop(endpoint);
+/// ```
 #[macro_export]
 macro_rules! path {
     ($path:expr) => {{
@@ -47,18 +87,67 @@ macro_rules! path {
 
     (@$verb:ident $path:expr) => {
         $crate::endpoint::ext::EndpointExt::and(
-            $crate::endpoint::syntax::verb::$verb(),
             $crate::endpoint::syntax::path!($path),
+            $crate::endpoint::syntax::verb::$verb(),
         )
     };
 }
 
 /// A trait that abstracts the extraction of values from HTTP path.
+///
+/// This trait is usually implemented by `#[derive(ExtractPath)]`, with the
+/// path written in the same syntax as `path!()`. If the struct has named
+/// fields, the placeholders in the path are the names of the fields instead
+/// of the types, and the struct itself is extracted.
+///
+/// The derive also generates an associated function `to_path`, which builds
+/// the path from the values of the parameters by percent-encoding each of
+/// them as a path segment. If the struct has named fields, it is a method
+/// that takes the values from the fields. It is not generated if the path
+/// contains an alternation, since the segment cannot be determined from the values.
+///
+/// # Example
+///
+/// ```
+/// # use finchers::prelude::*;
+/// use finchers::endpoint::syntax::{self, ExtractPath};
+///
+/// #[derive(ExtractPath)]
+/// #[path = "/users/<user_id>/posts/<post_id | 1..=9999>"]
+/// struct PostPath {
+///     user_id: u64,
+///     post_id: u32,
+/// }
+///
+/// let endpoint = syntax::path::<PostPath>()
+///     .map(|path: PostPath| format!("user={}, post={}", path.user_id, path.post_id));
+/// # drop(endpoint);
+///
+/// let path = PostPath {
+///     user_id: 1,
+///     post_id: 42,
+/// };
+/// assert_eq!(path.to_path(), "/users/1/posts/42");
+///
+/// #[derive(ExtractPath)]
+/// #[path = "/posts/<u64>/<String>"]
+/// struct PostLink;
+///
+/// assert_eq!(
+///     PostLink::to_path(&(42, "hello world".to_owned())),
+///     "/posts/42/hello%20world"
+/// );
+/// ```
 #[allow(missing_docs)]
 pub trait ExtractPath {
     type Output: Tuple;
 
     fn extract(cx: &mut PreflightContext<'_>) -> Result<Self::Output, ExtractPathError>;
+
+    /// Returns the descriptor of the path segments extracted by this type.
+    fn describe() -> Description {
+        Description::Opaque
+    }
 }
 
 #[allow(missing_docs)]
@@ -102,7 +191,14 @@ pub struct Path<T> {
 mod path {
     use super::*;
 
-    impl<T> IsEndpoint for Path<T> where T: ExtractPath {}
+    impl<T> IsEndpoint for Path<T>
+    where
+        T: ExtractPath,
+    {
+        fn describe(&self) -> Description {
+            T::describe()
+        }
+    }
 
     impl<T, Bd> Endpoint<Bd> for Path<T>
     where
@@ -163,7 +259,11 @@ pub struct MatchSegment {
     encoded: Arc<String>,
 }
 
-impl IsEndpoint for MatchSegment {}
+impl IsEndpoint for MatchSegment {
+    fn describe(&self) -> Description {
+        Description::Segment((*self.encoded).clone())
+    }
+}
 
 impl<Bd> Endpoint<Bd> for MatchSegment {
     type Output = ();
@@ -213,7 +313,11 @@ pub struct MatchEos {
     _priv: (),
 }
 
-impl IsEndpoint for MatchEos {}
+impl IsEndpoint for MatchEos {
+    fn describe(&self) -> Description {
+        Description::Eos
+    }
+}
 
 impl<Bd> Endpoint<Bd> for MatchEos {
     type Output = ();
@@ -277,7 +381,14 @@ impl<T> fmt::Debug for Param<T> {
     }
 }
 
-impl<T: FromEncodedStr> IsEndpoint for Param<T> {}
+impl<T: FromEncodedStr> IsEndpoint for Param<T> {
+    fn describe(&self) -> Description {
+        Description::Param {
+            name: None,
+            ty: None,
+        }
+    }
+}
 
 impl<T, Bd> Endpoint<Bd> for Param<T>
 where
@@ -351,7 +462,14 @@ impl<T> fmt::Debug for Remains<T> {
     }
 }
 
-impl<T: FromEncodedStr> IsEndpoint for Remains<T> {}
+impl<T: FromEncodedStr> IsEndpoint for Remains<T> {
+    fn describe(&self) -> Description {
+        Description::Remains {
+            name: None,
+            ty: None,
+        }
+    }
+}
 
 impl<T, Bd> Endpoint<Bd> for Remains<T>
 where
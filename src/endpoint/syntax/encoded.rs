@@ -4,11 +4,11 @@ use {
     crate::error::{Error, HttpError},
     failure::Fail,
     http::StatusCode,
-    percent_encoding::percent_decode,
+    percent_encoding::{percent_decode, utf8_percent_encode},
     std::{
         borrow::Cow,
         fmt, net,
-        path::PathBuf,
+        path::{Component, PathBuf},
         str::{self, FromStr, Utf8Error},
     },
 };
@@ -61,6 +61,26 @@ impl<'a> PartialEq<String> for &'a EncodedStr {
 }
 
 impl EncodedStr {
+    /// Create a new instance of `EncodedStr` if the given string is a
+    /// percent-encoded sequence.
+    ///
+    /// The string must consist of the visible ASCII characters, and each `%`
+    /// must be followed by two hexadecimal digits.
+    pub fn new(s: &(impl AsRef<[u8]> + ?Sized)) -> Option<&EncodedStr> {
+        let bytes = (*s).as_ref();
+        let is_valid = bytes.iter().enumerate().all(|(i, &b)| match b {
+            b'%' => bytes
+                .get(i + 1..i + 3)
+                .map_or(false, |hex| hex.iter().all(|b| b.is_ascii_hexdigit())),
+            b => b > 0x20 && b < 0x7F,
+        });
+        if is_valid {
+            Some(unsafe { Self::new_unchecked(bytes) })
+        } else {
+            None
+        }
+    }
+
     /// Create a new instance of `EncodedStr` from an encoded `str`.
     ///
     /// # Safety
@@ -149,6 +169,98 @@ impl FromEncodedStr for PathBuf {
     }
 }
 
+percent_encoding::define_encode_set! {
+    /// The encode set for the values of path parameters.
+    ///
+    /// In addition to `SEGMENT_ENCODE_SET`, `%` is encoded so that the value
+    /// is restored by `FromEncodedStr`.
+    #[doc(hidden)]
+    pub PARAM_ENCODE_SET = [super::SEGMENT_ENCODE_SET] | {'%'}
+}
+
+/// Trait representing the conversion into a percent-encoded path segment,
+/// the reverse of `FromEncodedStr`.
+pub trait ToEncodedStr {
+    /// Converts `self` into a percent-encoded string.
+    fn to_encoded_str(&self) -> String;
+}
+
+macro_rules! impl_to_encoded_str_display {
+    ($($t:ty,)*) => {$(
+        impl ToEncodedStr for $t {
+            #[inline]
+            fn to_encoded_str(&self) -> String {
+                utf8_percent_encode(&self.to_string(), PARAM_ENCODE_SET).to_string()
+            }
+        }
+    )*};
+}
+
+impl_to_encoded_str_display! {
+    bool, f32, f64,
+    i8, i16, i32, i64, isize,
+    u8, u16, u32, u64, usize,
+    net::IpAddr,
+    net::Ipv4Addr,
+    net::Ipv6Addr,
+    net::SocketAddr,
+    net::SocketAddrV4,
+    net::SocketAddrV6,
+}
+
+impl ToEncodedStr for String {
+    #[inline]
+    fn to_encoded_str(&self) -> String {
+        utf8_percent_encode(self, PARAM_ENCODE_SET).to_string()
+    }
+}
+
+impl ToEncodedStr for PathBuf {
+    /// Encodes each normal component of the path and joins them with `/`.
+    fn to_encoded_str(&self) -> String {
+        self.components()
+            .filter_map(|c| match c {
+                Component::Normal(s) => Some(s.to_string_lossy()),
+                _ => None,
+            })
+            .map(|s| utf8_percent_encode(&s, PARAM_ENCODE_SET).to_string())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+/// Trait representing a tuple of the path parameters which can be converted
+/// into the percent-encoded path segments.
+pub trait ToPathParams {
+    /// Converts the elements into the percent-encoded strings.
+    fn to_path_params(&self) -> Vec<String>;
+}
+
+impl ToPathParams for () {
+    fn to_path_params(&self) -> Vec<String> {
+        vec![]
+    }
+}
+
+macro_rules! impl_to_path_params {
+    () => {};
+    ($H:ident, $($T:ident,)*) => {
+        impl<$H: ToEncodedStr, $($T: ToEncodedStr),*> ToPathParams for ($H, $($T,)*) {
+            fn to_path_params(&self) -> Vec<String> {
+                #[allow(non_snake_case)]
+                let ($H, $($T,)*) = self;
+                vec![$H.to_encoded_str(), $($T.to_encoded_str()),*]
+            }
+        }
+
+        impl_to_path_params!($($T,)*);
+    };
+}
+
+impl_to_path_params! {
+    T15, T14, T13, T12, T11, T10, T9, T8, T7, T6, T5, T4, T3, T2, T1, T0,
+}
+
 #[allow(missing_docs)]
 #[derive(Debug, Fail)]
 #[fail(display = "failed to decode a percent encoded string to UTF-8")]
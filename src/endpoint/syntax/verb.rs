@@ -3,16 +3,20 @@
 use {
     crate::{
         endpoint::{
+            describe::Description,
             Endpoint,
             IsEndpoint,
             Oneshot,
             OneshotAction,
             PreflightContext, //
         },
-        error::Error,
+        error::{Error, HttpError},
+    },
+    http::{header, header::HeaderValue, Method, Request, Response, StatusCode},
+    std::{
+        fmt,
+        ops::{BitAnd, BitOr, BitOrAssign},
     },
-    http::Method,
-    std::ops::{BitOr, BitOrAssign},
 };
 
 /// Create an endpoint which checks if the verb of current request
@@ -27,7 +31,11 @@ pub struct MatchVerbs {
     allowed: Verbs,
 }
 
-impl IsEndpoint for MatchVerbs {}
+impl IsEndpoint for MatchVerbs {
+    fn describe(&self) -> Description {
+        Description::Verbs(self.allowed)
+    }
+}
 
 impl<Bd> Endpoint<Bd> for MatchVerbs {
     type Output = ();
@@ -51,14 +59,25 @@ impl OneshotAction for MatchVerbsAction {
     type Output = ();
 
     fn preflight(self, cx: &mut PreflightContext<'_>) -> Result<Self::Output, Error> {
-        if self.allowed.contains(cx.method()) {
+        if is_allowed(self.allowed, cx) {
             Ok(())
         } else {
-            Err(crate::error::method_not_allowed("invalid method"))
+            Err(MethodNotAllowed::new(self.allowed).into())
         }
     }
 }
 
+/// Returns whether the method of the current request is in the allowed set.
+///
+/// If the automatic `HEAD` handling is enabled, a `HEAD` request is also
+/// accepted by the routes which allow `GET`.
+fn is_allowed(allowed: Verbs, cx: &PreflightContext<'_>) -> bool {
+    allowed.contains(cx.method())
+        || (*cx.method() == Method::HEAD
+            && cx.auto_head_and_options()
+            && allowed.contains(&Method::GET))
+}
+
 macro_rules! define_verbs {
     ($(
         ($name:ident, $METHOD:ident, $Endpoint:ident, $Action:ident),
@@ -78,7 +97,11 @@ macro_rules! define_verbs {
             _priv: (),
         }
 
-        impl IsEndpoint for $Endpoint {}
+        impl IsEndpoint for $Endpoint {
+            fn describe(&self) -> Description {
+                Description::Verbs(Verbs::$METHOD)
+            }
+        }
 
         impl<Bd> Endpoint<Bd> for $Endpoint {
             type Output = ();
@@ -99,10 +122,10 @@ macro_rules! define_verbs {
 
             #[inline]
             fn preflight(self, cx: &mut PreflightContext<'_>) -> Result<Self::Output, Error> {
-                if *cx.method() == Method::$METHOD {
+                if is_allowed(Verbs::$METHOD, cx) {
                     Ok(())
                 } else {
-                    Err(crate::error::method_not_allowed("invalid method"))
+                    Err(MethodNotAllowed::new(Verbs::$METHOD).into())
                 }
             }
         }
@@ -121,8 +144,52 @@ define_verbs! {
     (trace, TRACE, MatchVerbTrace, MatchVerbTraceAction),
 }
 
+/// An `HttpError` indicating that the path was matched but the method was not.
+///
+/// The response created from this error contains the header field `Allow`
+/// with the list of allowed methods.
+#[derive(Debug, failure::Fail)]
+#[fail(display = "method not allowed")]
+pub struct MethodNotAllowed {
+    allowed: Verbs,
+}
+
+impl MethodNotAllowed {
+    /// Create a new `MethodNotAllowed` with the specified set of allowed methods.
+    pub fn new(allowed: Verbs) -> Self {
+        MethodNotAllowed { allowed }
+    }
+
+    /// Returns the set of allowed methods.
+    pub fn allowed(&self) -> Verbs {
+        self.allowed
+    }
+
+    /// Returns a mutable reference to the set of allowed methods.
+    pub fn allowed_mut(&mut self) -> &mut Verbs {
+        &mut self.allowed
+    }
+}
+
+impl HttpError for MethodNotAllowed {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::METHOD_NOT_ALLOWED
+    }
+
+    fn to_response(&self, _: &Request<()>) -> Response<()> {
+        let mut response = Response::new(());
+        *response.status_mut() = self.status_code();
+        response.headers_mut().insert(
+            header::ALLOW,
+            HeaderValue::from_str(&self.allowed.to_string())
+                .expect("should be a valid header value"),
+        );
+        response
+    }
+}
+
 /// A collection type which represents a set of allowed HTTP methods.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Verbs(Methods);
 
 bitflags::bitflags! {
@@ -187,6 +254,19 @@ impl Verbs {
     }
 }
 
+impl fmt::Display for Verbs {
+    /// Formats the contained methods as a comma-separated list, e.g. `GET, POST`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, method) in self.into_iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            f.write_str(method.as_str())?;
+        }
+        Ok(())
+    }
+}
+
 impl BitOr for Verbs {
     type Output = Verbs;
 
@@ -196,6 +276,15 @@ impl BitOr for Verbs {
     }
 }
 
+impl BitAnd for Verbs {
+    type Output = Verbs;
+
+    #[inline]
+    fn bitand(self, other: Verbs) -> Self::Output {
+        Verbs(self.0 & other.0)
+    }
+}
+
 impl BitOrAssign for Verbs {
     #[inline]
     fn bitor_assign(&mut self, other: Verbs) {
@@ -232,11 +321,11 @@ impl Iterator for VerbsIter {
             )*}
         }
         loop {
-            let masked = self.allowed & self.cursor;
-            self.cursor = Methods::from_bits_truncate(self.cursor.bits() << 1);
             if self.cursor.is_empty() {
                 return None;
             }
+            let masked = self.allowed & self.cursor;
+            self.cursor = Methods::from_bits_truncate(self.cursor.bits() << 1);
             dump_method!(masked => [
                 GET,
                 POST,
@@ -267,4 +356,10 @@ mod tests {
         let methods: Vec<Method> = (Verbs::GET | Verbs::POST).into_iter().cloned().collect();
         assert_eq!(methods, vec![Method::GET, Method::POST]);
     }
+
+    #[test]
+    fn test_methods_display() {
+        assert_eq!(Verbs::GET.to_string(), "GET");
+        assert_eq!((Verbs::GET | Verbs::POST).to_string(), "GET, POST");
+    }
 }
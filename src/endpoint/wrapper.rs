@@ -0,0 +1,44 @@
+//! The definition of `Wrapper` and related components.
+
+use super::IsEndpoint;
+
+/// A trait representing a middleware which wraps an endpoint and modifies its behavior.
+///
+/// A wrapper is typically used for implementing cross-cutting concerns (e.g. CORS,
+/// compression or authentication) once and applying them to a whole tree of endpoints.
+/// The wrapped endpoint is able to access the request context in the actions of
+/// the inner endpoint, and to modify their output values.
+///
+/// The implementors of this trait are applied via `EndpointExt::wrap`.
+pub trait Wrapper<E: IsEndpoint> {
+    /// The type of endpoint returned from `wrap`.
+    type Endpoint: IsEndpoint;
+
+    /// Wraps the specified endpoint and returns a new endpoint.
+    fn wrap(self, endpoint: E) -> Self::Endpoint;
+}
+
+/// Create a `Wrapper` from the specified function.
+pub fn wrapper_fn<F>(f: F) -> WrapperFn<F> {
+    WrapperFn { f }
+}
+
+#[allow(missing_docs)]
+#[derive(Debug, Copy, Clone)]
+pub struct WrapperFn<F> {
+    f: F,
+}
+
+impl<E, F, T> Wrapper<E> for WrapperFn<F>
+where
+    E: IsEndpoint,
+    F: FnOnce(E) -> T,
+    T: IsEndpoint,
+{
+    type Endpoint = T;
+
+    #[inline]
+    fn wrap(self, endpoint: E) -> Self::Endpoint {
+        (self.f)(endpoint)
+    }
+}
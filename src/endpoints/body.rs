@@ -8,17 +8,34 @@ use {
             Preflight,
             PreflightContext,
         },
-        endpoint::{Endpoint, IsEndpoint},
-        error::{self, Error},
+        endpoint::{
+            describe::{Description, TypeSchema},
+            Endpoint, IsEndpoint,
+        },
+        error::{self, Error, HttpError},
+        openapi::Schema,
     },
     futures::Poll,
-    http::Request,
+    http::{header, Request, StatusCode},
     izanami_util::buf_stream::BufStream,
     mime::Mime,
     serde::de::DeserializeOwned,
     std::{cell::UnsafeCell, marker::PhantomData},
 };
 
+mod multipart;
+
+pub use self::multipart::{
+    multipart,
+    multipart_form,
+    Multipart,
+    MultipartForm,
+    Part,
+    PartBody,
+    Parts,
+    UploadedFile, //
+};
+
 fn content_type<T>(request: &Request<T>) -> crate::error::Result<Option<Mime>> {
     if let Some(h) = request.headers().get(http::header::CONTENT_TYPE) {
         let mime = h
@@ -48,7 +65,14 @@ pub struct Raw(());
 mod raw {
     use super::*;
 
-    impl IsEndpoint for Raw {}
+    impl IsEndpoint for Raw {
+        fn describe(&self) -> Description {
+            Description::Body {
+                kind: "raw",
+                schema: None,
+            }
+        }
+    }
 
     impl<Bd> Endpoint<Bd> for Raw {
         type Output = (Bd,);
@@ -75,24 +99,82 @@ mod raw {
     }
 }
 
+// ==== PayloadTooLarge ====
+
+/// An `HttpError` indicating that the size of request body exceeds the limit.
+#[derive(Debug, failure::Fail)]
+#[fail(display = "the request body is too large (limit: {} bytes)", limit)]
+pub struct PayloadTooLarge {
+    limit: u64,
+}
+
+impl PayloadTooLarge {
+    /// Returns the limit of the request body size, in bytes.
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+}
+
+impl HttpError for PayloadTooLarge {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::PAYLOAD_TOO_LARGE
+    }
+}
+
+/// Rejects the request early if the declared size already exceeds the limit.
+fn check_content_length(cx: &PreflightContext<'_>, limit: u64) -> Result<(), Error> {
+    let content_length = cx
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|s| s.parse::<u64>().ok());
+    if content_length.map_or(false, |len| len > limit) {
+        return Err(PayloadTooLarge { limit }.into());
+    }
+    Ok(())
+}
+
+// ==== ReceiveAll ====
+
 /// Creates an endpoint which receives all of request body.
 ///
 /// If the instance of `BufStream` has already been stolen by another endpoint, it will
 /// return an error.
+///
+/// The size of request body is limited by the value specified by `limit`, or the
+/// application-wide default if not specified. If the limit is exceeded, it returns
+/// an error with the status code `413 Payload Too Large`.
 #[inline]
 pub fn receive_all() -> ReceiveAll {
-    ReceiveAll(())
+    ReceiveAll { limit: None }
 }
 
 #[allow(missing_docs)]
 #[derive(Debug)]
-pub struct ReceiveAll(());
+pub struct ReceiveAll {
+    limit: Option<u64>,
+}
+
+impl ReceiveAll {
+    /// Sets the maximum size of request body, in bytes.
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
 
 mod receive_all {
     use super::*;
     use bytes::Buf;
 
-    impl IsEndpoint for ReceiveAll {}
+    impl IsEndpoint for ReceiveAll {
+        fn describe(&self) -> Description {
+            Description::Body {
+                kind: "bytes",
+                schema: None,
+            }
+        }
+    }
 
     impl<Bd> Endpoint<Bd> for ReceiveAll
     where
@@ -103,13 +185,14 @@ mod receive_all {
         type Action = ReceiveAllAction<Bd>;
 
         fn action(&self) -> Self::Action {
-            new_action()
+            new_action(self.limit)
         }
     }
 
     #[allow(missing_debug_implementations)]
     pub struct ReceiveAllAction<Bd> {
         state: State<Bd>,
+        limit: Option<u64>,
     }
 
     #[allow(missing_debug_implementations)]
@@ -125,6 +208,21 @@ mod receive_all {
     {
         type Output = (Vec<u8>,);
 
+        fn preflight(
+            &mut self,
+            cx: &mut PreflightContext<'_>,
+        ) -> Result<Preflight<Self::Output>, Error> {
+            if self.limit.is_none() {
+                self.limit = cx.body_limit();
+            }
+
+            if let Some(limit) = self.limit {
+                check_content_length(cx, limit)?;
+            }
+
+            Ok(Preflight::Incomplete)
+        }
+
         fn poll_action(&mut self, cx: &mut ActionContext<'_, Bd>) -> Poll<Self::Output, Error> {
             loop {
                 self.state = match self.state {
@@ -138,6 +236,11 @@ mod receive_all {
                             .map_err(|e| failure::Error::from_boxed_compat(e.into())))
                         {
                             buf.extend_from_slice(data.bytes());
+                            if let Some(limit) = self.limit {
+                                if buf.len() as u64 > limit {
+                                    return Err(PayloadTooLarge { limit }.into());
+                                }
+                            }
                         }
                         let buf = std::mem::replace(buf, Vec::new());
                         return Ok((buf,).into());
@@ -147,12 +250,13 @@ mod receive_all {
         }
     }
 
-    pub(super) fn new_action<Bd>() -> ReceiveAllAction<Bd>
+    pub(super) fn new_action<Bd>(limit: Option<u64>) -> ReceiveAllAction<Bd>
     where
         Bd: BufStream,
     {
         ReceiveAllAction {
             state: State::Start,
+            limit,
         }
     }
 }
@@ -173,10 +277,26 @@ pub struct Text {
     receive_all: ReceiveAll,
 }
 
+impl Text {
+    /// Sets the maximum size of request body, in bytes.
+    pub fn limit(self, limit: u64) -> Self {
+        Text {
+            receive_all: self.receive_all.limit(limit),
+        }
+    }
+}
+
 mod text {
     use super::*;
 
-    impl IsEndpoint for Text {}
+    impl IsEndpoint for Text {
+        fn describe(&self) -> Description {
+            Description::Body {
+                kind: "text",
+                schema: None,
+            }
+        }
+    }
 
     impl<Bd> Endpoint<Bd> for Text
     where
@@ -188,7 +308,7 @@ mod text {
 
         fn action(&self) -> Self::Action {
             TextAction {
-                receive_all: super::receive_all::new_action(),
+                receive_all: super::receive_all::new_action(self.receive_all.limit),
             }
         }
     }
@@ -222,7 +342,9 @@ mod text {
                 }
             }
 
-            Ok(Preflight::Incomplete)
+            self.receive_all
+                .preflight(cx)
+                .map(|_| Preflight::Incomplete)
         }
 
         fn poll_action(&mut self, cx: &mut ActionContext<'_, Bd>) -> Poll<Self::Output, Error> {
@@ -242,26 +364,55 @@ where
     T: DeserializeOwned,
 {
     Json {
+        limit: None,
+        schema: None,
         _marker: PhantomData,
     }
 }
 
 #[allow(missing_docs)]
 pub struct Json<T> {
+    limit: Option<u64>,
+    schema: Option<TypeSchema>,
     _marker: PhantomData<fn() -> T>,
 }
 
+impl<T> Json<T> {
+    /// Sets the maximum size of request body, in bytes.
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
+impl<T: Schema> Json<T> {
+    /// Attaches the schema of `T` to the description of this endpoint.
+    ///
+    /// The schema is used as the one of the request body in the OpenAPI document.
+    pub fn with_schema(mut self) -> Self {
+        self.schema = Some(TypeSchema::new(T::name(), T::schema));
+        self
+    }
+}
+
 mod json {
     use super::*;
     use std::fmt;
 
     impl<T> fmt::Debug for Json<T> {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            f.debug_struct("Json").finish()
+            f.debug_struct("Json").field("limit", &self.limit).finish()
         }
     }
 
-    impl<T: DeserializeOwned> IsEndpoint for Json<T> {}
+    impl<T: DeserializeOwned> IsEndpoint for Json<T> {
+        fn describe(&self) -> Description {
+            Description::Body {
+                kind: "json",
+                schema: self.schema,
+            }
+        }
+    }
 
     impl<T, Bd> Endpoint<Bd> for Json<T>
     where
@@ -274,7 +425,7 @@ mod json {
 
         fn action(&self) -> Self::Action {
             JsonAction {
-                receive_all: super::receive_all::new_action(),
+                receive_all: super::receive_all::new_action(self.limit),
                 _marker: PhantomData,
             }
         }
@@ -310,7 +461,9 @@ mod json {
                 ));
             }
 
-            Ok(Preflight::Incomplete)
+            self.receive_all
+                .preflight(cx)
+                .map(|_| Preflight::Incomplete)
         }
 
         fn poll_action(&mut self, cx: &mut ActionContext<'_, Bd>) -> Poll<Self::Output, Error> {
@@ -331,26 +484,45 @@ where
     T: DeserializeOwned,
 {
     Urlencoded {
+        limit: None,
         _marker: PhantomData,
     }
 }
 
 #[allow(missing_docs)]
 pub struct Urlencoded<T> {
+    limit: Option<u64>,
     _marker: PhantomData<fn() -> T>,
 }
 
+impl<T> Urlencoded<T> {
+    /// Sets the maximum size of request body, in bytes.
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
 mod urlencoded {
     use super::*;
     use {failure::SyncFailure, std::fmt};
 
     impl<T> fmt::Debug for Urlencoded<T> {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            f.debug_struct("Json").finish()
+            f.debug_struct("Urlencoded")
+                .field("limit", &self.limit)
+                .finish()
         }
     }
 
-    impl<T: DeserializeOwned> IsEndpoint for Urlencoded<T> {}
+    impl<T: DeserializeOwned> IsEndpoint for Urlencoded<T> {
+        fn describe(&self) -> Description {
+            Description::Body {
+                kind: "urlencoded",
+                schema: None,
+            }
+        }
+    }
 
     impl<T, Bd> Endpoint<Bd> for Urlencoded<T>
     where
@@ -363,7 +535,7 @@ mod urlencoded {
 
         fn action(&self) -> Self::Action {
             UrlencodedAction {
-                receive_all: super::receive_all::new_action(),
+                receive_all: super::receive_all::new_action(self.limit),
                 _marker: PhantomData,
             }
         }
@@ -399,7 +571,9 @@ mod urlencoded {
                 ));
             }
 
-            Ok(Preflight::Incomplete)
+            self.receive_all
+                .preflight(cx)
+                .map(|_| Preflight::Incomplete)
         }
 
         fn poll_action(&mut self, cx: &mut ActionContext<'_, Bd>) -> Poll<Self::Output, Error> {
@@ -0,0 +1,1196 @@
+mod de;
+
+use {
+    super::{check_content_length, content_type, PayloadTooLarge},
+    crate::{
+        action::{
+            ActionContext, //
+            EndpointAction,
+            Preflight,
+            PreflightContext,
+        },
+        endpoint::{describe::Description, Endpoint, IsEndpoint},
+        error::{self, Error},
+    },
+    bytes::{Buf, Bytes, BytesMut},
+    futures::{Async, Future, Poll, Stream},
+    http::{
+        header::{self, HeaderMap, HeaderName, HeaderValue},
+        StatusCode,
+    },
+    izanami_util::buf_stream::BufStream,
+    mime::Mime,
+    serde::de::{self as de, Deserialize, DeserializeOwned, Deserializer},
+    std::{
+        collections::hash_map::{DefaultHasher, RandomState},
+        fmt, fs,
+        hash::{BuildHasher, Hash, Hasher},
+        io,
+        marker::PhantomData,
+        mem,
+        path::{Path, PathBuf},
+        process, str,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex, MutexGuard,
+        },
+        time::{SystemTime, UNIX_EPOCH},
+    },
+    tokio::{
+        fs::{
+            file::{File, OpenFuture},
+            OpenOptions,
+        },
+        io::AsyncWrite,
+    },
+};
+
+/// The maximum size of header section in each part.
+const MAX_HEADERS_SIZE: usize = 8 * 1024;
+
+fn boundary(cx: &PreflightContext<'_>) -> Result<String, Error> {
+    let mime = content_type(cx)? //
+        .ok_or_else(|| error::bad_request("missing content type"))?;
+    if mime.type_() != mime::MULTIPART || mime.subtype() != mime::FORM_DATA {
+        return Err(error::bad_request(
+            "The value of `Content-type` must be `multipart/form-data`.",
+        ));
+    }
+    mime.get_param(mime::BOUNDARY)
+        .map(|boundary| boundary.as_str().to_owned())
+        .ok_or_else(|| error::bad_request("missing boundary in `Content-type`"))
+}
+
+fn payload_too_large(msg: &'static str) -> Error {
+    error::err_msg(msg, StatusCode::PAYLOAD_TOO_LARGE)
+}
+
+// ==== Multipart ====
+
+/// Create an endpoint which parses the request body as `multipart/form-data`.
+///
+/// The returned value is a `Stream` of the parts in the message body. Since the
+/// parts share the underlying request body, the body of each part must be read
+/// before polling the next part. The unread data is skipped.
+#[inline]
+pub fn multipart() -> Multipart {
+    Multipart(())
+}
+
+#[allow(missing_docs)]
+#[derive(Debug)]
+pub struct Multipart(());
+
+impl IsEndpoint for Multipart {
+    fn describe(&self) -> Description {
+        Description::Body {
+            kind: "multipart",
+            schema: None,
+        }
+    }
+}
+
+impl<Bd> Endpoint<Bd> for Multipart
+where
+    Bd: BufStream,
+    Bd::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+{
+    type Output = (Parts<Bd>,);
+    type Action = MultipartAction<Bd>;
+
+    fn action(&self) -> Self::Action {
+        MultipartAction {
+            boundary: None,
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[allow(missing_debug_implementations)]
+pub struct MultipartAction<Bd> {
+    boundary: Option<String>,
+    _marker: PhantomData<fn(Bd)>,
+}
+
+impl<Bd> EndpointAction<Bd> for MultipartAction<Bd>
+where
+    Bd: BufStream,
+    Bd::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+{
+    type Output = (Parts<Bd>,);
+
+    fn preflight(
+        &mut self,
+        cx: &mut PreflightContext<'_>,
+    ) -> Result<Preflight<Self::Output>, Error> {
+        self.boundary = Some(boundary(cx)?);
+        Ok(Preflight::Incomplete)
+    }
+
+    fn poll_action(&mut self, cx: &mut ActionContext<'_, Bd>) -> Poll<Self::Output, Error> {
+        let body = cx.take_body()?;
+        let boundary = self.boundary.take().expect("the action has already polled");
+        Ok((Parts::new(body, &boundary, None),).into())
+    }
+}
+
+/// A `Stream` of the parts in a `multipart/form-data` message body.
+pub struct Parts<Bd> {
+    parser: Arc<Mutex<Parser<Bd>>>,
+}
+
+impl<Bd> fmt::Debug for Parts<Bd> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Parts").finish()
+    }
+}
+
+impl<Bd> Parts<Bd> {
+    fn new(body: Bd, boundary: &str, limit: Option<u64>) -> Self {
+        Parts {
+            parser: Arc::new(Mutex::new(Parser::new(body, boundary, limit))),
+        }
+    }
+}
+
+impl<Bd> Stream for Parts<Bd>
+where
+    Bd: BufStream,
+    Bd::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+{
+    type Item = Part<Bd>;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        let (headers, index) = {
+            let mut parser = lock(&self.parser);
+            match futures::try_ready!(parser.poll_next_part()) {
+                Some(headers) => (headers, parser.index),
+                None => return Ok(Async::Ready(None)),
+            }
+        };
+        let body = PartBody {
+            parser: self.parser.clone(),
+            index,
+        };
+        Part::new(headers, body).map(|part| Async::Ready(Some(part)))
+    }
+}
+
+/// A part in a `multipart/form-data` message body.
+pub struct Part<Bd> {
+    headers: HeaderMap,
+    name: Option<String>,
+    filename: Option<String>,
+    content_type: Option<Mime>,
+    body: PartBody<Bd>,
+}
+
+impl<Bd> fmt::Debug for Part<Bd> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Part")
+            .field("headers", &self.headers)
+            .field("name", &self.name)
+            .field("filename", &self.filename)
+            .field("content_type", &self.content_type)
+            .finish()
+    }
+}
+
+impl<Bd> Part<Bd> {
+    fn new(headers: HeaderMap, body: PartBody<Bd>) -> Result<Self, Error> {
+        let (name, filename) = match headers.get(header::CONTENT_DISPOSITION) {
+            Some(h) => {
+                let h = str::from_utf8(h.as_bytes()).map_err(error::bad_request)?;
+                parse_content_disposition(h)
+            }
+            None => (None, None),
+        };
+        let content_type = match headers.get(header::CONTENT_TYPE) {
+            Some(h) => Some(
+                h.to_str()
+                    .map_err(error::bad_request)?
+                    .parse()
+                    .map_err(error::bad_request)?,
+            ),
+            None => None,
+        };
+        Ok(Part {
+            headers,
+            name,
+            filename,
+            content_type,
+            body,
+        })
+    }
+
+    /// Returns the header fields of this part.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Returns the field name specified in `Content-Disposition`.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_ref().map(|s| s.as_str())
+    }
+
+    /// Returns the file name specified in `Content-Disposition`.
+    pub fn filename(&self) -> Option<&str> {
+        self.filename.as_ref().map(|s| s.as_str())
+    }
+
+    /// Returns the media type of this part, if specified.
+    pub fn content_type(&self) -> Option<&Mime> {
+        self.content_type.as_ref()
+    }
+
+    /// Returns a mutable reference to the body of this part.
+    pub fn body_mut(&mut self) -> &mut PartBody<Bd> {
+        &mut self.body
+    }
+
+    /// Consumes itself and returns the body of this part.
+    pub fn into_body(self) -> PartBody<Bd> {
+        self.body
+    }
+}
+
+/// The body of a part in a `multipart/form-data` message body.
+///
+/// After the next part is polled, this stream returns no more data.
+pub struct PartBody<Bd> {
+    parser: Arc<Mutex<Parser<Bd>>>,
+    index: usize,
+}
+
+impl<Bd> fmt::Debug for PartBody<Bd> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PartBody")
+            .field("index", &self.index)
+            .finish()
+    }
+}
+
+impl<Bd> BufStream for PartBody<Bd>
+where
+    Bd: BufStream,
+    Bd::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+{
+    type Item = io::Cursor<Bytes>;
+    type Error = Error;
+
+    fn poll_buf(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        let mut parser = lock(&self.parser);
+        if parser.index != self.index || parser.state != ParserState::Body {
+            return Ok(Async::Ready(None));
+        }
+        parser
+            .poll_data()
+            .map(|x| x.map(|chunk| chunk.map(io::Cursor::new)))
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+// ==== Parser ====
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum ParserState {
+    Preamble,
+    Delimiter,
+    Headers,
+    Body,
+    Done,
+}
+
+#[allow(missing_debug_implementations)]
+struct Parser<Bd> {
+    body: Bd,
+    eof: bool,
+    limit: Option<u64>,
+    received: u64,
+    buf: BytesMut,
+    delimiter: Bytes,
+    state: ParserState,
+    index: usize,
+}
+
+impl<Bd> Parser<Bd> {
+    fn new(body: Bd, boundary: &str, limit: Option<u64>) -> Self {
+        let mut delimiter = BytesMut::with_capacity(boundary.len() + 4);
+        delimiter.extend_from_slice(b"\r\n--");
+        delimiter.extend_from_slice(boundary.as_bytes());
+
+        // The first delimiter may not be preceded by CRLF.
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"\r\n");
+
+        Parser {
+            body,
+            eof: false,
+            limit,
+            received: 0,
+            buf,
+            delimiter: delimiter.freeze(),
+            state: ParserState::Preamble,
+            index: 0,
+        }
+    }
+
+    /// Returns the length of buffered data which cannot contain a delimiter.
+    fn safe_len(&self) -> usize {
+        self.buf
+            .len()
+            .saturating_sub(self.delimiter.len().saturating_sub(1))
+    }
+}
+
+impl<Bd> Parser<Bd>
+where
+    Bd: BufStream,
+    Bd::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+{
+    fn fill_buf(&mut self) -> Poll<(), Error> {
+        if self.eof {
+            return Err(error::bad_request("unexpected end of multipart body"));
+        }
+        let polled = futures::try_ready!(self
+            .body
+            .poll_buf()
+            .map_err(|e| failure::Error::from_boxed_compat(e.into())));
+        match polled {
+            Some(mut chunk) => {
+                self.received += chunk.remaining() as u64;
+                if let Some(limit) = self.limit {
+                    if self.received > limit {
+                        return Err(PayloadTooLarge { limit }.into());
+                    }
+                }
+                while chunk.has_remaining() {
+                    let n = {
+                        let bytes = chunk.bytes();
+                        self.buf.extend_from_slice(bytes);
+                        bytes.len()
+                    };
+                    chunk.advance(n);
+                }
+            }
+            None => self.eof = true,
+        }
+        Ok(Async::Ready(()))
+    }
+
+    /// Skips the remaining data until the next part and returns its header fields.
+    fn poll_next_part(&mut self) -> Poll<Option<HeaderMap>, Error> {
+        loop {
+            match self.state {
+                ParserState::Preamble | ParserState::Body => {
+                    match find(&self.buf, &self.delimiter) {
+                        Some(pos) => {
+                            self.buf.advance(pos + self.delimiter.len());
+                            self.state = ParserState::Delimiter;
+                            continue;
+                        }
+                        None => {
+                            let n = self.safe_len();
+                            self.buf.advance(n);
+                        }
+                    }
+                }
+                ParserState::Delimiter => {
+                    if self.buf.starts_with(b"--") {
+                        self.buf.clear();
+                        self.state = ParserState::Done;
+                        continue;
+                    }
+                    if let Some(pos) = find(&self.buf, b"\r\n") {
+                        // Ignores the transport padding after the delimiter.
+                        if self.buf[..pos].iter().any(|&b| b != b' ' && b != b'\t') {
+                            return Err(error::bad_request("malformed multipart delimiter"));
+                        }
+                        self.buf.advance(pos + 2);
+                        self.state = ParserState::Headers;
+                        continue;
+                    }
+                    if self.buf.len() > MAX_HEADERS_SIZE {
+                        return Err(error::bad_request("malformed multipart delimiter"));
+                    }
+                }
+                ParserState::Headers => {
+                    let headers = if self.buf.starts_with(b"\r\n") {
+                        self.buf.advance(2);
+                        Some(HeaderMap::new())
+                    } else if let Some(pos) = find(&self.buf, b"\r\n\r\n") {
+                        let headers = parse_headers(&self.buf[..pos])?;
+                        self.buf.advance(pos + 4);
+                        Some(headers)
+                    } else if self.buf.len() > MAX_HEADERS_SIZE {
+                        return Err(payload_too_large("too large header section in a part"));
+                    } else {
+                        None
+                    };
+                    if let Some(headers) = headers {
+                        self.state = ParserState::Body;
+                        self.index += 1;
+                        return Ok(Async::Ready(Some(headers)));
+                    }
+                }
+                ParserState::Done => return Ok(Async::Ready(None)),
+            }
+
+            futures::try_ready!(self.fill_buf());
+        }
+    }
+
+    /// Receives a chunk of the body in the current part.
+    fn poll_data(&mut self) -> Poll<Option<Bytes>, Error> {
+        debug_assert_eq!(self.state, ParserState::Body);
+        loop {
+            match find(&self.buf, &self.delimiter) {
+                Some(0) => {
+                    self.buf.advance(self.delimiter.len());
+                    self.state = ParserState::Delimiter;
+                    return Ok(Async::Ready(None));
+                }
+                Some(pos) => return Ok(Async::Ready(Some(self.buf.split_to(pos).freeze()))),
+                None => {
+                    let n = self.safe_len();
+                    if n > 0 {
+                        return Ok(Async::Ready(Some(self.buf.split_to(n).freeze())));
+                    }
+                }
+            }
+
+            futures::try_ready!(self.fill_buf());
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if haystack.len() < needle.len() {
+        return None;
+    }
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn parse_headers(raw: &[u8]) -> Result<HeaderMap, Error> {
+    let raw = str::from_utf8(raw).map_err(error::bad_request)?;
+    let mut headers = HeaderMap::new();
+    for line in raw.split("\r\n") {
+        let colon = line
+            .find(':')
+            .ok_or_else(|| error::bad_request("malformed header field in a part"))?;
+        let name =
+            HeaderName::from_bytes(line[..colon].trim().as_bytes()).map_err(error::bad_request)?;
+        let value = HeaderValue::from_bytes(line[colon + 1..].trim().as_bytes())
+            .map_err(error::bad_request)?;
+        headers.append(name, value);
+    }
+    Ok(headers)
+}
+
+/// Extracts the parameters `name` and `filename` from the value of `Content-Disposition`.
+fn parse_content_disposition(s: &str) -> (Option<String>, Option<String>) {
+    let mut name = None;
+    let mut filename = None;
+    let mut filename_ext = None;
+
+    for param in split_params(s).into_iter().skip(1) {
+        let eq = match param.find('=') {
+            Some(eq) => eq,
+            None => continue,
+        };
+        let key = param[..eq].trim().to_ascii_lowercase();
+        let value = param[eq + 1..].trim();
+        match &*key {
+            "name" => name = Some(unquote(value)),
+            "filename" => filename = Some(unquote(value)),
+            "filename*" => filename_ext = decode_ext_value(value),
+            _ => {}
+        }
+    }
+
+    (name, filename_ext.or(filename))
+}
+
+/// Splits the header value by semicolons, except the ones in the quoted strings.
+fn split_params(s: &str) -> Vec<&str> {
+    let mut params = vec![];
+    let mut start = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            ';' if !quoted => {
+                params.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    params.push(&s[start..]);
+    params
+}
+
+fn unquote(s: &str) -> String {
+    if s.len() < 2 || !s.starts_with('"') || !s.ends_with('"') {
+        return s.to_owned();
+    }
+    let mut unquoted = String::with_capacity(s.len() - 2);
+    let mut escaped = false;
+    for c in s[1..s.len() - 1].chars() {
+        match c {
+            '\\' if !escaped => escaped = true,
+            c => {
+                escaped = false;
+                unquoted.push(c);
+            }
+        }
+    }
+    unquoted
+}
+
+/// Decodes an extended parameter value, e.g. `UTF-8''%e2%82%ac.txt` (RFC 5987).
+fn decode_ext_value(s: &str) -> Option<String> {
+    let mut iter = s.splitn(3, '\'');
+    let charset = iter.next()?;
+    let _lang = iter.next()?;
+    let value = iter.next()?;
+    if !charset.eq_ignore_ascii_case("utf-8") {
+        return None;
+    }
+    percent_encoding::percent_decode(value.as_bytes())
+        .decode_utf8()
+        .ok()
+        .map(|s| s.into_owned())
+}
+
+// ==== MultipartForm ====
+
+/// Create an endpoint which parses a `multipart/form-data` message body into `T`.
+///
+/// The text fields are buffered in memory, and the file fields are spooled to
+/// temporary files. Each field is mapped to the top-level key of its name, and
+/// the fields with the same name can be deserialized as a sequence. A file field
+/// can only be deserialized as an `UploadedFile`.
+///
+/// # Example
+///
+/// ```
+/// # use finchers::endpoints::body::{self, UploadedFile};
+/// # use serde::Deserialize;
+/// #[derive(Debug, Deserialize)]
+/// struct Upload {
+///     title: String,
+///     attachment: Option<UploadedFile>,
+/// }
+///
+/// let endpoint = body::multipart_form::<Upload>()
+///     .max_file_size(1024 * 1024);
+/// # drop(endpoint);
+/// ```
+#[inline]
+pub fn multipart_form<T>() -> MultipartForm<T>
+where
+    T: DeserializeOwned,
+{
+    MultipartForm {
+        config: FormConfig {
+            limit: None,
+            max_field_size: 64 * 1024,
+            max_fields: 256,
+            max_total_field_size: 1024 * 1024,
+            max_file_size: 10 * 1024 * 1024,
+            max_files: 16,
+            temp_dir: None,
+        },
+        _marker: PhantomData,
+    }
+}
+
+#[allow(missing_docs)]
+pub struct MultipartForm<T> {
+    config: FormConfig,
+    _marker: PhantomData<fn() -> T>,
+}
+
+#[derive(Debug, Clone)]
+struct FormConfig {
+    limit: Option<u64>,
+    max_field_size: usize,
+    max_fields: usize,
+    max_total_field_size: usize,
+    max_file_size: u64,
+    max_files: usize,
+    temp_dir: Option<PathBuf>,
+}
+
+impl<T> fmt::Debug for MultipartForm<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultipartForm")
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl<T> MultipartForm<T> {
+    /// Sets the maximum size of request body, in bytes.
+    ///
+    /// By default, the value of `App::body_limit` is used.
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.config.limit = Some(limit);
+        self
+    }
+
+    /// Sets the maximum size of each text field, in bytes.
+    ///
+    /// The default value is 64 KiB.
+    pub fn max_field_size(mut self, size: usize) -> Self {
+        self.config.max_field_size = size;
+        self
+    }
+
+    /// Sets the maximum number of text fields.
+    ///
+    /// The default value is 256.
+    pub fn max_fields(mut self, n: usize) -> Self {
+        self.config.max_fields = n;
+        self
+    }
+
+    /// Sets the maximum total size of the text fields, in bytes.
+    ///
+    /// The default value is 1 MiB.
+    pub fn max_total_field_size(mut self, size: usize) -> Self {
+        self.config.max_total_field_size = size;
+        self
+    }
+
+    /// Sets the maximum size of each file field, in bytes.
+    ///
+    /// The default value is 10 MiB.
+    pub fn max_file_size(mut self, size: u64) -> Self {
+        self.config.max_file_size = size;
+        self
+    }
+
+    /// Sets the maximum number of file fields.
+    ///
+    /// The default value is 16.
+    pub fn max_files(mut self, n: usize) -> Self {
+        self.config.max_files = n;
+        self
+    }
+
+    /// Sets the directory where the temporary files are created.
+    ///
+    /// By default, the directory returned from `std::env::temp_dir` is used.
+    pub fn temp_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.config.temp_dir = Some(dir.into());
+        self
+    }
+}
+
+impl<T: DeserializeOwned> IsEndpoint for MultipartForm<T> {
+    fn describe(&self) -> Description {
+        Description::Body {
+            kind: "multipart",
+            schema: None,
+        }
+    }
+}
+
+impl<T, Bd> Endpoint<Bd> for MultipartForm<T>
+where
+    Bd: BufStream,
+    Bd::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+    T: DeserializeOwned,
+{
+    type Output = (T,);
+    type Action = MultipartFormAction<Bd, T>;
+
+    fn action(&self) -> Self::Action {
+        MultipartFormAction {
+            config: self.config.clone(),
+            state: FormState::Start(None),
+            fields: vec![],
+            fields_size: 0,
+            files: SpooledFiles::default(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[allow(missing_debug_implementations)]
+pub struct MultipartFormAction<Bd, T> {
+    config: FormConfig,
+    state: FormState<Bd>,
+    fields: Vec<(String, String)>,
+    fields_size: usize,
+    files: SpooledFiles,
+    _marker: PhantomData<fn() -> T>,
+}
+
+#[allow(missing_debug_implementations)]
+enum FormState<Bd> {
+    Start(Option<String>),
+    Receiving(Parts<Bd>, Option<Field<Bd>>),
+}
+
+#[allow(missing_debug_implementations)]
+enum Field<Bd> {
+    Text {
+        name: String,
+        body: PartBody<Bd>,
+        buf: Vec<u8>,
+    },
+    File(FileField<Bd>),
+}
+
+impl<Bd, T> EndpointAction<Bd> for MultipartFormAction<Bd, T>
+where
+    Bd: BufStream,
+    Bd::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+    T: DeserializeOwned,
+{
+    type Output = (T,);
+
+    fn preflight(
+        &mut self,
+        cx: &mut PreflightContext<'_>,
+    ) -> Result<Preflight<Self::Output>, Error> {
+        if self.config.limit.is_none() {
+            self.config.limit = cx.body_limit();
+        }
+        if let Some(limit) = self.config.limit {
+            check_content_length(cx, limit)?;
+        }
+        self.state = FormState::Start(Some(boundary(cx)?));
+        Ok(Preflight::Incomplete)
+    }
+
+    fn poll_action(&mut self, cx: &mut ActionContext<'_, Bd>) -> Poll<Self::Output, Error> {
+        loop {
+            let (parts, field) = match self.state {
+                FormState::Start(ref mut boundary) => {
+                    let boundary = boundary.take().expect("the action has already polled");
+                    let body = cx.take_body()?;
+                    let parts = Parts::new(body, &boundary, self.config.limit);
+                    self.state = FormState::Receiving(parts, None);
+                    continue;
+                }
+                FormState::Receiving(ref mut parts, ref mut field) => (parts, field),
+            };
+
+            match field.take() {
+                Some(Field::Text {
+                    name,
+                    mut body,
+                    mut buf,
+                }) => match body.poll_buf()? {
+                    Async::Ready(Some(chunk)) => {
+                        buf.extend_from_slice(chunk.bytes());
+                        self.fields_size += chunk.bytes().len();
+                        if buf.len() > self.config.max_field_size {
+                            return Err(payload_too_large("too large text field"));
+                        }
+                        if self.fields_size > self.config.max_total_field_size {
+                            return Err(payload_too_large("too large text fields"));
+                        }
+                        *field = Some(Field::Text { name, body, buf });
+                    }
+                    Async::Ready(None) => {
+                        let value = String::from_utf8(buf).map_err(error::bad_request)?;
+                        self.fields.push((name, value));
+                    }
+                    Async::NotReady => {
+                        *field = Some(Field::Text { name, body, buf });
+                        return Ok(Async::NotReady);
+                    }
+                },
+                Some(Field::File(mut file)) => {
+                    // The entry of the file being received is always the last one.
+                    let entry = self
+                        .files
+                        .entries
+                        .last_mut()
+                        .expect("the entry should exist");
+                    if file
+                        .poll_spool(entry, self.config.max_file_size)?
+                        .is_not_ready()
+                    {
+                        *field = Some(Field::File(file));
+                        return Ok(Async::NotReady);
+                    }
+                }
+                None => match futures::try_ready!(parts.poll()) {
+                    Some(part) => {
+                        *field =
+                            start_field(&self.config, self.fields.len(), &mut self.files, part)?
+                    }
+                    None => return finish(&self.fields, &mut self.files).map(|x| (x,).into()),
+                },
+            }
+        }
+    }
+}
+
+fn start_field<Bd>(
+    config: &FormConfig,
+    num_fields: usize,
+    files: &mut SpooledFiles,
+    part: Part<Bd>,
+) -> Result<Option<Field<Bd>>, Error> {
+    let name = match part.name {
+        Some(name) => name,
+        None => return Ok(None),
+    };
+    match part.filename {
+        // The browsers send an empty file part if no file is selected.
+        Some(ref filename) if filename.is_empty() => Ok(None),
+        Some(filename) => {
+            if files.entries.len() >= config.max_files {
+                return Err(payload_too_large("too many file fields"));
+            }
+            let dir = match config.temp_dir {
+                Some(ref dir) => dir.clone(),
+                None => std::env::temp_dir(),
+            };
+            let path = temp_path(&dir);
+            // The entry is registered before creating the file, so that the
+            // temporary file is removed even if an error occurs.
+            files.entries.push(SpooledFile {
+                name,
+                path: path.clone(),
+                filename,
+                content_type: part.content_type.map(|m| m.to_string()),
+                size: 0,
+            });
+            Ok(Some(Field::File(FileField {
+                body: part.body,
+                dir,
+                state: SpoolState::Opening(create_temp_file(path)),
+            })))
+        }
+        None => {
+            if num_fields >= config.max_fields {
+                return Err(payload_too_large("too many text fields"));
+            }
+            Ok(Some(Field::Text {
+                name,
+                body: part.body,
+                buf: vec![],
+            }))
+        }
+    }
+}
+
+fn finish<T>(fields: &[(String, String)], files: &mut SpooledFiles) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    let (result, claimed) = self::de::from_fields(fields, &files.entries);
+
+    // The remaining files are not owned by any `UploadedFile`, and will be
+    // removed when the action is dropped.
+    let mut claimed = claimed.into_iter();
+    files.entries.retain(|_| !claimed.next().unwrap_or(false));
+
+    result.map_err(error::bad_request)
+}
+
+/// A file field being written to the temporary file.
+#[allow(missing_debug_implementations)]
+struct FileField<Bd> {
+    body: PartBody<Bd>,
+    dir: PathBuf,
+    state: SpoolState,
+}
+
+enum SpoolState {
+    Opening(OpenFuture<PathBuf>),
+    Writing(File, Option<io::Cursor<Bytes>>),
+    Flushing(File),
+    Done,
+}
+
+impl<Bd> FileField<Bd>
+where
+    Bd: BufStream,
+    Bd::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+{
+    fn poll_spool(&mut self, entry: &mut SpooledFile, max_file_size: u64) -> Poll<(), Error> {
+        loop {
+            self.state = match self.state {
+                SpoolState::Opening(ref mut future) => match future.poll() {
+                    Ok(Async::Ready(file)) => SpoolState::Writing(file, None),
+                    Ok(Async::NotReady) => return Ok(Async::NotReady),
+                    Err(ref err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                        entry.path = temp_path(&self.dir);
+                        SpoolState::Opening(create_temp_file(entry.path.clone()))
+                    }
+                    Err(err) => return Err(err.into()),
+                },
+                SpoolState::Writing(ref mut file, ref mut chunk) => {
+                    if let Some(ref mut chunk) = *chunk {
+                        if chunk.has_remaining() {
+                            let n = futures::try_ready!(file.poll_write(chunk.bytes()));
+                            if n == 0 {
+                                return Err(io::Error::from(io::ErrorKind::WriteZero).into());
+                            }
+                            chunk.advance(n);
+                            continue;
+                        }
+                    }
+                    match futures::try_ready!(self.body.poll_buf()) {
+                        Some(data) => {
+                            entry.size += data.remaining() as u64;
+                            if entry.size > max_file_size {
+                                return Err(payload_too_large("too large file field"));
+                            }
+                            *chunk = Some(data);
+                            continue;
+                        }
+                        None => match mem::replace(&mut self.state, SpoolState::Done) {
+                            SpoolState::Writing(file, _) => SpoolState::Flushing(file),
+                            _ => unreachable!(),
+                        },
+                    }
+                }
+                SpoolState::Flushing(ref mut file) => {
+                    futures::try_ready!(file.poll_flush());
+                    SpoolState::Done
+                }
+                SpoolState::Done => return Ok(Async::Ready(())),
+            };
+        }
+    }
+}
+
+fn temp_path(dir: &Path) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    dir.join(format!(
+        "finchers-upload-{}-{}-{}",
+        process::id(),
+        COUNTER.fetch_add(1, Ordering::SeqCst),
+        nanos,
+    ))
+}
+
+fn create_temp_file(path: PathBuf) -> OpenFuture<PathBuf> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    OpenOptions::from(options).open(path)
+}
+
+#[derive(Debug)]
+struct SpooledFile {
+    name: String,
+    path: PathBuf,
+    filename: String,
+    content_type: Option<String>,
+    size: u64,
+}
+
+/// The temporary files which are not owned by any `UploadedFile`.
+#[derive(Debug, Default)]
+struct SpooledFiles {
+    entries: Vec<SpooledFile>,
+}
+
+impl Drop for SpooledFiles {
+    fn drop(&mut self) {
+        for entry in &self.entries {
+            let _ = fs::remove_file(&entry.path);
+        }
+    }
+}
+
+/// The name of the newtype struct which is passed to the deserializer to
+/// request the properties of a spooled file.
+const UPLOADED_FILE: &str = "$__finchers_private_UploadedFile";
+
+/// Returns the value which proves that the file has been spooled by this process.
+///
+/// It prevents the other deserializers from forging an `UploadedFile` which
+/// refers to an arbitrary file.
+fn file_token(path: &Path) -> u64 {
+    static SECRET: AtomicUsize = AtomicUsize::new(0);
+
+    let mut secret = SECRET.load(Ordering::SeqCst);
+    if secret == 0 {
+        let random = RandomState::new().build_hasher().finish() as usize | 1;
+        secret = match SECRET.compare_exchange(0, random, Ordering::SeqCst, Ordering::SeqCst) {
+            Ok(..) => random,
+            Err(current) => current,
+        };
+    }
+
+    let mut hasher = DefaultHasher::new();
+    secret.hash(&mut hasher);
+    path.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A file uploaded through `multipart/form-data`.
+///
+/// The content is stored in a temporary file, which is removed when this value
+/// is dropped unless `persist` is called.
+///
+/// This value can only be deserialized from a file field by `multipart_form`.
+#[derive(Debug)]
+pub struct UploadedFile {
+    path: PathBuf,
+    filename: String,
+    content_type: Option<Mime>,
+    size: u64,
+    persisted: bool,
+}
+
+impl UploadedFile {
+    /// Returns the path of the temporary file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the file name sent from the client.
+    ///
+    /// Note that this value must not be used as a path without sanitizing.
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    /// Returns the media type of this file, if specified.
+    pub fn content_type(&self) -> Option<&Mime> {
+        self.content_type.as_ref()
+    }
+
+    /// Returns the size of this file, in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Moves the temporary file to the specified path.
+    pub fn persist(mut self, to: impl AsRef<Path>) -> io::Result<()> {
+        let to = to.as_ref();
+        if fs::rename(&self.path, to).is_err() {
+            // The rename fails if the destination is on another file system.
+            fs::copy(&self.path, to)?;
+            let _ = fs::remove_file(&self.path);
+        }
+        self.persisted = true;
+        Ok(())
+    }
+}
+
+impl Drop for UploadedFile {
+    fn drop(&mut self) {
+        if !self.persisted {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for UploadedFile {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(serde::Deserialize)]
+        struct Raw {
+            path: PathBuf,
+            filename: String,
+            content_type: Option<String>,
+            size: u64,
+            token: u64,
+        }
+
+        struct UploadedFileVisitor;
+
+        impl<'de> de::Visitor<'de> for UploadedFileVisitor {
+            type Value = UploadedFile;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("an uploaded file")
+            }
+
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                let raw = Raw::deserialize(deserializer)?;
+                if raw.token != file_token(&raw.path) {
+                    return Err(de::Error::custom("not an uploaded file"));
+                }
+                // The value is constructed before parsing the media type, so
+                // that the temporary file is removed on error.
+                let mut file = UploadedFile {
+                    path: raw.path,
+                    filename: raw.filename,
+                    content_type: None,
+                    size: raw.size,
+                    persisted: false,
+                };
+                if let Some(m) = raw.content_type {
+                    file.content_type = Some(m.parse().map_err(de::Error::custom)?);
+                }
+                Ok(file)
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(UPLOADED_FILE, UploadedFileVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_content_disposition() {
+        assert_eq!(
+            parse_content_disposition(r#"form-data; name="field""#),
+            (Some("field".into()), None)
+        );
+        assert_eq!(
+            parse_content_disposition(r#"form-data; name="file"; filename="a;b \"c\".txt""#),
+            (Some("file".into()), Some(r#"a;b "c".txt"#.into()))
+        );
+        assert_eq!(
+            parse_content_disposition(
+                r#"form-data; name=file; filename="a.txt"; filename*=UTF-8''%E2%82%AC.txt"#
+            ),
+            (Some("file".into()), Some("\u{20ac}.txt".into()))
+        );
+    }
+
+    #[test]
+    fn test_forged_uploaded_file() {
+        let path = std::env::temp_dir().join("finchers-upload-forged");
+        let json = serde_json::json!({
+            "path": path,
+            "filename": "forged",
+            "size": 0,
+            "token": 0,
+        });
+        assert!(serde_json::from_value::<UploadedFile>(json).is_err());
+    }
+
+    #[test]
+    fn test_find() {
+        assert_eq!(find(b"abc\r\n--xyz", b"\r\n--xyz"), Some(3));
+        assert_eq!(find(b"abc\r\n--xy", b"\r\n--xyz"), None);
+        assert_eq!(find(b"", b"\r\n"), None);
+    }
+}
@@ -0,0 +1,392 @@
+//! A `Deserializer` which maps the collected fields in a `multipart/form-data`
+//! message body to the top-level keys.
+
+use {
+    super::{file_token, SpooledFile, UPLOADED_FILE},
+    serde::{
+        de::{
+            self,
+            value::{Error, MapAccessDeserializer},
+            DeserializeSeed, Deserializer, IntoDeserializer, MapAccess, SeqAccess, Unexpected,
+            Visitor,
+        },
+        forward_to_deserialize_any,
+    },
+    std::{cell::Cell, vec},
+};
+
+/// Deserializes the text fields and the spooled files into `T`.
+///
+/// The returned flags indicate whether each file is owned by an `UploadedFile`.
+pub(super) fn from_fields<T>(
+    fields: &[(String, String)],
+    files: &[SpooledFile],
+) -> (Result<T, Error>, Vec<bool>)
+where
+    T: de::DeserializeOwned,
+{
+    let claimed: Vec<Cell<bool>> = files.iter().map(|_| Cell::new(false)).collect();
+
+    let mut groups = vec![];
+    for (name, text) in fields {
+        push_value(&mut groups, name, Value::Text(text));
+    }
+    for (file, claimed) in files.iter().zip(&claimed) {
+        push_value(&mut groups, &file.name, Value::File(file, claimed));
+    }
+
+    let result = T::deserialize(FormDeserializer {
+        groups: groups.into_iter(),
+    });
+    (result, claimed.iter().map(Cell::get).collect())
+}
+
+fn push_value<'a>(groups: &mut Vec<(&'a str, Vec<Value<'a>>)>, name: &'a str, value: Value<'a>) {
+    match groups.iter_mut().find(|group| group.0 == name) {
+        Some(group) => group.1.push(value),
+        None => groups.push((name, vec![value])),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Value<'a> {
+    Text(&'a str),
+    File(&'a SpooledFile, &'a Cell<bool>),
+}
+
+// ==== FormDeserializer ====
+
+struct FormDeserializer<'a> {
+    groups: vec::IntoIter<(&'a str, Vec<Value<'a>>)>,
+}
+
+impl<'a, 'de> Deserializer<'de> for FormDeserializer<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(FormAccess {
+            groups: self.groups,
+            value: None,
+        })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct FormAccess<'a> {
+    groups: vec::IntoIter<(&'a str, Vec<Value<'a>>)>,
+    value: Option<(&'a str, Vec<Value<'a>>)>,
+}
+
+impl<'a, 'de> MapAccess<'de> for FormAccess<'a> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        match self.groups.next() {
+            Some((name, values)) => {
+                self.value = Some((name, values));
+                seed.deserialize(name.into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<S>(&mut self, seed: S) -> Result<S::Value, Self::Error>
+    where
+        S: DeserializeSeed<'de>,
+    {
+        let (name, values) = self
+            .value
+            .take()
+            .expect("next_value_seed is called before next_key_seed");
+        seed.deserialize(ValuesDeserializer { name, values })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.groups.len())
+    }
+}
+
+// ==== ValuesDeserializer ====
+
+/// A `Deserializer` for the values of the fields with the same name.
+struct ValuesDeserializer<'a> {
+    name: &'a str,
+    values: Vec<Value<'a>>,
+}
+
+impl<'a> ValuesDeserializer<'a> {
+    fn into_value(self) -> Result<Value<'a>, Error> {
+        if self.values.len() != 1 {
+            return Err(de::Error::custom(format_args!(
+                "duplicate field `{}`",
+                self.name
+            )));
+        }
+        Ok(self.values[0])
+    }
+}
+
+macro_rules! forward_to_value {
+    ($( $method:ident ( $($arg:ident : $ty:ty),* ); )*) => {$(
+        fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            self.into_value()?.$method($($arg,)* visitor)
+        }
+    )*};
+}
+
+impl<'a, 'de> Deserializer<'de> for ValuesDeserializer<'a> {
+    type Error = Error;
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(ValuesAccess {
+            values: self.values.into_iter(),
+        })
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    forward_to_value! {
+        deserialize_any();
+        deserialize_bool();
+        deserialize_i8();
+        deserialize_i16();
+        deserialize_i32();
+        deserialize_i64();
+        deserialize_u8();
+        deserialize_u16();
+        deserialize_u32();
+        deserialize_u64();
+        deserialize_f32();
+        deserialize_f64();
+        deserialize_char();
+        deserialize_str();
+        deserialize_string();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_newtype_struct(name: &'static str);
+        deserialize_tuple_struct(name: &'static str, len: usize);
+        deserialize_map();
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]);
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_identifier();
+    }
+}
+
+struct ValuesAccess<'a> {
+    values: vec::IntoIter<Value<'a>>,
+}
+
+impl<'a, 'de> SeqAccess<'de> for ValuesAccess<'a> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.values.next() {
+            Some(value) => seed.deserialize(value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.values.len())
+    }
+}
+
+// ==== Value ====
+
+macro_rules! deserialize_parsed {
+    ($( $method:ident => $visit:ident, )*) => {$(
+        fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            match self {
+                Value::Text(s) => match s.parse() {
+                    Ok(v) => visitor.$visit(v),
+                    Err(..) => Err(de::Error::invalid_value(Unexpected::Str(s), &visitor)),
+                },
+                Value::File(..) => self.deserialize_any(visitor),
+            }
+        }
+    )*};
+}
+
+impl<'a, 'de> Deserializer<'de> for Value<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Text(s) => visitor.visit_str(s),
+            Value::File(..) => Err(de::Error::invalid_type(
+                Unexpected::Other("file field"),
+                &visitor,
+            )),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Text(..) => visitor.visit_newtype_struct(self),
+            Value::File(file, claimed) if name == UPLOADED_FILE => {
+                let value =
+                    visitor.visit_newtype_struct(MapAccessDeserializer::new(FileAccess {
+                        file,
+                        keys: ["path", "filename", "content_type", "size", "token"].iter(),
+                        key: "",
+                    }))?;
+                // The temporary file is now owned by the `UploadedFile`.
+                claimed.set(true);
+                Ok(value)
+            }
+            Value::File(..) => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Text(s) => visitor.visit_enum(s.into_deserializer()),
+            Value::File(..) => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier
+    }
+}
+
+/// A `MapAccess` which passes the properties of a spooled file to `UploadedFile`.
+struct FileAccess<'a> {
+    file: &'a SpooledFile,
+    keys: std::slice::Iter<'static, &'static str>,
+    key: &'static str,
+}
+
+impl<'a, 'de> MapAccess<'de> for FileAccess<'a> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        loop {
+            match self.keys.next() {
+                Some(&"content_type") if self.file.content_type.is_none() => continue,
+                Some(&key) => {
+                    self.key = key;
+                    return seed.deserialize(key.into_deserializer()).map(Some);
+                }
+                None => return Ok(None),
+            }
+        }
+    }
+
+    fn next_value_seed<S>(&mut self, seed: S) -> Result<S::Value, Self::Error>
+    where
+        S: DeserializeSeed<'de>,
+    {
+        let file = self.file;
+        match self.key {
+            "path" => {
+                seed.deserialize(file.path.to_string_lossy().into_owned().into_deserializer())
+            }
+            "filename" => seed.deserialize(file.filename.as_str().into_deserializer()),
+            "content_type" => seed.deserialize(
+                file.content_type
+                    .as_ref()
+                    .map_or("", |s| s.as_str())
+                    .into_deserializer(),
+            ),
+            "size" => seed.deserialize(file.size.into_deserializer()),
+            "token" => seed.deserialize(file_token(&file.path).into_deserializer()),
+            _ => unreachable!("next_value_seed is called before next_key_seed"),
+        }
+    }
+}
@@ -1,31 +1,78 @@
 //! Endpoints for serving static contents on the file system.
 
+mod embedded;
+
+pub use {
+    self::embedded::{
+        static_dir, EmbeddedDir, EmbeddedFile, StaticDir, StaticDirAction, StaticFile,
+    },
+    crate::static_dir, //
+    finchers_macros::EmbedDir,
+};
+
 use {
     crate::{
         action::{
             ActionContext, //
+            Cursor,
             EndpointAction,
             Preflight,
             PreflightContext,
         },
-        endpoint::{Endpoint, IsEndpoint},
-        error::{self, Error},
-        output::fs::{NamedFile, OpenNamedFile},
+        endpoint::{describe::Description, Endpoint, IsEndpoint},
+        endpoints::header::Accept,
+        error::{self, Error, HttpError},
+        output::fs::{
+            select_precompressed, CachePolicy, DirListing, NamedFile, OpenNamedFile, ReadDirListing,
+        },
     },
     futures::Poll,
-    std::path::PathBuf,
+    http::{header::HeaderMap, Method, StatusCode},
+    std::{
+        io,
+        path::{Component, Path, PathBuf},
+        sync::Arc,
+    },
 };
 
 /// Create an endpoint which serves a specified file on the file system.
 #[inline]
 pub fn file(path: impl Into<PathBuf>) -> File {
-    File { path: path.into() }
+    File {
+        path: path.into(),
+        precompressed: false,
+        cache_policy: None,
+    }
 }
 
 #[allow(missing_docs)]
 #[derive(Debug)]
 pub struct File {
     path: PathBuf,
+    precompressed: bool,
+    cache_policy: Option<Arc<CachePolicy>>,
+}
+
+impl File {
+    /// Sets whether to serve the precompressed sibling of the file.
+    ///
+    /// If enabled, the file with the extension `.br` or `.gz` appended is
+    /// served instead when it exists and the coding is acceptable for the request.
+    /// The default value is `false`.
+    pub fn precompressed(self, enabled: bool) -> Self {
+        Self {
+            precompressed: enabled,
+            ..self
+        }
+    }
+
+    /// Sets the policy which determines the value of `Cache-Control` in the response.
+    pub fn cache_policy(self, cache_policy: CachePolicy) -> Self {
+        Self {
+            cache_policy: Some(Arc::new(cache_policy)),
+            ..self
+        }
+    }
 }
 
 mod file {
@@ -42,6 +89,8 @@ mod file {
         fn action(&self) -> Self::Action {
             FileAction {
                 path: self.path.clone(),
+                precompressed: self.precompressed,
+                cache_policy: self.cache_policy.clone(),
                 opening: None,
                 _marker: PhantomData,
             }
@@ -51,6 +100,8 @@ mod file {
     #[allow(missing_debug_implementations)]
     pub struct FileAction<Bd> {
         path: PathBuf,
+        precompressed: bool,
+        cache_policy: Option<Arc<CachePolicy>>,
         opening: Option<OpenNamedFile>,
         _marker: PhantomData<fn(Bd)>,
     }
@@ -58,42 +109,299 @@ mod file {
     impl<Bd> EndpointAction<Bd> for FileAction<Bd> {
         type Output = (NamedFile,);
 
-        fn poll_action(&mut self, _: &mut ActionContext<'_, Bd>) -> Poll<Self::Output, Error> {
+        fn poll_action(&mut self, cx: &mut ActionContext<'_, Bd>) -> Poll<Self::Output, Error> {
             loop {
                 if let Some(ref mut opening) = self.opening {
                     return opening.poll().map(|x| x.map(|x| (x,))).map_err(Into::into);
                 }
-                self.opening = Some(NamedFile::open(self.path.clone()));
+                let opening = if self.precompressed {
+                    NamedFile::open_precompressed(self.path.clone(), cx.headers())
+                } else {
+                    NamedFile::open(self.path.clone())
+                };
+                self.opening = Some(opening.with_cache_policy(self.cache_policy.clone()));
             }
         }
     }
 }
 
 /// Create an endpoint which serves files in the specified directory.
+///
+/// The remaining path segments are resolved relative to `root`. The segments
+/// `..` are rejected, and the hidden files (whose name starts with `.`) are not
+/// served by default.
+///
+/// The output is `Either<NamedFile, DirListing>`, whose right variant is
+/// returned only if the directory listing is enabled by `Dir::listing`.
 #[inline]
 pub fn dir(root: impl Into<PathBuf>) -> Dir {
-    Dir { root: root.into() }
+    Dir {
+        config: Arc::new(DirConfig {
+            root: root.into(),
+            hidden_files: false,
+            restrict_symlinks: false,
+            listing: false,
+            precompressed: false,
+            spa_fallback: None,
+            cache_policy: None,
+        }),
+    }
 }
 
 #[allow(missing_docs)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Dir {
+    config: Arc<DirConfig>,
+}
+
+#[derive(Debug, Clone)]
+struct DirConfig {
     root: PathBuf,
+    hidden_files: bool,
+    restrict_symlinks: bool,
+    listing: bool,
+    precompressed: bool,
+    spa_fallback: Option<PathBuf>,
+    cache_policy: Option<Arc<CachePolicy>>,
+}
+
+impl Dir {
+    /// Sets whether to serve the hidden files, whose name starts with `.`.
+    ///
+    /// The default value is `false`, and the requests to the hidden files
+    /// are rejected with `404 Not Found`.
+    pub fn hidden_files(mut self, enabled: bool) -> Self {
+        Arc::make_mut(&mut self.config).hidden_files = enabled;
+        self
+    }
+
+    /// Sets whether to reject the files resolved to a location outside of
+    /// the root directory via symbolic links.
+    ///
+    /// The default value is `false`. If enabled, such requests are rejected
+    /// with `403 Forbidden`. The check is also applied to the SPA fallback,
+    /// and the paths which cannot be resolved (e.g. dangling symbolic links)
+    /// are rejected as well.
+    pub fn restrict_symlinks(mut self, enabled: bool) -> Self {
+        Arc::make_mut(&mut self.config).restrict_symlinks = enabled;
+        self
+    }
+
+    /// Sets whether to list the entries of the directories without `index.html`.
+    ///
+    /// The default value is `false`. If enabled, the list is rendered as an
+    /// HTML page or a JSON array, chosen by `Accept` in the request.
+    /// See `DirListing` for details.
+    pub fn listing(mut self, enabled: bool) -> Self {
+        Arc::make_mut(&mut self.config).listing = enabled;
+        self
+    }
+
+    /// Sets whether to serve the precompressed siblings of the files.
+    ///
+    /// See `File::precompressed` for details.
+    pub fn precompressed(mut self, enabled: bool) -> Self {
+        Arc::make_mut(&mut self.config).precompressed = enabled;
+        self
+    }
+
+    /// Sets the path of the file served for the unknown paths, relative to the root.
+    ///
+    /// This mode is intended for single-page applications with the client-side routing.
+    /// The fallback file is served only for `GET` and `HEAD` requests which accept
+    /// `text/html` and whose last segment has no file extension, so the requests
+    /// to missing assets still result in `404 Not Found`.
+    pub fn spa_fallback(mut self, path: impl Into<PathBuf>) -> Self {
+        Arc::make_mut(&mut self.config).spa_fallback = Some(path.into());
+        self
+    }
+
+    /// Sets the policy which determines the value of `Cache-Control` in the responses.
+    pub fn cache_policy(mut self, cache_policy: CachePolicy) -> Self {
+        Arc::make_mut(&mut self.config).cache_policy = Some(Arc::new(cache_policy));
+        self
+    }
+}
+
+/// The target of a request located by `Dir`.
+enum Located {
+    File {
+        file_path: PathBuf,
+        path: PathBuf,
+        encoding: Option<&'static str>,
+    },
+    Listing(PathBuf),
+}
+
+impl DirConfig {
+    /// Converts the remaining path segments into a path on the file system.
+    ///
+    /// This method does not access the file system.
+    fn resolve(&self, cursor: Cursor<'_>) -> Result<PathBuf, Error> {
+        let mut path = self.root.clone();
+        for segment in cursor {
+            let segment = segment.percent_decode().map_err(error::bad_request)?;
+            if segment.is_empty() || segment == "." {
+                continue;
+            }
+
+            // Each segment must be exactly a normal component, so that the
+            // decoded values like `..`, `a/b` or `C:` cannot escape the root.
+            let mut components = Path::new(&*segment).components();
+            match (components.next(), components.next()) {
+                (Some(Component::Normal(name)), None) if name == &*segment => {}
+                _ => return Err(DirError::Traversal.into()),
+            }
+            if !self.hidden_files && segment.starts_with('.') {
+                return Err(DirError::Hidden.into());
+            }
+            path.push(&*segment);
+        }
+        Ok(path)
+    }
+
+    /// Returns the path of the SPA fallback if the request is eligible for it.
+    fn fallback(&self, cx: &PreflightContext<'_>) -> Option<PathBuf> {
+        let fallback = self.spa_fallback.as_ref()?;
+
+        let is_get_or_head = *cx.method() == Method::GET || *cx.method() == Method::HEAD;
+        let has_extension = cx
+            .uri()
+            .path()
+            .rsplit('/')
+            .next()
+            .map_or(false, |segment| segment.contains('.'));
+        let accepts_html = Accept::from_headers(cx.headers())
+            .map(|accept| accept.quality(&mime::TEXT_HTML) > 0.0)
+            .unwrap_or(false);
+
+        if is_get_or_head && !has_extension && accepts_html {
+            Some(self.root.join(fallback))
+        } else {
+            None
+        }
+    }
+
+    /// Inspects the file system and determines the file or directory to be served.
+    ///
+    /// This method blocks the current thread, and must be called via `blocking`.
+    fn locate(
+        &self,
+        mut path: PathBuf,
+        fallback: Option<&Path>,
+        headers: &HeaderMap,
+    ) -> Result<Located, Error> {
+        if path.is_dir() {
+            let index = path.join("index.html");
+            if self.listing && !index.is_file() {
+                self.check_symlinks(&path)?;
+                return Ok(Located::Listing(path));
+            }
+            path = index;
+        }
+
+        // Replaces the path of a missing file with the SPA fallback.
+        if let Some(fallback) = fallback {
+            if !path.is_file() {
+                path = fallback.to_owned();
+            }
+        }
+
+        self.check_symlinks(&path)?;
+
+        if self.precompressed {
+            if let Some((encoded_path, encoding)) = select_precompressed(&path, headers) {
+                self.check_symlinks(&encoded_path)?;
+                return Ok(Located::File {
+                    file_path: encoded_path,
+                    path,
+                    encoding: Some(encoding),
+                });
+            }
+        }
+        Ok(Located::File {
+            file_path: path.clone(),
+            path,
+            encoding: None,
+        })
+    }
+
+    fn check_symlinks(&self, path: &Path) -> Result<(), Error> {
+        if !self.restrict_symlinks {
+            return Ok(());
+        }
+
+        // The path which cannot be resolved (e.g. a dangling symbolic link, or
+        // a directory without the permission) is also rejected, so that the
+        // check never fails open.
+        let canonicalize = |path: &Path| {
+            path.canonicalize().map_err(|err| match err.kind() {
+                io::ErrorKind::NotFound => DirError::NotFound,
+                _ => DirError::OutsideRoot,
+            })
+        };
+        let root = canonicalize(&self.root)?;
+        let resolved = canonicalize(path)?;
+        if !resolved.starts_with(&root) {
+            return Err(DirError::OutsideRoot.into());
+        }
+        Ok(())
+    }
+}
+
+/// The error type returned when `Dir` rejects the request path.
+#[derive(Debug, failure::Fail)]
+pub enum DirError {
+    /// The path contains a segment which points to outside of the current directory,
+    /// such as `..`.
+    #[fail(display = "the request path contains an invalid segment")]
+    Traversal,
+
+    /// The path contains a hidden file or directory.
+    #[fail(display = "not found")]
+    Hidden,
+
+    /// The path is resolved to outside of the root directory via symbolic links,
+    /// or cannot be resolved.
+    #[fail(display = "the requested file is outside of the root directory")]
+    OutsideRoot,
+
+    /// The path does not exist, or is a dangling symbolic link.
+    #[fail(display = "not found")]
+    NotFound,
+}
+
+impl HttpError for DirError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            DirError::Traversal | DirError::OutsideRoot => StatusCode::FORBIDDEN,
+            DirError::Hidden | DirError::NotFound => StatusCode::NOT_FOUND,
+        }
+    }
 }
 
 mod dir {
     use super::*;
-    use futures::Future as _Future;
+    use either::Either;
+    use futures::{try_ready, Future as _Future};
+    use tokio_threadpool::blocking;
 
-    impl IsEndpoint for Dir {}
+    impl IsEndpoint for Dir {
+        fn describe(&self) -> Description {
+            Description::Remains {
+                name: None,
+                ty: None,
+            }
+        }
+    }
 
     impl<Bd> Endpoint<Bd> for Dir {
-        type Output = (NamedFile,);
+        type Output = (Either<NamedFile, DirListing>,);
         type Action = DirAction;
 
         fn action(&self) -> Self::Action {
             DirAction {
-                root: self.root.clone(),
+                config: self.config.clone(),
                 state: State::Init,
             }
         }
@@ -101,43 +409,84 @@ mod dir {
 
     #[allow(missing_debug_implementations)]
     pub struct DirAction {
-        root: PathBuf,
+        config: Arc<DirConfig>,
         state: State,
     }
 
     enum State {
         Init,
+        Locating {
+            path: PathBuf,
+            fallback: Option<PathBuf>,
+        },
         Opening(OpenNamedFile),
+        Listing(ReadDirListing),
     }
 
     impl<Bd> EndpointAction<Bd> for DirAction {
-        type Output = (NamedFile,);
+        type Output = (Either<NamedFile, DirListing>,);
 
         fn preflight(
             &mut self,
             cx: &mut PreflightContext<'_>,
         ) -> Result<Preflight<Self::Output>, Error> {
-            let path = cx
-                .cursor()
-                .remaining_path()
-                .percent_decode()
-                .map(|path| PathBuf::from(path.into_owned()));
-            let _ = cx.cursor().count();
-            let path = path.map_err(error::bad_request)?;
-
-            let mut path = self.root.join(path);
-            if path.is_dir() {
-                path = path.join("index.html");
-            }
-
-            self.state = State::Opening(NamedFile::open(path));
+            let path = self.config.resolve(cx.cursor())?;
+            let fallback = self.config.fallback(cx);
+            self.state = State::Locating { path, fallback };
             Ok(Preflight::Incomplete)
         }
 
-        fn poll_action(&mut self, _: &mut ActionContext<'_, Bd>) -> Poll<Self::Output, Error> {
-            match self.state {
-                State::Init => unreachable!(),
-                State::Opening(ref mut f) => f.poll().map(|x| x.map(|x| (x,))).map_err(Into::into),
+        fn poll_action(&mut self, cx: &mut ActionContext<'_, Bd>) -> Poll<Self::Output, Error> {
+            loop {
+                self.state = match self.state {
+                    State::Init => unreachable!(),
+                    State::Locating {
+                        ref path,
+                        ref fallback,
+                    } => {
+                        let config = &self.config;
+                        let headers = cx.headers();
+                        let located = try_ready!(blocking(|| config.locate(
+                            path.clone(),
+                            fallback.as_ref().map(|p| p.as_path()),
+                            headers
+                        ))
+                        .map_err(|err| io::Error::new(io::ErrorKind::Other, err)))?;
+                        match located {
+                            Located::File {
+                                file_path,
+                                path,
+                                encoding,
+                            } => State::Opening(
+                                NamedFile::open_with(
+                                    file_path,
+                                    path,
+                                    encoding,
+                                    config.precompressed,
+                                )
+                                .with_cache_policy(config.cache_policy.clone()),
+                            ),
+                            Located::Listing(path) => State::Listing(DirListing::read(
+                                path,
+                                cx.uri().path(),
+                                cx.uri().query(),
+                                config.hidden_files,
+                            )),
+                        }
+                    }
+                    State::Opening(ref mut f) => {
+                        return f
+                            .poll()
+                            .map(|x| x.map(|file| (Either::Left(file),)))
+                            .map_err(Into::into);
+                    }
+                    State::Listing(ref mut f) => {
+                        return f
+                            .poll()
+                            .map(|x| x.map(|listing| (Either::Right(listing),)))
+                            .map_err(Into::into);
+                    }
+                };
             }
         }
     }
@@ -0,0 +1,328 @@
+use {
+    crate::{
+        endpoint::{
+            describe::Description, Endpoint, IsEndpoint, Oneshot, OneshotAction, PreflightContext,
+        },
+        endpoints::header::negotiate_encoding,
+        error::{self, Error},
+        output::{
+            fs::{generate_boundary, parse_range, Ranges},
+            IntoResponse,
+        },
+    },
+    http::{
+        header::{self, HeaderValue},
+        Method, Request, Response, StatusCode,
+    },
+    mime_guess::guess_mime_type,
+    std::{borrow::Cow, marker::PhantomData},
+};
+
+/// A macro for creating an endpoint which serves the files embedded into the binary.
+///
+/// The path is relative to the directory containing `Cargo.toml` of the crate.
+/// All files under the directory, except the hidden ones, are embedded at compile time
+/// with their entity tags and gzip-compressed variants. The brotli-compressed variants
+/// are also embedded if the feature `brotli` is enabled.
+///
+/// The created endpoint has the same routing semantics as `fs::dir`: the remaining path
+/// is looked up in the directory, and `index.html` is served for the directories.
+/// The responses support `HEAD`, `If-None-Match`, `Range` and `If-Range` as `NamedFile`
+/// does, but the options of `fs::dir` (e.g. the listing) are not available.
+///
+/// # Example
+///
+/// ```
+/// use finchers::prelude::*;
+/// use finchers::endpoint::syntax::path;
+/// use finchers::endpoints::fs::static_dir;
+///
+/// // Embeds the directory `tests/assets/static` in this crate.
+/// let endpoint = path!(@get "/assets")
+///     .and(static_dir!("tests/assets/static"));
+/// # drop(endpoint);
+/// ```
+#[macro_export]
+macro_rules! static_dir {
+    ($dir:expr) => {{
+        #[derive($crate::endpoints::fs::EmbedDir)]
+        #[dir = $dir]
+        struct __DerivedEmbedDir(());
+
+        $crate::endpoints::fs::static_dir::<__DerivedEmbedDir>()
+    }};
+}
+
+/// A trait representing a directory embedded into the binary.
+///
+/// This trait is implemented by `#[derive(EmbedDir)]`, used internally in `static_dir!()`.
+pub trait EmbeddedDir {
+    /// Returns the embedded files, sorted by their paths.
+    fn files() -> &'static [EmbeddedFile];
+}
+
+/// A file embedded into the binary.
+#[derive(Debug)]
+pub struct EmbeddedFile {
+    /// The path relative to the embedded directory, separated by `/`.
+    pub path: &'static str,
+
+    /// The strong entity tag of the content, including the double quotes.
+    pub etag: &'static str,
+
+    /// The content of this file.
+    pub data: &'static [u8],
+
+    /// The gzip-compressed content of this file, if it is smaller than the original.
+    pub gzip: Option<&'static [u8]>,
+
+    /// The brotli-compressed content of this file, if it is smaller than the original.
+    ///
+    /// This field is always `None` unless the feature `brotli` is enabled.
+    pub br: Option<&'static [u8]>,
+}
+
+/// Create an endpoint which serves the files in an embedded directory.
+///
+/// Use `static_dir!()` instead of calling this function directly.
+pub fn static_dir<T: EmbeddedDir>() -> StaticDir<T> {
+    StaticDir {
+        _marker: PhantomData,
+    }
+}
+
+#[allow(missing_docs)]
+#[derive(Debug)]
+pub struct StaticDir<T> {
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: EmbeddedDir> IsEndpoint for StaticDir<T> {
+    fn describe(&self) -> Description {
+        Description::Remains {
+            name: None,
+            ty: None,
+        }
+    }
+}
+
+impl<T: EmbeddedDir, Bd> Endpoint<Bd> for StaticDir<T> {
+    type Output = (StaticFile,);
+    type Action = Oneshot<StaticDirAction<T>>;
+
+    fn action(&self) -> Self::Action {
+        StaticDirAction {
+            _marker: PhantomData,
+        }
+        .into_action()
+    }
+}
+
+#[doc(hidden)]
+#[allow(missing_debug_implementations)]
+pub struct StaticDirAction<T> {
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: EmbeddedDir> OneshotAction for StaticDirAction<T> {
+    type Output = (StaticFile,);
+
+    fn preflight(self, cx: &mut PreflightContext<'_>) -> Result<Self::Output, Error> {
+        let mut path = String::new();
+        for segment in cx.cursor() {
+            let segment = segment.percent_decode().map_err(error::bad_request)?;
+            if segment.is_empty() || segment == "." {
+                continue;
+            }
+            if segment == ".." || segment.contains('/') {
+                return Err(error::not_found("not found"));
+            }
+            if !path.is_empty() {
+                path.push('/');
+            }
+            path.push_str(&segment);
+        }
+
+        let files = T::files();
+        let find = |path: &str| {
+            files
+                .binary_search_by_key(&path, |file| file.path)
+                .ok()
+                .map(|i| &files[i])
+        };
+
+        let index = if path.is_empty() {
+            "index.html".to_owned()
+        } else {
+            format!("{}/index.html", path)
+        };
+        find(&path)
+            .or_else(|| find(&index))
+            .map(|file| (StaticFile { file },))
+            .ok_or_else(|| error::not_found("not found"))
+    }
+}
+
+/// An instance of `Output` representing a file embedded by `static_dir!()`.
+///
+/// The response supports `If-None-Match`, `Range` and `If-Range`, and the
+/// compressed content (`br` or `gzip`) is served if the client accepts it.
+/// The body of the response to a `HEAD` request is empty.
+#[derive(Debug)]
+pub struct StaticFile {
+    file: &'static EmbeddedFile,
+}
+
+impl StaticFile {
+    /// Returns the embedded file.
+    pub fn file(&self) -> &'static EmbeddedFile {
+        self.file
+    }
+}
+
+impl IntoResponse for StaticFile {
+    type Body = Cow<'static, [u8]>;
+
+    fn into_response(self, request: &Request<()>) -> Response<Self::Body> {
+        let file = self.file;
+
+        let mut candidates = vec![];
+        if file.br.is_some() {
+            candidates.push("br");
+        }
+        if file.gzip.is_some() {
+            candidates.push("gzip");
+        }
+        let (data, etag, encoding) = match negotiate_encoding(request.headers(), &candidates) {
+            Some(encoding) => {
+                let data = match encoding {
+                    "br" => file.br,
+                    _ => file.gzip,
+                };
+                // The compressed representation needs its own strong validator.
+                let etag = format!("{}-{}\"", &file.etag[..file.etag.len() - 1], encoding);
+                (
+                    data.expect("the candidate is available"),
+                    etag,
+                    Some(encoding),
+                )
+            }
+            None => (file.data, file.etag.to_owned(), None),
+        };
+        let len = data.len() as u64;
+
+        let not_modified = request
+            .headers()
+            .get_all(header::IF_NONE_MATCH)
+            .iter()
+            .filter_map(|h| h.to_str().ok())
+            .flat_map(|h| h.split(','))
+            .map(|tag| tag.trim())
+            .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag);
+
+        let is_get_or_head = *request.method() == Method::GET || *request.method() == Method::HEAD;
+        let ranges = if is_get_or_head && !not_modified {
+            match request.headers().get(header::RANGE) {
+                Some(range) if if_range_matches(request, &etag) => parse_range(range, len),
+                _ => None,
+            }
+        } else {
+            None
+        };
+
+        let mut response = if not_modified {
+            let mut response = Response::new(Cow::Borrowed(&[] as &[u8]));
+            *response.status_mut() = StatusCode::NOT_MODIFIED;
+            response
+        } else {
+            let content_type = guess_mime_type(file.path);
+            let mut response = match ranges {
+                None => Response::builder()
+                    .status(StatusCode::OK)
+                    .header(header::CONTENT_TYPE, content_type.as_ref())
+                    .body(Cow::Borrowed(data))
+                    .unwrap(),
+
+                Some(Ranges::Unsatisfiable) => Response::builder()
+                    .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                    .header(header::CONTENT_RANGE, &*format!("bytes */{}", len))
+                    .body(Cow::Borrowed(&[] as &[u8]))
+                    .unwrap(),
+
+                Some(Ranges::Satisfiable(ref ranges)) if ranges.len() == 1 => {
+                    let range = &ranges[0];
+                    Response::builder()
+                        .status(StatusCode::PARTIAL_CONTENT)
+                        .header(header::CONTENT_TYPE, content_type.as_ref())
+                        .header(header::CONTENT_RANGE, &*range.content_range(len))
+                        .body(Cow::Borrowed(
+                            &data[range.start as usize..range.end as usize],
+                        ))
+                        .unwrap()
+                }
+
+                Some(Ranges::Satisfiable(ranges)) => {
+                    let boundary = generate_boundary();
+                    let mut body = vec![];
+                    for (i, range) in ranges.iter().enumerate() {
+                        body.extend_from_slice(
+                            format!(
+                                "{}--{}\r\nContent-Type: {}\r\nContent-Range: {}\r\n\r\n",
+                                if i == 0 { "" } else { "\r\n" },
+                                boundary,
+                                content_type,
+                                range.content_range(len),
+                            )
+                            .as_bytes(),
+                        );
+                        body.extend_from_slice(&data[range.start as usize..range.end as usize]);
+                    }
+                    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+
+                    Response::builder()
+                        .status(StatusCode::PARTIAL_CONTENT)
+                        .header(
+                            header::CONTENT_TYPE,
+                            &*format!("multipart/byteranges; boundary={}", boundary),
+                        )
+                        .body(Cow::Owned(body))
+                        .unwrap()
+                }
+            };
+
+            let content_length = response.body().len();
+            let headers = response.headers_mut();
+            headers.insert(header::CONTENT_LENGTH, HeaderValue::from(content_length));
+            headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+            if let Some(encoding) = encoding {
+                headers.insert(header::CONTENT_ENCODING, HeaderValue::from_static(encoding));
+            }
+            response
+        };
+
+        let headers = response.headers_mut();
+        headers.insert(
+            header::ETAG,
+            HeaderValue::from_str(&etag).expect("should be a valid header value"),
+        );
+        if !candidates.is_empty() {
+            headers.insert(header::VARY, HeaderValue::from_static("accept-encoding"));
+        }
+
+        // The header fields are kept, including `Content-Length`.
+        if *request.method() == Method::HEAD {
+            *response.body_mut() = Cow::Borrowed(&[]);
+        }
+        response
+    }
+}
+
+/// Returns whether the validator in `If-Range` matches the entity tag strongly.
+///
+/// The embedded files have no modification time, so an HTTP-date never matches.
+fn if_range_matches(request: &Request<()>, etag: &str) -> bool {
+    match request.headers().get(header::IF_RANGE) {
+        Some(if_range) => if_range.to_str().map_or(false, |v| v.trim() == etag),
+        None => true,
+    }
+}
@@ -7,16 +7,16 @@ use {
             OneshotAction,
             PreflightContext, //
         },
-        endpoint::{Endpoint, IsEndpoint},
+        endpoint::{describe::Description, Endpoint, IsEndpoint},
         error::{self, Error},
         util::Never,
     },
     http::{
-        header::{HeaderName, HeaderValue, ToStrError},
+        header::{self, HeaderMap, HeaderName, HeaderValue, ToStrError},
         HttpTryFrom,
     },
     mime::Mime,
-    std::{fmt, marker::PhantomData},
+    std::{cmp, fmt, marker::PhantomData, slice},
     url::Url,
 };
 
@@ -108,7 +108,14 @@ pub struct Parse<T> {
 mod parse {
     use super::*;
 
-    impl<T: FromHeaderValue> IsEndpoint for Parse<T> {}
+    impl<T: FromHeaderValue> IsEndpoint for Parse<T> {
+        fn describe(&self) -> Description {
+            Description::Header {
+                name: self.name.clone(),
+                required: true,
+            }
+        }
+    }
 
     impl<T, Bd> Endpoint<Bd> for Parse<T>
     where
@@ -184,7 +191,14 @@ pub struct Optional<T> {
 mod optional {
     use super::*;
 
-    impl<T: FromHeaderValue> IsEndpoint for Optional<T> {}
+    impl<T: FromHeaderValue> IsEndpoint for Optional<T> {
+        fn describe(&self) -> Description {
+            Description::Header {
+                name: self.name.clone(),
+                required: false,
+            }
+        }
+    }
 
     impl<T, Bd> Endpoint<Bd> for Optional<T>
     where
@@ -273,7 +287,14 @@ pub struct Matches<T> {
 mod matches {
     use super::*;
 
-    impl<T: PartialEq<HeaderValue>> IsEndpoint for Matches<T> {}
+    impl<T: PartialEq<HeaderValue>> IsEndpoint for Matches<T> {
+        fn describe(&self) -> Description {
+            Description::Header {
+                name: self.name.clone(),
+                required: true,
+            }
+        }
+    }
 
     impl<T, Bd> Endpoint<Bd> for Matches<T>
     where
@@ -335,7 +356,14 @@ pub struct Raw {
 mod raw {
     use super::*;
 
-    impl IsEndpoint for Raw {}
+    impl IsEndpoint for Raw {
+        fn describe(&self) -> Description {
+            Description::Header {
+                name: self.name.clone(),
+                required: false,
+            }
+        }
+    }
 
     impl<Bd> Endpoint<Bd> for Raw {
         type Output = (Option<HeaderValue>,);
@@ -362,3 +390,347 @@ mod raw {
         }
     }
 }
+
+// ==== Accept ====
+
+/// Create an endpoint which parses the header field `Accept`.
+///
+/// This endpoint always matches to the request. If the header is missing,
+/// it returns an empty `Accept`, which accepts any media type.
+///
+/// # Example
+///
+/// ```
+/// # use finchers::prelude::*;
+/// # use finchers::endpoints::header;
+/// let endpoint = header::accept()
+///     .map(|accept: header::Accept| {
+///         match accept.preferred(&[mime::APPLICATION_JSON, mime::TEXT_PLAIN]) {
+///             Some(m) if *m == mime::APPLICATION_JSON => "json",
+///             _ => "text",
+///         }
+///     });
+/// # drop(endpoint);
+/// ```
+#[inline]
+pub fn accept() -> ParseAccept {
+    ParseAccept { _priv: () }
+}
+
+#[allow(missing_docs)]
+#[derive(Debug, Copy, Clone)]
+pub struct ParseAccept {
+    _priv: (),
+}
+
+mod accept {
+    use super::*;
+
+    impl IsEndpoint for ParseAccept {
+        fn describe(&self) -> Description {
+            Description::Header {
+                name: header::ACCEPT,
+                required: false,
+            }
+        }
+    }
+
+    impl<Bd> Endpoint<Bd> for ParseAccept {
+        type Output = (Accept,);
+        type Action = Oneshot<ParseAcceptAction>;
+
+        fn action(&self) -> Self::Action {
+            ParseAcceptAction { _priv: () }.into_action()
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    pub struct ParseAcceptAction {
+        _priv: (),
+    }
+
+    impl OneshotAction for ParseAcceptAction {
+        type Output = (Accept,);
+
+        fn preflight(self, cx: &mut PreflightContext<'_>) -> Result<Self::Output, Error> {
+            Accept::from_headers(cx.headers())
+                .map(|accept| (accept,))
+                .map_err(error::bad_request)
+        }
+    }
+}
+
+/// A media range in the header field `Accept`, with its quality value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaRange {
+    mime: Mime,
+    quality: u16,
+}
+
+impl MediaRange {
+    /// Returns the media range, which may contain wildcards such as `text/*`.
+    pub fn mime(&self) -> &Mime {
+        &self.mime
+    }
+
+    /// Returns the quality value of this media range, in the range `0.0..=1.0`.
+    pub fn quality(&self) -> f32 {
+        f32::from(self.quality) / 1000.0
+    }
+
+    /// Returns the specificity of this range if it matches the specified media type.
+    fn specificity(&self, mime: &Mime) -> Option<u8> {
+        if self.mime.type_() == mime::STAR {
+            Some(0)
+        } else if self.mime.type_() != mime.type_() {
+            None
+        } else if self.mime.subtype() == mime::STAR {
+            Some(1)
+        } else if self.mime.subtype() == mime.subtype() {
+            Some(2)
+        } else {
+            None
+        }
+    }
+}
+
+/// The list of media ranges in the header field `Accept`.
+///
+/// The entries are sorted in descending order of their quality values.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Accept {
+    ranges: Vec<MediaRange>,
+}
+
+impl Accept {
+    /// Parses all values of the header field `Accept` in the specified header map.
+    pub fn from_headers(headers: &HeaderMap) -> Result<Self, failure::Error> {
+        let mut ranges = vec![];
+        for value in headers.get_all(header::ACCEPT) {
+            parse_media_ranges(value.to_str()?, &mut ranges)?;
+        }
+        Ok(Accept::from_ranges(ranges))
+    }
+
+    fn from_ranges(mut ranges: Vec<MediaRange>) -> Self {
+        // The sort is stable, so that the ranges with the same quality keep their order.
+        ranges.sort_by(|a, b| b.quality.cmp(&a.quality));
+        Accept { ranges }
+    }
+
+    /// Returns an iterator over the media ranges.
+    pub fn iter(&self) -> slice::Iter<'_, MediaRange> {
+        self.ranges.iter()
+    }
+
+    /// Returns `true` if no media range is specified.
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Returns the quality value of the specified media type.
+    ///
+    /// The value is taken from the most specific media range which matches it,
+    /// and it is `0.0` if no range matches.
+    pub fn quality(&self, mime: &Mime) -> f32 {
+        if self.is_empty() {
+            return 1.0;
+        }
+        self.quality_raw(mime)
+            .map_or(0.0, |q| f32::from(q) / 1000.0)
+    }
+
+    fn quality_raw(&self, mime: &Mime) -> Option<u16> {
+        self.ranges
+            .iter()
+            .filter_map(|range| range.specificity(mime).map(|s| (s, range.quality)))
+            .fold(None, |acc: Option<(u8, u16)>, (s, q)| match acc {
+                Some((s0, _)) if s0 >= s => acc,
+                _ => Some((s, q)),
+            })
+            .map(|(_, q)| q)
+    }
+
+    /// Chooses the most preferable media type from the specified candidates.
+    ///
+    /// When some candidates have the same quality value, the earlier one is chosen.
+    /// It returns `None` if none of the candidates is acceptable.
+    pub fn preferred<'a>(&self, candidates: &'a [Mime]) -> Option<&'a Mime> {
+        if self.is_empty() {
+            return candidates.first();
+        }
+        candidates
+            .iter()
+            .filter_map(|mime| self.quality_raw(mime).map(|q| (q, mime)))
+            .filter(|&(q, _)| q > 0)
+            .fold(None, |acc: Option<(u16, &'a Mime)>, (q, mime)| match acc {
+                Some((q0, _)) if q0 >= q => acc,
+                _ => Some((q, mime)),
+            })
+            .map(|(_, mime)| mime)
+    }
+}
+
+impl<'a> IntoIterator for &'a Accept {
+    type Item = &'a MediaRange;
+    type IntoIter = slice::Iter<'a, MediaRange>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl FromHeaderValue for Accept {
+    type Error = failure::Error;
+
+    fn from_header_value(value: &HeaderValue) -> Result<Self, Self::Error> {
+        let mut ranges = vec![];
+        parse_media_ranges(value.to_str()?, &mut ranges)?;
+        Ok(Accept::from_ranges(ranges))
+    }
+}
+
+fn parse_media_ranges(s: &str, ranges: &mut Vec<MediaRange>) -> Result<(), failure::Error> {
+    for item in s.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        let mime: Mime = item.parse()?;
+        let quality = match mime.get_param("q") {
+            Some(q) => parse_quality(q.as_str())
+                .ok_or_else(|| failure::format_err!("invalid quality value: {}", q))?,
+            None => 1000,
+        };
+        ranges.push(MediaRange { mime, quality });
+    }
+    Ok(())
+}
+
+/// Chooses the content coding with the highest quality value in `Accept-Encoding`
+/// from `candidates`, which are listed in the order of preference.
+///
+/// Returns `None` if none of the candidates is acceptable.
+pub(crate) fn negotiate_encoding<'a>(
+    headers: &HeaderMap,
+    candidates: &[&'a str],
+) -> Option<&'a str> {
+    let mut wildcard = None;
+    let mut qualities = vec![None; candidates.len()];
+    for value in headers.get_all(header::ACCEPT_ENCODING) {
+        let value = match value.to_str() {
+            Ok(value) => value,
+            Err(..) => continue,
+        };
+        for item in value.split(',') {
+            let mut params = item.split(';');
+            let name = params.next().map(str::trim).unwrap_or("");
+            if name.is_empty() {
+                continue;
+            }
+            let mut quality = Some(1000);
+            for param in params {
+                let param = param.trim();
+                if param.len() >= 2 && param[..2].eq_ignore_ascii_case("q=") {
+                    quality = parse_quality(&param[2..]);
+                }
+            }
+            let quality = match quality {
+                Some(quality) => quality,
+                None => continue,
+            };
+
+            if name == "*" {
+                wildcard = Some(quality);
+                continue;
+            }
+            // `x-gzip` is an alias of `gzip` (RFC 7230, section 4.2.3).
+            let name = if name.eq_ignore_ascii_case("x-gzip") {
+                "gzip"
+            } else {
+                name
+            };
+            if let Some(i) = candidates.iter().position(|c| c.eq_ignore_ascii_case(name)) {
+                qualities[i] = Some(quality);
+            }
+        }
+    }
+
+    let mut chosen: Option<(&'a str, u16)> = None;
+    for (&candidate, quality) in candidates.iter().zip(qualities) {
+        let quality = quality.or(wildcard).unwrap_or(0);
+        if quality > 0 && chosen.map_or(true, |(_, q)| quality > q) {
+            chosen = Some((candidate, quality));
+        }
+    }
+    chosen.map(|(candidate, _)| candidate)
+}
+
+/// Parses a quality value into an integer in the range `0..=1000`.
+pub(crate) fn parse_quality(s: &str) -> Option<u16> {
+    let q: f32 = s.parse().ok()?;
+    if q < 0.0 || q > 1.0 {
+        return None;
+    }
+    Some(cmp::min((q * 1000.0).round() as u16, 1000))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &'static str) -> Accept {
+        Accept::from_header_value(&HeaderValue::from_static(s)).unwrap()
+    }
+
+    #[test]
+    fn test_parse_accept() {
+        let accept = parse("text/plain; q=0.5, application/json, text/*;q=0.8");
+        let ranges: Vec<_> = accept
+            .iter()
+            .map(|r| {
+                (
+                    format!("{}/{}", r.mime().type_(), r.mime().subtype()),
+                    r.quality,
+                )
+            })
+            .collect();
+        assert_eq!(
+            ranges,
+            vec![
+                ("application/json".to_owned(), 1000),
+                ("text/*".to_owned(), 800),
+                ("text/plain".to_owned(), 500),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_accept_invalid_quality() {
+        assert!(Accept::from_header_value(&HeaderValue::from_static("text/plain;q=2")).is_err());
+    }
+
+    #[test]
+    fn test_accept_quality() {
+        let accept = parse("text/*;q=0.3, text/html;q=0.7, */*;q=0.1");
+        assert_eq!(accept.quality_raw(&mime::TEXT_HTML), Some(700));
+        assert_eq!(accept.quality_raw(&mime::TEXT_PLAIN), Some(300));
+        assert_eq!(accept.quality_raw(&mime::IMAGE_PNG), Some(100));
+        assert_eq!(parse("text/*").quality_raw(&mime::IMAGE_PNG), None);
+    }
+
+    #[test]
+    fn test_accept_preferred() {
+        let candidates = [mime::APPLICATION_JSON, mime::TEXT_PLAIN];
+
+        let accept = parse("text/plain, application/json;q=0.9");
+        assert_eq!(accept.preferred(&candidates), Some(&mime::TEXT_PLAIN));
+
+        let accept = parse("*/*");
+        assert_eq!(accept.preferred(&candidates), Some(&mime::APPLICATION_JSON));
+
+        let accept = parse("image/*, text/plain;q=0");
+        assert_eq!(accept.preferred(&candidates), None);
+
+        assert_eq!(
+            Accept::default().preferred(&candidates),
+            Some(&mime::APPLICATION_JSON)
+        );
+    }
+}
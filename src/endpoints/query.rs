@@ -7,8 +7,12 @@ use {
             OneshotAction,
             PreflightContext, //
         },
-        endpoint::{Endpoint, IsEndpoint},
+        endpoint::{
+            describe::{Description, TypeSchema},
+            Endpoint, IsEndpoint,
+        },
         error::{self, Error},
+        openapi::Schema,
     },
     failure::SyncFailure,
     serde::de::DeserializeOwned,
@@ -50,6 +54,7 @@ where
     T: DeserializeOwned,
 {
     Required {
+        schema: None,
         _marker: PhantomData,
     }
 }
@@ -57,13 +62,31 @@ where
 #[allow(missing_docs)]
 #[derive(Debug)]
 pub struct Required<T> {
+    schema: Option<TypeSchema>,
     _marker: PhantomData<fn() -> T>,
 }
 
+impl<T: Schema> Required<T> {
+    /// Attaches the schema of `T` to the description of this endpoint.
+    ///
+    /// The properties of the schema are documented as the query parameters.
+    pub fn with_schema(mut self) -> Self {
+        self.schema = Some(TypeSchema::new(T::name(), T::schema));
+        self
+    }
+}
+
 mod required {
     use super::*;
 
-    impl<T: DeserializeOwned> IsEndpoint for Required<T> {}
+    impl<T: DeserializeOwned> IsEndpoint for Required<T> {
+        fn describe(&self) -> Description {
+            Description::Query {
+                required: true,
+                schema: self.schema,
+            }
+        }
+    }
 
     impl<T, Bd> Endpoint<Bd> for Required<T>
     where
@@ -138,6 +161,7 @@ where
     T: DeserializeOwned,
 {
     Optional {
+        schema: None,
         _marker: PhantomData,
     }
 }
@@ -145,13 +169,31 @@ where
 #[allow(missing_docs)]
 #[derive(Debug)]
 pub struct Optional<T> {
+    schema: Option<TypeSchema>,
     _marker: PhantomData<fn() -> T>,
 }
 
+impl<T: Schema> Optional<T> {
+    /// Attaches the schema of `T` to the description of this endpoint.
+    ///
+    /// The properties of the schema are documented as the query parameters.
+    pub fn with_schema(mut self) -> Self {
+        self.schema = Some(TypeSchema::new(T::name(), T::schema));
+        self
+    }
+}
+
 mod optional {
     use super::*;
 
-    impl<T: DeserializeOwned> IsEndpoint for Optional<T> {}
+    impl<T: DeserializeOwned> IsEndpoint for Optional<T> {
+        fn describe(&self) -> Description {
+            Description::Query {
+                required: false,
+                schema: self.schema,
+            }
+        }
+    }
 
     impl<T, Bd> Endpoint<Bd> for Optional<T>
     where
@@ -189,7 +231,6 @@ mod optional {
             }
         }
     }
-
 }
 
 /// Create an endpoint which extracts the query string from a request.
@@ -204,7 +245,14 @@ pub struct Raw(());
 mod raw {
     use super::*;
 
-    impl IsEndpoint for Raw {}
+    impl IsEndpoint for Raw {
+        fn describe(&self) -> Description {
+            Description::Query {
+                required: false,
+                schema: None,
+            }
+        }
+    }
 
     impl<Bd> Endpoint<Bd> for Raw {
         type Output = (Option<String>,);
@@ -45,12 +45,18 @@
 #![forbid(clippy::unimplemented)]
 #![doc(test(attr(deny(warnings))))]
 
+// The dependency is renamed so that the feature `brotli` can also enable it in `finchers-macros`.
+#[cfg(feature = "brotli")]
+extern crate brotli_crate as brotli;
+
 mod common;
 
 pub mod action;
 pub mod endpoint;
 pub mod endpoints;
 pub mod error;
+pub mod middleware;
+pub mod openapi;
 pub mod output;
 pub mod service;
 pub mod test;
@@ -0,0 +1,5 @@
+//! Built-in middlewares.
+
+pub mod compress;
+pub mod cors;
+pub mod log;
@@ -0,0 +1,634 @@
+//! A wrapper for compressing the response body.
+//!
+//! The wrapper negotiates the content coding with the value of
+//! `Accept-Encoding` in the request and encodes the response body
+//! on the fly.
+//!
+//! The supported codings are `gzip` and `deflate`, and also `br` if
+//! the feature `brotli` is enabled.
+//!
+//! # Example
+//!
+//! ```
+//! use finchers::prelude::*;
+//! use finchers::endpoint::syntax::path;
+//! use finchers::middleware::compress::compress;
+//!
+//! let endpoint = path!(@get "/posts")
+//!     .map(|| "posts")
+//!     .wrap(
+//!         compress()
+//!             .min_size(256)
+//!             .allow_content_type("application/xml"),
+//!     );
+//! # drop(endpoint);
+//! ```
+
+use {
+    crate::{
+        action::{
+            ActionContext, //
+            EndpointAction,
+            Preflight,
+            PreflightContext,
+        },
+        endpoint::{describe::Description, Endpoint, IsEndpoint, Wrapper},
+        endpoints::header::negotiate_encoding,
+        error::Error,
+        output::IntoResponse,
+    },
+    bytes::{Buf, Bytes},
+    either::Either,
+    flate2::{
+        write::{GzEncoder, ZlibEncoder},
+        Compression,
+    },
+    futures::{Async, Poll},
+    http::{
+        header::{self, HeaderMap, HeaderValue},
+        Request, Response, StatusCode,
+    },
+    izanami_util::buf_stream::BufStream,
+    mime::Mime,
+    std::{
+        fmt,
+        io::{self, Write},
+        mem,
+        sync::{Arc, Mutex},
+    },
+};
+
+/// Create a builder of the compression wrapper.
+///
+/// By default, the wrapper enables all of the supported codings and
+/// compresses the textual responses whose length is 1024 bytes or more.
+pub fn compress() -> Compress {
+    Compress {
+        gzip: true,
+        deflate: true,
+        #[cfg(feature = "brotli")]
+        brotli: true,
+        min_size: 1024,
+        content_types: DEFAULT_CONTENT_TYPES
+            .iter()
+            .map(|&s| s.to_owned())
+            .collect(),
+    }
+}
+
+const DEFAULT_CONTENT_TYPES: &[&str] = &[
+    "text/*",
+    "application/javascript",
+    "application/json",
+    "application/wasm",
+    "application/xml",
+    "image/svg+xml",
+];
+
+/// A builder of the compression wrapper.
+#[derive(Debug, Clone)]
+pub struct Compress {
+    gzip: bool,
+    deflate: bool,
+    #[cfg(feature = "brotli")]
+    brotli: bool,
+    min_size: u64,
+    content_types: Vec<String>,
+}
+
+impl Compress {
+    /// Sets whether to use the coding `gzip`.
+    pub fn gzip(self, enabled: bool) -> Self {
+        Self {
+            gzip: enabled,
+            ..self
+        }
+    }
+
+    /// Sets whether to use the coding `deflate`.
+    pub fn deflate(self, enabled: bool) -> Self {
+        Self {
+            deflate: enabled,
+            ..self
+        }
+    }
+
+    /// Sets whether to use the coding `br`.
+    #[cfg(feature = "brotli")]
+    pub fn brotli(self, enabled: bool) -> Self {
+        Self {
+            brotli: enabled,
+            ..self
+        }
+    }
+
+    /// Sets the minimum length of the response body to be compressed.
+    ///
+    /// The length is taken from `Content-Length`. The responses without
+    /// this header field are always compressed.
+    pub fn min_size(self, min_size: u64) -> Self {
+        Self { min_size, ..self }
+    }
+
+    /// Appends a media type to the list of compressible content types.
+    ///
+    /// The value is either a media type without parameters (e.g. `application/json`)
+    /// or a top-level type followed by `/*` (e.g. `text/*`).
+    pub fn allow_content_type(mut self, content_type: impl Into<String>) -> Self {
+        let mut content_type = content_type.into();
+        content_type.make_ascii_lowercase();
+        self.content_types.push(content_type);
+        self
+    }
+
+    /// Replaces the list of compressible content types with the specified values.
+    pub fn content_types<I>(mut self, content_types: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.content_types.clear();
+        content_types.into_iter().fold(self, |this, content_type| {
+            this.allow_content_type(content_type)
+        })
+    }
+
+    fn into_config(self) -> Config {
+        let mut encodings = vec![];
+        #[cfg(feature = "brotli")]
+        {
+            if self.brotli {
+                encodings.push(Encoding::Brotli);
+            }
+        }
+        if self.gzip {
+            encodings.push(Encoding::Gzip);
+        }
+        if self.deflate {
+            encodings.push(Encoding::Deflate);
+        }
+
+        Config {
+            encodings,
+            min_size: self.min_size,
+            content_types: self.content_types,
+        }
+    }
+}
+
+impl<E: IsEndpoint> Wrapper<E> for Compress {
+    type Endpoint = CompressEndpoint<E>;
+
+    fn wrap(self, endpoint: E) -> Self::Endpoint {
+        CompressEndpoint {
+            endpoint,
+            config: Arc::new(self.into_config()),
+        }
+    }
+}
+
+/// A content coding supported by the compression wrapper.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Encoding {
+    /// The coding `gzip`.
+    Gzip,
+
+    /// The coding `deflate`, i.e. the zlib format.
+    Deflate,
+
+    /// The coding `br`.
+    #[cfg(feature = "brotli")]
+    Brotli,
+}
+
+impl Encoding {
+    /// Returns the name of this coding used in `Content-Encoding`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Encoding::Gzip => "gzip",
+            Encoding::Deflate => "deflate",
+            #[cfg(feature = "brotli")]
+            Encoding::Brotli => "br",
+        }
+    }
+
+    fn encoder(self, buf: SharedBuf) -> Box<dyn Encode> {
+        match self {
+            Encoding::Gzip => Box::new(GzEncoder::new(buf, Compression::default())),
+            Encoding::Deflate => Box::new(ZlibEncoder::new(buf, Compression::default())),
+            #[cfg(feature = "brotli")]
+            Encoding::Brotli => Box::new(brotli::CompressorWriter::new(buf, 4096, 5, 22)),
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug)]
+struct Config {
+    encodings: Vec<Encoding>,
+    min_size: u64,
+    content_types: Vec<String>,
+}
+
+impl Config {
+    /// Chooses the coding with the highest quality value in `Accept-Encoding`.
+    ///
+    /// If some codings have the same quality, the one appearing first in
+    /// `self.encodings` is preferred.
+    fn negotiate(&self, headers: &HeaderMap) -> Option<Encoding> {
+        let names: Vec<_> = self.encodings.iter().map(|e| e.as_str()).collect();
+        let name = negotiate_encoding(headers, &names)?;
+        self.encodings.iter().cloned().find(|e| e.as_str() == name)
+    }
+
+    /// Returns whether the specified response is a target of compression.
+    fn is_compressible(&self, response: &Response<impl Sized>) -> bool {
+        let status = response.status();
+        if status.is_informational()
+            || status == StatusCode::NO_CONTENT
+            || status == StatusCode::NOT_MODIFIED
+        {
+            return false;
+        }
+
+        let headers = response.headers();
+        if headers.contains_key(header::CONTENT_ENCODING)
+            || headers.contains_key(header::CONTENT_RANGE)
+        {
+            return false;
+        }
+
+        let no_transform = headers
+            .get_all(header::CACHE_CONTROL)
+            .iter()
+            .filter_map(|h| h.to_str().ok())
+            .flat_map(|h| h.split(','))
+            .any(|directive| directive.trim().eq_ignore_ascii_case("no-transform"));
+        if no_transform {
+            return false;
+        }
+
+        let len = headers
+            .get(header::CONTENT_LENGTH)
+            .and_then(|h| h.to_str().ok())
+            .and_then(|h| h.parse::<u64>().ok());
+        if len.map_or(false, |len| len < self.min_size) {
+            return false;
+        }
+
+        headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|h| h.to_str().ok())
+            .and_then(|h| h.parse::<Mime>().ok())
+            .map_or(false, |mime| self.allows_content_type(&mime))
+    }
+
+    fn allows_content_type(&self, mime: &Mime) -> bool {
+        let type_ = mime.type_().as_str();
+        let subtype = mime.subtype().as_str();
+        self.content_types.iter().any(|allowed| {
+            let mut parts = allowed.splitn(2, '/');
+            match (parts.next(), parts.next()) {
+                (Some(t), Some("*")) => t.eq_ignore_ascii_case(type_),
+                (Some(t), Some(s)) => {
+                    t.eq_ignore_ascii_case(type_) && s.eq_ignore_ascii_case(subtype)
+                }
+                _ => false,
+            }
+        })
+    }
+}
+
+/// An endpoint wrapped by `Compress`.
+#[derive(Debug)]
+pub struct CompressEndpoint<E> {
+    endpoint: E,
+    config: Arc<Config>,
+}
+
+impl<E: IsEndpoint> IsEndpoint for CompressEndpoint<E> {
+    fn describe(&self) -> Description {
+        self.endpoint.describe()
+    }
+}
+
+impl<E, T, Bd> Endpoint<Bd> for CompressEndpoint<E>
+where
+    E: Endpoint<Bd, Output = (T,)>,
+{
+    type Output = (Compressed<T>,);
+    type Action = CompressAction<E::Action>;
+
+    fn action(&self) -> Self::Action {
+        CompressAction {
+            action: self.endpoint.action(),
+            config: self.config.clone(),
+        }
+    }
+}
+
+#[doc(hidden)]
+#[allow(missing_debug_implementations)]
+pub struct CompressAction<A> {
+    action: A,
+    config: Arc<Config>,
+}
+
+impl<A, T, Bd> EndpointAction<Bd> for CompressAction<A>
+where
+    A: EndpointAction<Bd, Output = (T,)>,
+{
+    type Output = (Compressed<T>,);
+
+    fn preflight(
+        &mut self,
+        cx: &mut PreflightContext<'_>,
+    ) -> Result<Preflight<Self::Output>, Error> {
+        let config = &self.config;
+        self.action
+            .preflight(cx)
+            .map(|x| x.map(|(output,)| (Compressed::new(output, config),)))
+    }
+
+    fn poll_action(&mut self, cx: &mut ActionContext<'_, Bd>) -> Poll<Self::Output, Error> {
+        let config = &self.config;
+        self.action
+            .poll_action(cx)
+            .map(|x| x.map(|(output,)| (Compressed::new(output, config),)))
+    }
+}
+
+/// The output of the endpoint wrapped by `Compress`.
+#[derive(Debug)]
+pub struct Compressed<T> {
+    output: T,
+    config: Arc<Config>,
+}
+
+impl<T> Compressed<T> {
+    fn new(output: T, config: &Arc<Config>) -> Self {
+        Compressed {
+            output,
+            config: config.clone(),
+        }
+    }
+
+    /// Consumes itself and returns the inner output.
+    pub fn into_inner(self) -> T {
+        self.output
+    }
+}
+
+impl<T> IntoResponse for Compressed<T>
+where
+    T: IntoResponse,
+{
+    type Body = CompressedBody<T::Body>;
+
+    fn into_response(self, request: &Request<()>) -> Response<Self::Body> {
+        let response = self.output.into_response(request);
+        if !self.config.is_compressible(&response) {
+            return response.map(CompressedBody::identity);
+        }
+
+        let (mut parts, body) = response.into_parts();
+        parts
+            .headers
+            .append(header::VARY, HeaderValue::from_static("accept-encoding"));
+
+        match self.config.negotiate(request.headers()) {
+            Some(encoding) => {
+                parts.headers.remove(header::CONTENT_LENGTH);
+                // The encoded body is not byte-for-byte identical to the
+                // original representation, so the strong validator is weakened.
+                if let Some(weak) = parts.headers.get(header::ETAG).and_then(weaken_etag) {
+                    parts.headers.insert(header::ETAG, weak);
+                }
+                parts.headers.insert(
+                    header::CONTENT_ENCODING,
+                    HeaderValue::from_static(encoding.as_str()),
+                );
+                Response::from_parts(parts, CompressedBody::encoded(body, encoding))
+            }
+            None => Response::from_parts(parts, CompressedBody::identity(body)),
+        }
+    }
+}
+
+/// Converts a strong entity tag into the weak one.
+fn weaken_etag(etag: &HeaderValue) -> Option<HeaderValue> {
+    if etag.as_bytes().starts_with(b"W/") {
+        return None;
+    }
+    let mut weak = b"W/".to_vec();
+    weak.extend_from_slice(etag.as_bytes());
+    HeaderValue::from_bytes(&weak).ok()
+}
+
+// ==== CompressedBody ====
+
+type BoxedStdError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// The message body of `Compressed`.
+#[allow(missing_debug_implementations)]
+pub struct CompressedBody<Bd> {
+    body: Bd,
+    kind: BodyKind,
+}
+
+enum BodyKind {
+    Identity,
+    Encoded {
+        encoder: Option<Box<dyn Encode>>,
+        buf: SharedBuf,
+    },
+}
+
+impl<Bd> CompressedBody<Bd> {
+    fn identity(body: Bd) -> Self {
+        CompressedBody {
+            body,
+            kind: BodyKind::Identity,
+        }
+    }
+
+    fn encoded(body: Bd, encoding: Encoding) -> Self {
+        let buf = SharedBuf::default();
+        CompressedBody {
+            body,
+            kind: BodyKind::Encoded {
+                encoder: Some(encoding.encoder(buf.clone())),
+                buf,
+            },
+        }
+    }
+}
+
+impl<Bd> BufStream for CompressedBody<Bd>
+where
+    Bd: BufStream,
+    Bd::Error: Into<BoxedStdError>,
+{
+    type Item = Either<Bd::Item, io::Cursor<Bytes>>;
+    type Error = BoxedStdError;
+
+    fn poll_buf(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        let (encoder, buf) = match self.kind {
+            BodyKind::Identity => {
+                return self
+                    .body
+                    .poll_buf()
+                    .map(|x| x.map(|chunk| chunk.map(Either::Left)))
+                    .map_err(Into::into);
+            }
+            BodyKind::Encoded {
+                ref mut encoder,
+                ref buf,
+            } => (encoder, buf),
+        };
+
+        loop {
+            let polled = match encoder {
+                Some(..) => match self.body.poll_buf() {
+                    Ok(Async::Ready(chunk)) => chunk,
+                    Ok(Async::NotReady) => return Ok(Async::NotReady),
+                    Err(err) => return Err(err.into()),
+                },
+                None => return Ok(Async::Ready(None)),
+            };
+
+            match polled {
+                Some(mut chunk) => {
+                    let encoder = encoder.as_mut().expect("the encoder should be available");
+                    while chunk.has_remaining() {
+                        let n = {
+                            let bytes = chunk.bytes();
+                            encoder.write_all(bytes)?;
+                            bytes.len()
+                        };
+                        chunk.advance(n);
+                    }
+                }
+                None => {
+                    encoder
+                        .take()
+                        .expect("the encoder should be available")
+                        .finish()?;
+                }
+            }
+
+            let encoded = buf.take();
+            if !encoded.is_empty() {
+                return Ok(Async::Ready(Some(Either::Right(io::Cursor::new(
+                    Bytes::from(encoded),
+                )))));
+            }
+        }
+    }
+}
+
+/// A streaming encoder which writes the compressed data into a `SharedBuf`.
+trait Encode: Write + Send {
+    /// Writes the remaining data and the trailer of the encoded stream.
+    fn finish(self: Box<Self>) -> io::Result<()>;
+}
+
+impl Encode for GzEncoder<SharedBuf> {
+    fn finish(self: Box<Self>) -> io::Result<()> {
+        (*self).finish().map(drop)
+    }
+}
+
+impl Encode for ZlibEncoder<SharedBuf> {
+    fn finish(self: Box<Self>) -> io::Result<()> {
+        (*self).finish().map(drop)
+    }
+}
+
+#[cfg(feature = "brotli")]
+impl Encode for brotli::CompressorWriter<SharedBuf> {
+    fn finish(mut self: Box<Self>) -> io::Result<()> {
+        // `into_inner` discards the errors that occur while closing the
+        // stream, so the pending data is flushed beforehand to report them.
+        // After that, only the trailer is written into the infallible buffer.
+        self.flush()?;
+        drop((*self).into_inner());
+        Ok(())
+    }
+}
+
+/// An output buffer shared between the encoder and `CompressedBody`.
+#[derive(Debug, Clone, Default)]
+struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuf {
+    fn take(&self) -> Vec<u8> {
+        let mut buf = self.0.lock().expect("the buffer should not be poisoned");
+        mem::replace(&mut *buf, Vec::new())
+    }
+}
+
+impl Write for SharedBuf {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.0
+            .lock()
+            .expect("the buffer should not be poisoned")
+            .extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn negotiate(value: &'static str) -> Option<Encoding> {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT_ENCODING, HeaderValue::from_static(value));
+        compress().into_config().negotiate(&headers)
+    }
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(negotiate("gzip, deflate"), Some(Encoding::Gzip));
+        assert_eq!(negotiate("deflate"), Some(Encoding::Deflate));
+        assert_eq!(negotiate("gzip;q=0.5, deflate"), Some(Encoding::Deflate));
+        assert_eq!(negotiate("gzip;q=0, deflate;q=0"), None);
+        assert_eq!(negotiate("identity"), None);
+        assert_eq!(negotiate("X-GZIP"), Some(Encoding::Gzip));
+    }
+
+    #[test]
+    fn test_negotiate_wildcard() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::ACCEPT_ENCODING,
+            HeaderValue::from_static("*;q=0.5, gzip;q=0"),
+        );
+        let config = compress().gzip(false).into_config();
+        assert_eq!(config.negotiate(&headers), Some(Encoding::Deflate));
+
+        let config = compress().deflate(false).into_config();
+        #[cfg(not(feature = "brotli"))]
+        assert_eq!(config.negotiate(&headers), None);
+        #[cfg(feature = "brotli")]
+        assert_eq!(config.negotiate(&headers), Some(Encoding::Brotli));
+    }
+
+    #[test]
+    fn test_allows_content_type() {
+        let config = compress().into_config();
+        assert!(config.allows_content_type(&mime::TEXT_HTML_UTF_8));
+        assert!(config.allows_content_type(&mime::APPLICATION_JSON));
+        assert!(!config.allows_content_type(&mime::IMAGE_PNG));
+    }
+}
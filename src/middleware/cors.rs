@@ -0,0 +1,464 @@
+//! A wrapper for supporting Cross-Origin Resource Sharing (CORS).
+//!
+//! # Example
+//!
+//! ```
+//! use finchers::prelude::*;
+//! use finchers::endpoint::syntax::{path, verb::Verbs};
+//! use finchers::middleware::cors::cors;
+//! use std::time::Duration;
+//!
+//! let endpoint = path!(@get "/posts")
+//!     .map(|| "posts")
+//!     .wrap(
+//!         cors()
+//!             .allow_origin("https://example.com")
+//!             .allow_methods(Verbs::GET | Verbs::POST)
+//!             .allow_header("x-api-key")
+//!             .max_age(Duration::from_secs(3600)),
+//!     );
+//! # drop(endpoint);
+//! ```
+
+use {
+    crate::{
+        action::{
+            ActionContext, //
+            EndpointAction,
+            Preflight,
+            PreflightContext,
+        },
+        endpoint::{
+            describe::Description,
+            syntax::verb::{MethodNotAllowed, Verbs},
+            Endpoint, IsEndpoint, Wrapper,
+        },
+        error::{Error, HttpError},
+        output::IntoResponse,
+    },
+    either::Either,
+    futures::{Async, Poll},
+    http::{
+        header::{self, HeaderMap, HeaderName, HeaderValue},
+        HttpTryFrom, Method, Request, Response, StatusCode,
+    },
+    std::{fmt, sync::Arc, time::Duration},
+};
+
+/// Create a builder of the CORS wrapper.
+///
+/// By default, the wrapper allows the requests from any origin with the methods
+/// `GET`, `HEAD` and `POST`.
+pub fn cors() -> Cors {
+    Cors {
+        origins: None,
+        methods: Verbs::GET | Verbs::HEAD | Verbs::POST,
+        headers: Some(vec![]),
+        exposed_headers: vec![],
+        credentials: false,
+        max_age: None,
+    }
+}
+
+/// A builder of the CORS wrapper.
+#[derive(Debug, Clone)]
+pub struct Cors {
+    origins: Option<Vec<HeaderValue>>,
+    methods: Verbs,
+    headers: Option<Vec<HeaderName>>,
+    exposed_headers: Vec<HeaderName>,
+    credentials: bool,
+    max_age: Option<Duration>,
+}
+
+impl Cors {
+    /// Adds an origin to the set of allowed origins.
+    ///
+    /// If this method is never called, the requests from any origin are allowed.
+    ///
+    /// The preflight requests from the other origins are rejected with
+    /// `403 Forbidden`, and the actual requests from them are processed
+    /// without the CORS headers so that the browser enforces the policy.
+    pub fn allow_origin(mut self, origin: &str) -> Self {
+        let origin = HeaderValue::from_str(origin).expect("invalid origin");
+        self.origins.get_or_insert_with(Vec::new).push(origin);
+        self
+    }
+
+    /// Sets the set of allowed methods.
+    pub fn allow_methods(self, methods: Verbs) -> Self {
+        Cors { methods, ..self }
+    }
+
+    /// Adds a header name to the set of allowed request headers.
+    pub fn allow_header<H>(mut self, name: H) -> Self
+    where
+        HeaderName: HttpTryFrom<H>,
+        <HeaderName as HttpTryFrom<H>>::Error: fmt::Debug,
+    {
+        let name = HeaderName::try_from(name).expect("invalid header name");
+        if let Some(ref mut headers) = self.headers {
+            headers.push(name);
+        }
+        self
+    }
+
+    /// Allows all request headers requested by the clients.
+    pub fn allow_any_header(self) -> Self {
+        Cors {
+            headers: None,
+            ..self
+        }
+    }
+
+    /// Adds a header name to the set of response headers exposed to the clients.
+    pub fn expose_header<H>(mut self, name: H) -> Self
+    where
+        HeaderName: HttpTryFrom<H>,
+        <HeaderName as HttpTryFrom<H>>::Error: fmt::Debug,
+    {
+        let name = HeaderName::try_from(name).expect("invalid header name");
+        self.exposed_headers.push(name);
+        self
+    }
+
+    /// Sets whether to allow the requests with credentials.
+    ///
+    /// The credentials can be allowed only if the set of allowed origins is
+    /// given explicitly by `allow_origin`, since reflecting any origin with
+    /// credentials would expose the resources to every site.
+    pub fn allow_credentials(self, enabled: bool) -> Self {
+        Cors {
+            credentials: enabled,
+            ..self
+        }
+    }
+
+    /// Sets the duration how long the results of a preflight request can be cached.
+    pub fn max_age(self, max_age: Duration) -> Self {
+        Cors {
+            max_age: Some(max_age),
+            ..self
+        }
+    }
+
+    fn into_config(self) -> Config {
+        assert!(
+            !self.credentials || self.origins.is_some(),
+            "the allowed origins must be specified explicitly when the credentials are allowed"
+        );
+
+        fn join<T: AsRef<str>>(items: &[T]) -> Option<HeaderValue> {
+            if items.is_empty() {
+                return None;
+            }
+            let joined = items
+                .iter()
+                .map(|item| item.as_ref())
+                .collect::<Vec<_>>()
+                .join(", ");
+            Some(HeaderValue::from_str(&joined).expect("should be a valid header value"))
+        }
+
+        Config {
+            origins: self.origins,
+            methods: self.methods,
+            allow_headers: self.headers.as_ref().and_then(|headers| join(&headers[..])),
+            headers: self.headers,
+            expose_headers: join(&self.exposed_headers[..]),
+            credentials: self.credentials,
+            max_age: self
+                .max_age
+                .map(|max_age| HeaderValue::from(max_age.as_secs())),
+        }
+    }
+}
+
+impl<E: IsEndpoint> Wrapper<E> for Cors {
+    type Endpoint = CorsEndpoint<E>;
+
+    fn wrap(self, endpoint: E) -> Self::Endpoint {
+        CorsEndpoint {
+            endpoint,
+            config: Arc::new(self.into_config()),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Config {
+    origins: Option<Vec<HeaderValue>>,
+    methods: Verbs,
+    headers: Option<Vec<HeaderName>>,
+    allow_headers: Option<HeaderValue>,
+    expose_headers: Option<HeaderValue>,
+    credentials: bool,
+    max_age: Option<HeaderValue>,
+}
+
+impl Config {
+    fn validate_origin(&self, origin: &HeaderValue) -> Result<(), CorsError> {
+        match self.origins {
+            Some(ref origins) if !origins.contains(origin) => Err(CorsError::DisallowedOrigin),
+            _ => Ok(()),
+        }
+    }
+
+    fn allow_origin(&self, origin: &HeaderValue) -> HeaderValue {
+        match self.origins {
+            Some(..) => origin.clone(),
+            None => HeaderValue::from_static("*"),
+        }
+    }
+
+    /// Creates the response headers for a preflight request, with the set of
+    /// methods allowed by the route matched to the request.
+    fn preflight_headers(
+        &self,
+        origin: &HeaderValue,
+        request: &Request<()>,
+        allowed: Verbs,
+    ) -> Result<HeaderMap, CorsError> {
+        let method = request
+            .headers()
+            .get(header::ACCESS_CONTROL_REQUEST_METHOD)
+            .and_then(|h| Method::from_bytes(h.as_bytes()).ok())
+            .ok_or(CorsError::InvalidRequestMethod)?;
+        let allowed = self.methods & allowed;
+        if !allowed.contains(&method) {
+            return Err(CorsError::DisallowedMethod);
+        }
+
+        let requested_headers = request
+            .headers()
+            .get(header::ACCESS_CONTROL_REQUEST_HEADERS)
+            .cloned();
+        if let Some(ref requested_headers) = requested_headers {
+            let requested_headers = requested_headers
+                .to_str()
+                .map_err(|_| CorsError::InvalidRequestHeaders)?;
+            for name in requested_headers.split(',').map(|s| s.trim()) {
+                if name.is_empty() {
+                    continue;
+                }
+                let name = HeaderName::from_bytes(name.as_bytes())
+                    .map_err(|_| CorsError::InvalidRequestHeaders)?;
+                if let Some(ref headers) = self.headers {
+                    if !headers.contains(&name) {
+                        return Err(CorsError::DisallowedHeader);
+                    }
+                }
+            }
+        }
+
+        let mut headers = self.common_headers(origin);
+        headers.insert(
+            header::ACCESS_CONTROL_ALLOW_METHODS,
+            HeaderValue::from_str(&allowed.to_string()).expect("should be a valid header value"),
+        );
+        match self.headers {
+            Some(..) => {
+                if let Some(ref allow_headers) = self.allow_headers {
+                    headers.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, allow_headers.clone());
+                }
+            }
+            None => {
+                if let Some(requested_headers) = requested_headers {
+                    headers.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, requested_headers);
+                }
+            }
+        }
+        if let Some(ref max_age) = self.max_age {
+            headers.insert(header::ACCESS_CONTROL_MAX_AGE, max_age.clone());
+        }
+        Ok(headers)
+    }
+
+    fn actual_headers(&self, origin: &HeaderValue) -> HeaderMap {
+        let mut headers = self.common_headers(origin);
+        if let Some(ref expose_headers) = self.expose_headers {
+            headers.insert(
+                header::ACCESS_CONTROL_EXPOSE_HEADERS,
+                expose_headers.clone(),
+            );
+        }
+        headers
+    }
+
+    fn common_headers(&self, origin: &HeaderValue) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::ACCESS_CONTROL_ALLOW_ORIGIN,
+            self.allow_origin(origin),
+        );
+        if self.credentials {
+            headers.insert(
+                header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
+        if self.origins.is_some() {
+            headers.insert(header::VARY, HeaderValue::from_static("origin"));
+        }
+        headers
+    }
+}
+
+/// An endpoint wrapped by `Cors`.
+#[derive(Debug)]
+pub struct CorsEndpoint<E> {
+    endpoint: E,
+    config: Arc<Config>,
+}
+
+impl<E: IsEndpoint> IsEndpoint for CorsEndpoint<E> {
+    fn describe(&self) -> Description {
+        self.endpoint.describe()
+    }
+}
+
+impl<E, T, Bd> Endpoint<Bd> for CorsEndpoint<E>
+where
+    E: Endpoint<Bd, Output = (T,)>,
+{
+    type Output = (Either<CorsPreflight, T>,);
+    type Action = CorsAction<E::Action, T>;
+
+    fn action(&self) -> Self::Action {
+        CorsAction {
+            action: self.endpoint.action(),
+            config: self.config.clone(),
+            headers: None,
+            output: None,
+        }
+    }
+}
+
+#[doc(hidden)]
+#[allow(missing_debug_implementations)]
+pub struct CorsAction<A, T> {
+    action: A,
+    config: Arc<Config>,
+    headers: Option<HeaderMap>,
+    output: Option<T>,
+}
+
+impl<A, T, Bd> EndpointAction<Bd> for CorsAction<A, T>
+where
+    A: EndpointAction<Bd, Output = (T,)>,
+{
+    type Output = (Either<CorsPreflight, T>,);
+
+    fn preflight(
+        &mut self,
+        cx: &mut PreflightContext<'_>,
+    ) -> Result<Preflight<Self::Output>, Error> {
+        let origin = match cx.headers().get(header::ORIGIN) {
+            Some(origin) => origin.clone(),
+            None => {
+                return self
+                    .action
+                    .preflight(cx)
+                    .map(|x| x.map(|(out,)| (Either::Right(out),)));
+            }
+        };
+        if *cx.method() == Method::OPTIONS
+            && cx
+                .headers()
+                .contains_key(header::ACCESS_CONTROL_REQUEST_METHOD)
+        {
+            // The preflight request is answered only if the inner endpoint
+            // matches the path. The allowed methods are taken from the error
+            // returned by the router, since the method OPTIONS is typically
+            // rejected by the matched route.
+            let allowed = match self.action.preflight(cx) {
+                Ok(..) => Verbs::any(),
+                Err(err) => match err.downcast_ref::<MethodNotAllowed>() {
+                    Some(err) => err.allowed(),
+                    None => return Err(err),
+                },
+            };
+            self.config.validate_origin(&origin)?;
+            let headers = self
+                .config
+                .preflight_headers(&origin, cx.request(), allowed)?;
+            return Ok(Preflight::Completed((Either::Left(CorsPreflight {
+                headers,
+            }),)));
+        }
+
+        // The actual request from a disallowed origin is not rejected here,
+        // since the same-origin requests may also have `Origin`. The CORS
+        // headers are omitted instead and the browser enforces the policy.
+        //
+        // The CORS headers are appended to the response within `poll_action`,
+        // since the request context cannot be modified in this method.
+        if self.config.validate_origin(&origin).is_ok() {
+            self.headers = Some(self.config.actual_headers(&origin));
+        }
+        if let Preflight::Completed((out,)) = self.action.preflight(cx)? {
+            self.output = Some(out);
+        }
+        Ok(Preflight::Incomplete)
+    }
+
+    fn poll_action(&mut self, cx: &mut ActionContext<'_, Bd>) -> Poll<Self::Output, Error> {
+        if let Some(headers) = self.headers.take() {
+            let response_headers = cx.response_headers();
+            for (name, value) in &headers {
+                response_headers.append(name, value.clone());
+            }
+        }
+
+        if let Some(out) = self.output.take() {
+            return Ok(Async::Ready((Either::Right(out),)));
+        }
+
+        self.action
+            .poll_action(cx)
+            .map(|x| x.map(|(out,)| (Either::Right(out),)))
+    }
+}
+
+/// The response to a CORS preflight request.
+#[derive(Debug)]
+pub struct CorsPreflight {
+    headers: HeaderMap,
+}
+
+impl IntoResponse for CorsPreflight {
+    type Body = &'static [u8];
+
+    fn into_response(self, _: &Request<()>) -> Response<Self::Body> {
+        let mut response = Response::new(&[] as &[u8]);
+        *response.status_mut() = StatusCode::NO_CONTENT;
+        *response.headers_mut() = self.headers;
+        response
+    }
+}
+
+/// The error type returned when a CORS request is rejected.
+#[allow(missing_docs)]
+#[derive(Debug, failure::Fail)]
+pub enum CorsError {
+    #[fail(display = "the origin is not allowed")]
+    DisallowedOrigin,
+
+    #[fail(display = "missing or invalid Access-Control-Request-Method")]
+    InvalidRequestMethod,
+
+    #[fail(display = "the requested method is not allowed")]
+    DisallowedMethod,
+
+    #[fail(display = "invalid Access-Control-Request-Headers")]
+    InvalidRequestHeaders,
+
+    #[fail(display = "the requested header is not allowed")]
+    DisallowedHeader,
+}
+
+impl HttpError for CorsError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::FORBIDDEN
+    }
+}
@@ -0,0 +1,536 @@
+//! A middleware for logging the HTTP requests and responses.
+//!
+//! # Example
+//!
+//! ```
+//! use finchers::prelude::*;
+//! use finchers::middleware::log::{log, stdlog, ApacheCombined};
+//!
+//! # fn main() -> izanami::Result<()> {
+//! let endpoint = endpoint::value("Hello");
+//!
+//! let service = endpoint
+//!     .into_service()
+//!     .with_layer(log(stdlog(ApacheCombined)));
+//! # drop(move || -> izanami::Result<_> {
+//! izanami::Server::bind_tcp(&"127.0.0.1:4000".parse()?)?
+//!     .start(service)
+//! # });
+//! # Ok(())
+//! # }
+//! ```
+
+use {
+    crate::service::Layer,
+    bytes::Buf,
+    futures::{Async, Future, Poll},
+    http::{
+        header::{self, HeaderValue},
+        Method, Request, Response, StatusCode, Uri, Version,
+    },
+    izanami_service::Service,
+    izanami_util::buf_stream::BufStream,
+    std::{
+        fmt,
+        sync::Arc,
+        time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+    },
+};
+
+/// A trait representing the logger which starts logging for each request.
+pub trait Logger {
+    /// The type of logging session returned from `start`.
+    type Instance: Logging;
+
+    /// Starts a logging session for the specified request.
+    fn start<T>(&self, request: &Request<T>) -> Self::Instance;
+}
+
+/// A trait representing a logging session associated with a request.
+pub trait Logging {
+    /// Finishes this logging session.
+    ///
+    /// This method is called after the whole of response body has been sent
+    /// (or the transfer has been aborted), with the sent response and the number
+    /// of bytes in its message body.
+    fn finish(self, response: &Response<()>, body_size: u64);
+}
+
+/// Create a `Layer` which applies the specified `Logger` to every request.
+pub fn log<L>(logger: L) -> LogLayer<L>
+where
+    L: Logger,
+{
+    LogLayer {
+        logger: Arc::new(logger),
+    }
+}
+
+#[allow(missing_docs)]
+#[derive(Debug)]
+pub struct LogLayer<L> {
+    logger: Arc<L>,
+}
+
+impl<S, L> Layer<S> for LogLayer<L>
+where
+    L: Logger,
+{
+    type Service = LogService<S, L>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        LogService {
+            inner,
+            logger: self.logger.clone(),
+        }
+    }
+}
+
+#[allow(missing_docs)]
+#[derive(Debug)]
+pub struct LogService<S, L> {
+    inner: S,
+    logger: Arc<L>,
+}
+
+impl<S, L, Bd, RespBd> Service<Request<Bd>> for LogService<S, L>
+where
+    S: Service<Request<Bd>, Response = Response<RespBd>>,
+    L: Logger,
+    RespBd: BufStream,
+{
+    type Response = Response<LogBody<RespBd, L::Instance>>;
+    type Error = S::Error;
+    type Future = LogFuture<S::Future, L::Instance>;
+
+    #[inline]
+    fn poll_ready(&mut self) -> Poll<(), Self::Error> {
+        self.inner.poll_ready()
+    }
+
+    fn call(&mut self, request: Request<Bd>) -> Self::Future {
+        let instance = self.logger.start(&request);
+        LogFuture {
+            future: self.inner.call(request),
+            instance: Some(instance),
+        }
+    }
+}
+
+#[allow(missing_docs)]
+#[derive(Debug)]
+pub struct LogFuture<F, I> {
+    future: F,
+    instance: Option<I>,
+}
+
+impl<F, I, Bd> Future for LogFuture<F, I>
+where
+    F: Future<Item = Response<Bd>>,
+    I: Logging,
+{
+    type Item = Response<LogBody<Bd, I>>;
+    type Error = F::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let response = futures::try_ready!(self.future.poll());
+
+        let mut head = Response::new(());
+        *head.status_mut() = response.status();
+        *head.version_mut() = response.version();
+        *head.headers_mut() = response.headers().clone();
+
+        let instance = self.instance.take().expect("the future has already polled");
+        Ok(Async::Ready(response.map(|body| LogBody {
+            body,
+            session: Some((instance, head)),
+            body_size: 0,
+        })))
+    }
+}
+
+/// The type of response body which notifies its completion to the logger.
+#[derive(Debug)]
+pub struct LogBody<Bd, I: Logging> {
+    body: Bd,
+    session: Option<(I, Response<()>)>,
+    body_size: u64,
+}
+
+impl<Bd, I: Logging> LogBody<Bd, I> {
+    fn finish(&mut self) {
+        if let Some((instance, head)) = self.session.take() {
+            instance.finish(&head, self.body_size);
+        }
+    }
+}
+
+impl<Bd, I> BufStream for LogBody<Bd, I>
+where
+    Bd: BufStream,
+    I: Logging,
+{
+    type Item = Bd::Item;
+    type Error = Bd::Error;
+
+    fn poll_buf(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        match self.body.poll_buf() {
+            Ok(Async::Ready(Some(chunk))) => {
+                self.body_size += chunk.remaining() as u64;
+                Ok(Async::Ready(Some(chunk)))
+            }
+            Ok(Async::Ready(None)) => {
+                self.finish();
+                Ok(Async::Ready(None))
+            }
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            Err(err) => {
+                self.finish();
+                Err(err)
+            }
+        }
+    }
+}
+
+impl<Bd, I: Logging> Drop for LogBody<Bd, I> {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+// ==== StdLog ====
+
+/// A summary of an HTTP request and the corresponding response, passed to `Format`.
+#[derive(Debug)]
+pub struct Record<'a> {
+    /// The time when the request was received.
+    pub time: SystemTime,
+
+    /// The elapsed time from receiving the request to the completion of response.
+    pub elapsed: Duration,
+
+    /// The method of the request.
+    pub method: &'a Method,
+
+    /// The URI of the request.
+    pub uri: &'a Uri,
+
+    /// The HTTP version of the request.
+    pub version: Version,
+
+    /// The value of `Referer` in the request, if exists.
+    pub referer: Option<&'a str>,
+
+    /// The value of `User-Agent` in the request, if exists.
+    pub user_agent: Option<&'a str>,
+
+    /// The status code of the response.
+    pub status: StatusCode,
+
+    /// The number of bytes in the response body.
+    pub body_size: u64,
+
+    _priv: (),
+}
+
+/// A trait representing the format of log lines emitted by `StdLog`.
+pub trait Format {
+    /// Formats the specified record into a line.
+    fn format(&self, record: &Record<'_>) -> String;
+}
+
+impl<F> Format for F
+where
+    F: Fn(&Record<'_>) -> String,
+{
+    fn format(&self, record: &Record<'_>) -> String {
+        (*self)(record)
+    }
+}
+
+/// Create a `Logger` which emits a line for each request through the `log` crate.
+///
+/// The lines are emitted at the `INFO` level with the target `finchers::access`.
+pub fn stdlog<F>(format: F) -> StdLog<F>
+where
+    F: Format,
+{
+    StdLog {
+        format: Arc::new(format),
+    }
+}
+
+#[allow(missing_docs)]
+#[derive(Debug)]
+pub struct StdLog<F> {
+    format: Arc<F>,
+}
+
+impl<F> Logger for StdLog<F>
+where
+    F: Format,
+{
+    type Instance = StdLogInstance<F>;
+
+    fn start<T>(&self, request: &Request<T>) -> Self::Instance {
+        StdLogInstance {
+            format: self.format.clone(),
+            time: SystemTime::now(),
+            start: Instant::now(),
+            method: request.method().clone(),
+            uri: request.uri().clone(),
+            version: request.version(),
+            referer: request.headers().get(header::REFERER).cloned(),
+            user_agent: request.headers().get(header::USER_AGENT).cloned(),
+        }
+    }
+}
+
+#[allow(missing_docs)]
+#[derive(Debug)]
+pub struct StdLogInstance<F> {
+    format: Arc<F>,
+    time: SystemTime,
+    start: Instant,
+    method: Method,
+    uri: Uri,
+    version: Version,
+    referer: Option<HeaderValue>,
+    user_agent: Option<HeaderValue>,
+}
+
+impl<F> Logging for StdLogInstance<F>
+where
+    F: Format,
+{
+    fn finish(self, response: &Response<()>, body_size: u64) {
+        let record = Record {
+            time: self.time,
+            elapsed: self.start.elapsed(),
+            method: &self.method,
+            uri: &self.uri,
+            version: self.version,
+            referer: self.referer.as_ref().and_then(|h| h.to_str().ok()),
+            user_agent: self.user_agent.as_ref().and_then(|h| h.to_str().ok()),
+            status: response.status(),
+            body_size,
+            _priv: (),
+        };
+        ::log::info!(target: "finchers::access", "{}", self.format.format(&record));
+    }
+}
+
+/// A `Format` which emits lines in the Apache combined log format.
+///
+/// The remote host, the identity and the user name are always written as `-`.
+/// As Apache does, `"` and `\` in the quoted fields are escaped with a backslash,
+/// and the non-printable characters are written as `\xhh`.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct ApacheCombined;
+
+impl Format for ApacheCombined {
+    fn format(&self, record: &Record<'_>) -> String {
+        let t = UtcTime::new(record.time);
+        let body_size = match record.body_size {
+            0 => "-".to_owned(),
+            n => n.to_string(),
+        };
+        format!(
+            "- - - [{:02}/{}/{:04}:{:02}:{:02}:{:02} +0000] \"{} {} {:?}\" {} {} \"{}\" \"{}\"",
+            t.day,
+            MONTHS[t.month as usize - 1],
+            t.year,
+            t.hour,
+            t.minute,
+            t.second,
+            record.method,
+            Escaped(&record.uri.to_string()),
+            record.version,
+            record.status.as_u16(),
+            body_size,
+            Escaped(record.referer.unwrap_or("-")),
+            Escaped(record.user_agent.unwrap_or("-")),
+        )
+    }
+}
+
+/// A wrapper for writing a string into a quoted field of the log line.
+struct Escaped<'a>(&'a str);
+
+impl<'a> fmt::Display for Escaped<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for &b in self.0.as_bytes() {
+            match b {
+                b'"' | b'\\' => write!(f, "\\{}", b as char)?,
+                0x20..=0x7e => write!(f, "{}", b as char)?,
+                _ => write!(f, "\\x{:02x}", b)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A `Format` which emits each record as a line of JSON object.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct JsonLine;
+
+impl Format for JsonLine {
+    fn format(&self, record: &Record<'_>) -> String {
+        let elapsed =
+            record.elapsed.as_secs() as f64 * 1e3 + f64::from(record.elapsed.subsec_nanos()) / 1e6;
+        serde_json::json!({
+            "time": UtcTime::new(record.time).to_string(),
+            "method": record.method.as_str(),
+            "uri": record.uri.to_string(),
+            "version": format!("{:?}", record.version),
+            "status": record.status.as_u16(),
+            "body_size": record.body_size,
+            "elapsed_ms": elapsed,
+            "referer": record.referer,
+            "user_agent": record.user_agent,
+        })
+        .to_string()
+    }
+}
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// A broken-down representation of a `SystemTime` in UTC.
+struct UtcTime {
+    year: i64,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+}
+
+impl UtcTime {
+    fn new(time: SystemTime) -> Self {
+        let secs = time
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        let days = secs / 86400;
+        let rem = secs - days * 86400;
+
+        // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+        let z = days + 719_468;
+        let era = z / 146_097;
+        let doe = z - era * 146_097;
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+        let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        UtcTime {
+            year,
+            month,
+            day,
+            hour: (rem / 3600) as u32,
+            minute: (rem % 3600 / 60) as u32,
+            second: (rem % 60) as u32,
+        }
+    }
+}
+
+impl fmt::Display for UtcTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apache_combined() {
+        let uri: Uri = "/index.html?q=1".parse().unwrap();
+        let record = Record {
+            time: UNIX_EPOCH + Duration::from_secs(971_186_136),
+            elapsed: Duration::from_millis(3),
+            method: &Method::GET,
+            uri: &uri,
+            version: Version::HTTP_11,
+            referer: None,
+            user_agent: Some("curl/7.61.0"),
+            status: StatusCode::OK,
+            body_size: 2326,
+            _priv: (),
+        };
+        assert_eq!(
+            ApacheCombined.format(&record),
+            "- - - [10/Oct/2000:13:55:36 +0000] \"GET /index.html?q=1 HTTP/1.1\" 200 2326 \"-\" \"curl/7.61.0\""
+        );
+    }
+
+    #[test]
+    fn test_apache_combined_escape() {
+        let uri: Uri = "/".parse().unwrap();
+        let record = Record {
+            time: UNIX_EPOCH + Duration::from_secs(971_186_136),
+            elapsed: Duration::from_millis(3),
+            method: &Method::GET,
+            uri: &uri,
+            version: Version::HTTP_11,
+            referer: Some("https://example.com/\\"),
+            user_agent: Some("Mozilla/5.0 \"evil\"\tagent"),
+            status: StatusCode::OK,
+            body_size: 0,
+            _priv: (),
+        };
+        assert_eq!(
+            ApacheCombined.format(&record),
+            "- - - [10/Oct/2000:13:55:36 +0000] \"GET / HTTP/1.1\" 200 - \"https://example.com/\\\\\" \"Mozilla/5.0 \\\"evil\\\"\\x09agent\""
+        );
+    }
+
+    #[test]
+    fn test_json_line() {
+        let uri: Uri = "/index.html?q=1".parse().unwrap();
+        let record = Record {
+            time: UNIX_EPOCH + Duration::from_secs(971_186_136),
+            elapsed: Duration::from_micros(3500),
+            method: &Method::POST,
+            uri: &uri,
+            version: Version::HTTP_2,
+            referer: Some("https://example.com/"),
+            user_agent: None,
+            status: StatusCode::CREATED,
+            body_size: 0,
+            _priv: (),
+        };
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&JsonLine.format(&record)).unwrap(),
+            serde_json::json!({
+                "time": "2000-10-10T13:55:36Z",
+                "method": "POST",
+                "uri": "/index.html?q=1",
+                "version": "HTTP/2.0",
+                "status": 201,
+                "body_size": 0,
+                "elapsed_ms": 3.5,
+                "referer": "https://example.com/",
+                "user_agent": null
+            })
+        );
+    }
+
+    #[test]
+    fn test_utc_time() {
+        assert_eq!(UtcTime::new(UNIX_EPOCH).to_string(), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            UtcTime::new(UNIX_EPOCH + Duration::from_secs(951_782_400)).to_string(),
+            "2000-02-29T00:00:00Z"
+        );
+    }
+}
@@ -0,0 +1,637 @@
+//! Generation of OpenAPI 3 documents from the endpoint definitions.
+//!
+//! The operations are collected from the route descriptors returned by
+//! `IsEndpoint::describe`, that is, the paths and methods of `path!()`,
+//! the path parameters, the query, the header fields and the message body.
+//! The schemas of the query and the message body are taken from the
+//! implementors of `Schema`, by calling `with_schema` on the endpoints such
+//! as `query::required::<T>()` and `body::json::<T>()`, and the schema of
+//! a response is given by wrapping the endpoint with `json_output`.
+//! The schemas with a name are placed into `components` and referred from
+//! the operations.
+//!
+//! The document can be served by `into_endpoint` and `into_yaml_endpoint`,
+//! and `swagger_ui` serves an HTML page of Swagger UI which loads the document.
+//! The assets of Swagger UI are not bundled; see `swagger_ui` for details.
+//!
+//! # Example
+//!
+//! ```
+//! # use finchers::prelude::*;
+//! # use finchers::endpoint::syntax::path;
+//! # use finchers::endpoints::body;
+//! # use finchers::output::Json;
+//! # use serde::{Deserialize, Serialize};
+//! use finchers::openapi::{self, OpenApi, Schema};
+//!
+//! #[derive(Deserialize, Serialize, Schema)]
+//! struct Post {
+//!     title: String,
+//!     tags: Option<Vec<String>>,
+//! }
+//!
+//! let endpoint = openapi::json_output::<Post, _>(
+//!     path!(@get "/posts/<id: u32>").map(|_id: u32| {
+//!         Json(Post {
+//!             title: "Hello".into(),
+//!             tags: None,
+//!         })
+//!     }),
+//! )
+//! .or(path!(@post "/posts")
+//!     .and(body::json::<Post>().with_schema())
+//!     .map(|post: Post| Json(post)));
+//!
+//! let document = OpenApi::new("Blog API", "1.0.0")
+//!     .routes(&endpoint)
+//!     .to_json();
+//! let show = &document["paths"]["/posts/{id}"]["get"];
+//! assert_eq!(show["parameters"][0]["schema"]["type"], "integer");
+//! assert_eq!(
+//!     show["responses"]["200"]["content"]["application/json"]["schema"]["$ref"],
+//!     "#/components/schemas/Post"
+//! );
+//! assert_eq!(document["components"]["schemas"]["Post"]["type"], "object");
+//! ```
+
+use {
+    crate::{
+        endpoint::{
+            describe::{Description, Route, TypeSchema},
+            value, Endpoint, EndpointExt, IsEndpoint,
+        },
+        output::Json,
+    },
+    http::{header, header::HeaderValue, Response},
+    serde_json::{json, Map, Value},
+    std::{
+        collections::{BTreeMap, HashMap},
+        fmt::Write,
+        marker::PhantomData,
+    },
+};
+
+pub use finchers_macros::Schema;
+
+/// The version of OpenAPI Specification emitted by `OpenApi`.
+const OPENAPI_VERSION: &str = "3.0.2";
+
+/// A trait representing the types which provides the JSON Schema of their values.
+///
+/// This trait can be derived for the structs with named fields and the enums
+/// which consist of unit variants by `#[derive(Schema)]`.
+///
+/// The derived schema follows the attributes `#[serde(rename)]`,
+/// `#[serde(rename_all)]`, `#[serde(skip)]`, `#[serde(flatten)]` and
+/// `#[serde(default)]` (and the variants of `skip_*`). The other attributes
+/// which change the representation, such as `tag` and `with`, are rejected
+/// at compile time.
+pub trait Schema {
+    /// Returns the Schema Object of this type.
+    fn schema() -> Value;
+
+    /// Returns whether the value of this type is required when it is used as a field.
+    ///
+    /// The default implementation returns `true`, and `Option<T>` overrides it.
+    fn required() -> bool {
+        true
+    }
+
+    /// Returns the name of this type used in `components`, if any.
+    ///
+    /// The schema of a type with a name is placed into `components` and
+    /// referred from the operations. The default implementation returns `None`
+    /// so that the schema is written inline.
+    fn name() -> Option<&'static str> {
+        None
+    }
+}
+
+macro_rules! impl_schema {
+    ($($t:ty => $schema:tt,)*) => {$(
+        impl Schema for $t {
+            fn schema() -> Value {
+                json!($schema)
+            }
+        }
+    )*};
+}
+
+impl_schema! {
+    bool => { "type": "boolean" },
+    i8 => { "type": "integer" },
+    i16 => { "type": "integer" },
+    i32 => { "type": "integer", "format": "int32" },
+    i64 => { "type": "integer", "format": "int64" },
+    isize => { "type": "integer" },
+    u8 => { "type": "integer", "minimum": 0 },
+    u16 => { "type": "integer", "minimum": 0 },
+    u32 => { "type": "integer", "format": "int32", "minimum": 0 },
+    u64 => { "type": "integer", "format": "int64", "minimum": 0 },
+    usize => { "type": "integer", "minimum": 0 },
+    f32 => { "type": "number", "format": "float" },
+    f64 => { "type": "number", "format": "double" },
+    String => { "type": "string" },
+    Value => {},
+}
+
+impl<'a> Schema for &'a str {
+    fn schema() -> Value {
+        String::schema()
+    }
+}
+
+impl<T: Schema> Schema for Option<T> {
+    fn schema() -> Value {
+        T::schema()
+    }
+
+    fn required() -> bool {
+        false
+    }
+
+    fn name() -> Option<&'static str> {
+        T::name()
+    }
+}
+
+impl<T: Schema> Schema for Vec<T> {
+    fn schema() -> Value {
+        json!({ "type": "array", "items": T::schema() })
+    }
+}
+
+impl<T: Schema> Schema for HashMap<String, T> {
+    fn schema() -> Value {
+        json!({ "type": "object", "additionalProperties": T::schema() })
+    }
+}
+
+impl<T: Schema> Schema for BTreeMap<String, T> {
+    fn schema() -> Value {
+        json!({ "type": "object", "additionalProperties": T::schema() })
+    }
+}
+
+#[doc(hidden)]
+pub mod __private {
+    pub use serde_json::Value;
+    use serde_json::{json, Map};
+
+    // used in #[derive(Schema)]
+    pub fn object_schema(fields: Vec<(&str, Value, bool)>, flattened: Vec<(Value, bool)>) -> Value {
+        let mut properties = Map::new();
+        let mut required = vec![];
+        for (name, schema, is_required) in fields {
+            if is_required {
+                required.push(Value::String(name.to_owned()));
+            }
+            properties.insert(name.to_owned(), schema);
+        }
+
+        // The properties of `#[serde(flatten)]` fields are merged into the object.
+        let mut additional_properties = None;
+        for (schema, is_required) in flattened {
+            let mut schema = match schema {
+                Value::Object(schema) => schema,
+                _ => continue,
+            };
+            if let Some(Value::Object(props)) = schema.remove("properties") {
+                properties.extend(props);
+            }
+            if is_required {
+                if let Some(Value::Array(names)) = schema.remove("required") {
+                    required.extend(names);
+                }
+            }
+            if let Some(additional) = schema.remove("additionalProperties") {
+                additional_properties = Some(additional);
+            }
+        }
+
+        let mut schema = json!({ "type": "object", "properties": properties });
+        if !required.is_empty() {
+            schema["required"] = Value::Array(required);
+        }
+        if let Some(additional) = additional_properties {
+            schema["additionalProperties"] = additional;
+        }
+        schema
+    }
+
+    // used in #[derive(Schema)]
+    pub fn enum_schema(variants: &[&str]) -> Value {
+        json!({ "type": "string", "enum": variants })
+    }
+}
+
+/// A builder of OpenAPI documents.
+#[derive(Debug, Clone)]
+pub struct OpenApi {
+    info: Map<String, Value>,
+    routes: Vec<Route>,
+    schemas: Map<String, Value>,
+}
+
+impl OpenApi {
+    /// Create a new `OpenApi` with the specified title and version of the API.
+    pub fn new(title: impl Into<String>, version: impl Into<String>) -> OpenApi {
+        let mut info = Map::new();
+        info.insert("title".into(), Value::String(title.into()));
+        info.insert("version".into(), Value::String(version.into()));
+        OpenApi {
+            info,
+            routes: vec![],
+            schemas: Map::new(),
+        }
+    }
+
+    /// Sets the description of the API.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.info
+            .insert("description".into(), Value::String(description.into()));
+        self
+    }
+
+    /// Adds the routes described by the specified endpoint.
+    ///
+    /// The routes which do not restrict the HTTP method are omitted,
+    /// since OpenAPI requires the operations to be associated with methods.
+    pub fn routes(mut self, endpoint: &impl IsEndpoint) -> Self {
+        self.routes.extend(endpoint.describe().routes());
+        self
+    }
+
+    /// Registers the schema of `T` into `components` with the specified name.
+    ///
+    /// The operations refer the registered schema as `#/components/schemas/<name>`
+    /// wherever the schema of `T` appears as a body, even if `T` has no name.
+    pub fn schema<T: Schema>(mut self, name: impl Into<String>) -> Self {
+        self.schemas.insert(name.into(), T::schema());
+        self
+    }
+
+    /// Generates the OpenAPI document as a JSON value.
+    pub fn to_json(&self) -> Value {
+        let mut schemas = self.schemas.clone();
+        let mut paths = Map::new();
+        for route in &self.routes {
+            let verbs = match route.verbs {
+                Some(verbs) => verbs,
+                None => continue,
+            };
+            let (path, mut parameters) = path_template(&route.path);
+            let mut request_body = None;
+            for extractor in &route.extractors {
+                match extractor {
+                    Description::Query { required, schema } => {
+                        parameters.extend(query_parameters(*required, schema.as_ref()));
+                    }
+                    Description::Header { name, required } => parameters.push(json!({
+                        "name": name.as_str(),
+                        "in": "header",
+                        "required": required,
+                        "schema": { "type": "string" }
+                    })),
+                    Description::Body { kind, schema } => {
+                        let schema = match schema {
+                            Some(schema) => schema_object(schema, &mut schemas),
+                            None => json!({}),
+                        };
+                        request_body = Some(json!({
+                            "required": true,
+                            "content": { body_media_type(kind): { "schema": schema } }
+                        }));
+                    }
+                    _ => {}
+                }
+            }
+            let responses = match route.output {
+                Some(ref output) => json!({
+                    "200": {
+                        "description": "The response of the endpoint.",
+                        "content": {
+                            "application/json": { "schema": schema_object(output, &mut schemas) }
+                        }
+                    }
+                }),
+                None => json!({ "default": { "description": "The response of the endpoint." } }),
+            };
+
+            let item = paths
+                .entry(path)
+                .or_insert_with(|| Value::Object(Map::new()));
+            for method in verbs {
+                let method = method.as_str().to_lowercase();
+                // The route evaluated first wins, as the router does.
+                if item.get(&method).is_some() {
+                    continue;
+                }
+                let mut operation = Map::new();
+                if !parameters.is_empty() {
+                    operation.insert("parameters".into(), Value::Array(parameters.clone()));
+                }
+                if let Some(ref request_body) = request_body {
+                    operation.insert("requestBody".into(), request_body.clone());
+                }
+                operation.insert("responses".into(), responses.clone());
+                item[method] = Value::Object(operation);
+            }
+        }
+
+        let mut document = json!({
+            "openapi": OPENAPI_VERSION,
+            "info": self.info,
+            "paths": paths
+        });
+        if !schemas.is_empty() {
+            document["components"] = json!({ "schemas": schemas });
+        }
+        document
+    }
+
+    /// Generates the OpenAPI document as a YAML string.
+    pub fn to_yaml(&self) -> String {
+        let mut yaml = String::new();
+        write_yaml(&mut yaml, &self.to_json(), 0);
+        yaml.remove(0);
+        yaml.push('\n');
+        yaml
+    }
+
+    /// Converts this document into an endpoint which returns it as a JSON.
+    pub fn into_endpoint<Bd>(self) -> impl Endpoint<Bd, Output = (Value,)> {
+        value(self.to_json())
+    }
+
+    /// Converts this document into an endpoint which returns it as a YAML.
+    pub fn into_yaml_endpoint<Bd>(self) -> impl Endpoint<Bd, Output = (Response<String>,)> {
+        value(self.to_yaml()).map(|yaml: String| {
+            let mut response = Response::new(yaml);
+            response.headers_mut().insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/x-yaml"),
+            );
+            response
+        })
+    }
+}
+
+/// Returns the Schema Object of the type, or the reference to it if the schema is
+/// placed into `components`.
+fn schema_object(schema: &TypeSchema, schemas: &mut Map<String, Value>) -> Value {
+    let value = schema.schema();
+    let registered = schemas
+        .iter()
+        .find(|&(_, registered)| *registered == value)
+        .map(|(name, _)| name.clone());
+    let name = match (registered, schema.name()) {
+        (Some(name), _) => name,
+        (None, Some(name)) if !schemas.contains_key(name) => {
+            schemas.insert(name.to_owned(), value);
+            name.to_owned()
+        }
+        (None, _) => return value,
+    };
+    json!({ "$ref": format!("#/components/schemas/{}", name) })
+}
+
+/// Converts the properties of the query schema into the query parameters.
+///
+/// The query whose schema is unknown or not an object is not documented.
+fn query_parameters(required: bool, schema: Option<&TypeSchema>) -> Vec<Value> {
+    let schema = match schema {
+        Some(schema) => schema.schema(),
+        None => return vec![],
+    };
+    let properties = match schema.get("properties").and_then(Value::as_object) {
+        Some(properties) => properties,
+        None => return vec![],
+    };
+    let required_properties = schema.get("required").and_then(Value::as_array);
+    properties
+        .iter()
+        .map(|(name, schema)| {
+            let is_required = required
+                && required_properties.map_or(false, |names| names.iter().any(|n| n == name));
+            json!({
+                "name": name,
+                "in": "query",
+                "required": is_required,
+                "schema": schema
+            })
+        })
+        .collect()
+}
+
+/// Converts the path segments into a path template and the path parameters.
+///
+/// The parameters without a name are named in order, as `param0`, `param1` and so on.
+fn path_template(path: &[Description]) -> (String, Vec<Value>) {
+    let mut template = String::new();
+    let mut parameters = vec![];
+    for desc in path {
+        let (name, ty, schema) = match desc {
+            Description::Segment(s) => {
+                template.push('/');
+                template.push_str(s);
+                continue;
+            }
+            Description::Param { name, ty } => (*name, *ty, schema_for_type_name(ty.unwrap_or(""))),
+            Description::Remains { name, ty } => (*name, *ty, json!({ "type": "string" })),
+            _ => continue,
+        };
+        let name = name
+            .map(ToOwned::to_owned)
+            .unwrap_or_else(|| format!("param{}", parameters.len()));
+        let _ = write!(template, "/{{{}}}", name);
+        let mut parameter = json!({
+            "name": name,
+            "in": "path",
+            "required": true,
+            "schema": schema
+        });
+        if let Description::Remains { .. } = desc {
+            parameter["description"] = Value::String(format!(
+                "The remaining path segments{}.",
+                ty.map(|ty| format!(", parsed as `{}`", ty))
+                    .unwrap_or_default()
+            ));
+        }
+        parameters.push(parameter);
+    }
+    if template.is_empty() {
+        template.push('/');
+    }
+    (template, parameters)
+}
+
+/// Guesses the schema of a path parameter from the name of its type.
+fn schema_for_type_name(name: &str) -> Value {
+    match name.rsplit("::").next().unwrap_or(name).trim() {
+        "bool" => bool::schema(),
+        "i8" | "i16" | "isize" => i8::schema(),
+        "i32" => i32::schema(),
+        "i64" => i64::schema(),
+        "u8" | "u16" | "usize" => u8::schema(),
+        "u32" => u32::schema(),
+        "u64" => u64::schema(),
+        "f32" => f32::schema(),
+        "f64" => f64::schema(),
+        _ => String::schema(),
+    }
+}
+
+/// Returns the media type of the request body extracted by the endpoint of the specified kind.
+fn body_media_type(kind: &str) -> &'static str {
+    match kind {
+        "json" => "application/json",
+        "urlencoded" => "application/x-www-form-urlencoded",
+        "text" => "text/plain",
+        "multipart" => "multipart/form-data",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Writes the JSON value as a block of YAML, after a mapping key or a sequence indicator.
+///
+/// The scalars are written in the JSON syntax, which is also valid in YAML.
+fn write_yaml(yaml: &mut String, value: &Value, indent: usize) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                yaml.push('\n');
+                yaml.extend(std::iter::repeat(' ').take(indent));
+                if is_plain_key(key) {
+                    yaml.push_str(key);
+                } else {
+                    yaml.push_str(&Value::String(key.clone()).to_string());
+                }
+                yaml.push(':');
+                write_yaml(yaml, value, indent + 2);
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for item in items {
+                yaml.push('\n');
+                yaml.extend(std::iter::repeat(' ').take(indent));
+                yaml.push('-');
+                write_yaml(yaml, item, indent + 2);
+            }
+        }
+        value => {
+            yaml.push(' ');
+            yaml.push_str(&value.to_string());
+        }
+    }
+}
+
+/// Returns whether the mapping key can be written without quotes.
+fn is_plain_key(key: &str) -> bool {
+    let is_reserved = match key.to_lowercase().as_str() {
+        "y" | "n" | "yes" | "no" | "on" | "off" | "true" | "false" | "null" => true,
+        _ => false,
+    };
+    !is_reserved
+        && key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Create an endpoint which documents the output of the specified endpoint,
+/// which returns a `Json<T>`, with the schema of `T`.
+///
+/// The schema is used as the one of the successful response in the OpenAPI document.
+pub fn json_output<T, E>(endpoint: E) -> JsonOutput<E, T>
+where
+    T: Schema,
+    E: IsEndpoint,
+{
+    JsonOutput {
+        endpoint,
+        _marker: PhantomData,
+    }
+}
+
+#[allow(missing_docs)]
+#[derive(Debug)]
+pub struct JsonOutput<E, T> {
+    endpoint: E,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<E, T> IsEndpoint for JsonOutput<E, T>
+where
+    E: IsEndpoint,
+    T: Schema,
+{
+    fn describe(&self) -> Description {
+        self.endpoint
+            .describe()
+            .and(Description::Output(TypeSchema::new(T::name(), T::schema)))
+    }
+}
+
+impl<E, T, Bd> Endpoint<Bd> for JsonOutput<E, T>
+where
+    E: Endpoint<Bd, Output = (Json<T>,)>,
+    T: Schema,
+{
+    type Output = E::Output;
+    type Action = E::Action;
+
+    #[inline]
+    fn action(&self) -> Self::Action {
+        self.endpoint.action()
+    }
+}
+
+/// The base URL of the distribution of Swagger UI on the CDN.
+pub const SWAGGER_UI_CDN: &str = "https://unpkg.com/swagger-ui-dist@3";
+
+/// Create an endpoint which returns an HTML page of Swagger UI for the specified document.
+///
+/// Only the HTML page is generated; the assets of Swagger UI are *not*
+/// bundled with finchers. The assets (`swagger-ui.css` and
+/// `swagger-ui-bundle.js`) are loaded by the browser from `assets_url`, which
+/// is the base URL of the `swagger-ui-dist` package served by the application
+/// itself (e.g. with `fs::dir`), or `SWAGGER_UI_CDN` to opt in to loading them
+/// from the CDN.
+pub fn swagger_ui<Bd>(
+    spec_url: &str,
+    assets_url: &str,
+) -> impl Endpoint<Bd, Output = (Response<String>,)> {
+    let assets_url = assets_url.trim_end_matches('/');
+    let html = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Swagger UI</title>
+<link rel="stylesheet" href={css}>
+</head>
+<body>
+<div id="swagger-ui"></div>
+<script src={js}></script>
+<script>
+window.onload = function() {{
+  window.ui = SwaggerUIBundle({{ url: {url}, dom_id: "#swagger-ui" }});
+}};
+</script>
+</body>
+</html>
+"#,
+        css = Value::String(format!("{}/swagger-ui.css", assets_url)),
+        js = Value::String(format!("{}/swagger-ui-bundle.js", assets_url)),
+        url = Value::String(spec_url.to_owned())
+    );
+    value(html).map(|html: String| {
+        let mut response = Response::new(html);
+        response.headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("text/html; charset=utf-8"),
+        );
+        response
+    })
+}
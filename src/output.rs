@@ -3,18 +3,22 @@
 pub mod fs;
 pub mod status;
 
+mod attachment;
 mod binary;
 mod debug;
 mod json;
+mod negotiated;
 mod redirect;
 mod text;
 
 use either::Either;
 use http::{Request, Response, StatusCode};
 
+pub use self::attachment::Attachment;
 pub use self::debug::Debug;
 pub use self::fs::NamedFile;
 pub use self::json::Json;
+pub use self::negotiated::Negotiated;
 pub use self::redirect::Redirect;
 
 /// A trait representing the value to be converted into an HTTP response.
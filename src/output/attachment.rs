@@ -0,0 +1,163 @@
+use http::header::{self, HeaderValue};
+use http::{Request, Response};
+use mime::Mime;
+use std::fmt::Write;
+
+use super::IntoResponse;
+
+/// An instance of `Output` which makes the client download the response
+/// under the specified file name.
+///
+/// The header field `Content-Disposition` is constructed as described in
+/// RFC 6266. If the file name is not representable as a plain ASCII string,
+/// the UTF-8 encoded name is provided via the `filename*` parameter along
+/// with an ASCII fallback.
+///
+/// # Example
+///
+/// ```
+/// use finchers::output::Attachment;
+///
+/// let report = Attachment::new(b"id,name\n1,alice\n".to_vec(), "report.csv")
+///     .content_type(mime::TEXT_CSV);
+/// # drop(report);
+/// ```
+#[derive(Debug)]
+pub struct Attachment<T> {
+    inner: T,
+    filename: String,
+    content_type: Option<Mime>,
+}
+
+impl<T> Attachment<T> {
+    /// Create a new `Attachment` with the specified file name.
+    pub fn new(inner: T, filename: impl Into<String>) -> Attachment<T> {
+        Attachment {
+            inner,
+            filename: filename.into(),
+            content_type: None,
+        }
+    }
+
+    /// Overrides the value of `Content-Type` set by the inner value.
+    pub fn content_type(self, content_type: Mime) -> Self {
+        Attachment {
+            content_type: Some(content_type),
+            ..self
+        }
+    }
+
+    /// Returns the file name of this attachment.
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    /// Returns a reference to the inner value.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Consumes itself and returns the inner value.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: IntoResponse> IntoResponse for Attachment<T> {
+    type Body = T::Body;
+
+    fn into_response(self, request: &Request<()>) -> Response<Self::Body> {
+        let mut response = self.inner.into_response(request);
+        if !response.status().is_success() {
+            return response;
+        }
+
+        let headers = response.headers_mut();
+        headers.insert(
+            header::CONTENT_DISPOSITION,
+            HeaderValue::from_str(&content_disposition(&self.filename))
+                .expect("should be a valid header value"),
+        );
+
+        if let Some(content_type) = self.content_type {
+            // The media type of multipart/byteranges describes the payload
+            // itself, not the content of the file.
+            let is_multipart = headers
+                .get(header::CONTENT_TYPE)
+                .and_then(|h| h.to_str().ok())
+                .map_or(false, |h| h.starts_with("multipart/byteranges"));
+            if !is_multipart {
+                headers.insert(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_str(content_type.as_ref())
+                        .expect("should be a valid header value"),
+                );
+            }
+        }
+
+        response
+    }
+}
+
+/// Creates the value of `Content-Disposition` for the attachment with the
+/// specified file name.
+fn content_disposition(filename: &str) -> String {
+    let fallback: String = filename
+        .chars()
+        .map(|c| match c {
+            ' '..='~' if c != '"' && c != '\\' && c != '%' => c,
+            _ => '_',
+        })
+        .collect();
+
+    let mut value = format!("attachment; filename=\"{}\"", fallback);
+    if fallback != filename {
+        value.push_str("; filename*=UTF-8''");
+        for &b in filename.as_bytes() {
+            if is_attr_char(b) {
+                value.push(b as char);
+            } else {
+                let _ = write!(value, "%{:02X}", b);
+            }
+        }
+    }
+    value
+}
+
+/// Returns whether the byte is `attr-char` defined in RFC 5987.
+fn is_attr_char(b: u8) -> bool {
+    match b {
+        b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' => true,
+        b'!' | b'#' | b'$' | b'&' | b'+' | b'-' | b'.' | b'^' | b'_' | b'`' | b'|' | b'~' => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::content_disposition;
+
+    #[test]
+    fn test_content_disposition_ascii() {
+        assert_eq!(
+            content_disposition("report.csv"),
+            "attachment; filename=\"report.csv\""
+        );
+    }
+
+    #[test]
+    fn test_content_disposition_non_ascii() {
+        assert_eq!(
+            content_disposition("résumé 2018.pdf"),
+            "attachment; filename=\"r_sum_ 2018.pdf\"; filename*=UTF-8''r%C3%A9sum%C3%A9%202018.pdf"
+        );
+    }
+
+    #[test]
+    fn test_content_disposition_special_chars() {
+        assert_eq!(
+            content_disposition("a\"b\\c%.txt"),
+            "attachment; filename=\"a_b_c_.txt\"; filename*=UTF-8''a%22b%5Cc%25.txt"
+        );
+    }
+}
@@ -1,21 +1,40 @@
 #![allow(missing_docs)]
 
 use std::cmp;
+use std::collections::hash_map::RandomState;
+use std::collections::VecDeque;
 use std::fs::Metadata;
-use std::io;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, SeekFrom};
 use std::mem;
-use std::path::PathBuf;
+#[cfg(unix)]
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use futures::{try_ready, Async, Future, Poll};
 use izanami_util::buf_stream::BufStream;
 use tokio::fs::file::{File, MetadataFuture, OpenFuture};
 use tokio::io::AsyncRead;
+#[cfg(unix)]
+use tokio::io::AsyncWrite;
 
+#[cfg(unix)]
+use bytes::Buf;
 use bytes::{BufMut, Bytes, BytesMut};
-use http::{header, Request, Response};
+use http::header::{HeaderMap, HeaderValue};
+use http::{header, Method, Request, Response, StatusCode};
 use mime_guess::guess_mime_type;
 
-use super::IntoResponse;
+use super::{Attachment, IntoResponse};
+use crate::endpoints::header::negotiate_encoding;
+
+mod cache;
+mod listing;
+
+pub use self::cache::CachePolicy;
+pub use self::listing::{DirEntryInfo, DirListing, EntryType, ReadDirListing};
 
 /// An instance of `Output` representing a file on the file system.
 #[derive(Debug)]
@@ -23,16 +42,94 @@ pub struct NamedFile {
     file: File,
     meta: Metadata,
     path: PathBuf,
+    encoding: Option<&'static str>,
+    vary_encoding: bool,
+    cache_policy: Option<Arc<CachePolicy>>,
 }
 
 impl NamedFile {
     #[allow(missing_docs)]
     pub fn open(path: PathBuf) -> OpenNamedFile {
+        Self::open_with(path.clone(), path, None, false)
+    }
+
+    /// Opens the specified file, or its precompressed sibling (`.br` or `.gz`)
+    /// if exists and the coding is acceptable for the request.
+    ///
+    /// The sibling is served with `Content-Encoding`, and `Content-Type` is
+    /// guessed from the name of the original file.
+    pub fn open_precompressed(path: PathBuf, headers: &HeaderMap) -> OpenNamedFile {
+        match select_precompressed(&path, headers) {
+            Some((encoded_path, encoding)) => {
+                Self::open_with(encoded_path, path, Some(encoding), true)
+            }
+            None => Self::open_with(path.clone(), path, None, true),
+        }
+    }
+
+    /// Opens `file_path` as the representation of the file `path`.
+    pub(crate) fn open_with(
+        file_path: PathBuf,
+        path: PathBuf,
+        encoding: Option<&'static str>,
+        vary_encoding: bool,
+    ) -> OpenNamedFile {
         OpenNamedFile {
-            state: State::Opening(File::open(path.clone())),
+            state: State::Opening(File::open(file_path)),
             path: Some(path),
+            encoding,
+            vary_encoding,
+            cache_policy: None,
         }
     }
+
+    /// Sets the policy which determines the value of `Cache-Control`.
+    pub fn with_cache_policy(self, cache_policy: Arc<CachePolicy>) -> Self {
+        NamedFile {
+            cache_policy: Some(cache_policy),
+            ..self
+        }
+    }
+
+    /// Makes the client download this file under the specified name.
+    pub fn as_attachment(self, filename: impl Into<String>) -> Attachment<Self> {
+        Attachment::new(self, filename)
+    }
+
+    fn cache_control(&self) -> Option<HeaderValue> {
+        let policy = self.cache_policy.as_ref()?;
+        let name = self.path.file_name()?.to_str()?;
+        policy.cache_control(name).cloned()
+    }
+}
+
+/// The pairs of the content coding and the extension of precompressed files,
+/// in the order of preference.
+const PRECOMPRESSED: &[(&str, &str)] = &[("br", "br"), ("gzip", "gz")];
+
+/// Finds the precompressed sibling of `path` with the most preferred coding
+/// acceptable for the request.
+pub(crate) fn select_precompressed(
+    path: &Path,
+    headers: &HeaderMap,
+) -> Option<(PathBuf, &'static str)> {
+    let mut available = vec![];
+    for &(encoding, extension) in PRECOMPRESSED {
+        let mut encoded_path = path.as_os_str().to_owned();
+        encoded_path.push(".");
+        encoded_path.push(extension);
+        let encoded_path = PathBuf::from(encoded_path);
+        if encoded_path.is_file() {
+            available.push((encoded_path, encoding));
+        }
+    }
+    if available.is_empty() {
+        return None;
+    }
+
+    let candidates: Vec<_> = available.iter().map(|&(_, encoding)| encoding).collect();
+    let encoding = negotiate_encoding(headers, &candidates)?;
+    available.into_iter().find(|&(_, e)| e == encoding)
 }
 
 #[allow(missing_docs)]
@@ -40,6 +137,18 @@ impl NamedFile {
 pub struct OpenNamedFile {
     state: State,
     path: Option<PathBuf>,
+    encoding: Option<&'static str>,
+    vary_encoding: bool,
+    cache_policy: Option<Arc<CachePolicy>>,
+}
+
+impl OpenNamedFile {
+    pub(crate) fn with_cache_policy(self, cache_policy: Option<Arc<CachePolicy>>) -> Self {
+        OpenNamedFile {
+            cache_policy,
+            ..self
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -75,6 +184,9 @@ impl Future for OpenNamedFile {
                         file,
                         meta,
                         path: self.path.take().unwrap(),
+                        encoding: self.encoding,
+                        vary_encoding: self.vary_encoding,
+                        cache_policy: self.cache_policy.take(),
                     };
                     return Ok(Async::Ready(named_file));
                 }
@@ -87,41 +199,609 @@ impl Future for OpenNamedFile {
 impl IntoResponse for NamedFile {
     type Body = FileStream;
 
-    fn into_response(self, _: &Request<()>) -> Response<Self::Body> {
-        let NamedFile { file, meta, path } = self;
-
-        let body = FileStream::new(file, &meta);
+    fn into_response(self, request: &Request<()>) -> Response<Self::Body> {
+        let cache_control = self.cache_control();
+        let NamedFile {
+            file,
+            meta,
+            path,
+            encoding,
+            vary_encoding,
+            ..
+        } = self;
 
+        let len = meta.len();
+        let buf_size = optimal_buf_size(&meta);
         let content_type = guess_mime_type(&path);
 
-        Response::builder()
-            .header(header::CONTENT_LENGTH, meta.len())
-            .header(header::CONTENT_TYPE, content_type.as_ref())
-            .body(body)
-            .unwrap()
+        let validators = Validators::new(&meta);
+        let is_get_or_head = *request.method() == Method::GET || *request.method() == Method::HEAD;
+
+        if is_get_or_head && validators.is_not_modified(request) {
+            let mut response = Response::new(FileStream::new(file, buf_size, vec![]));
+            *response.status_mut() = StatusCode::NOT_MODIFIED;
+            validators.append_to(response.headers_mut());
+            if let Some(cache_control) = cache_control {
+                response
+                    .headers_mut()
+                    .insert(header::CACHE_CONTROL, cache_control);
+            }
+            if vary_encoding {
+                response
+                    .headers_mut()
+                    .append(header::VARY, HeaderValue::from_static("accept-encoding"));
+            }
+            return response;
+        }
+
+        let ranges = if is_get_or_head {
+            match request.headers().get(header::RANGE) {
+                Some(range) if validators.if_range_matches(request) => parse_range(range, len),
+                _ => None,
+            }
+        } else {
+            None
+        };
+
+        let mut response = match ranges {
+            None => Response::builder()
+                .status(StatusCode::OK)
+                .header(header::CONTENT_LENGTH, len)
+                .header(header::CONTENT_TYPE, content_type.as_ref())
+                .body(FileStream::new(file, buf_size, vec![Segment::file(0, len)]))
+                .unwrap(),
+
+            Some(Ranges::Unsatisfiable) => Response::builder()
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(header::CONTENT_LENGTH, 0u64)
+                .header(header::CONTENT_RANGE, &*format!("bytes */{}", len))
+                .body(FileStream::new(file, buf_size, vec![]))
+                .unwrap(),
+
+            Some(Ranges::Satisfiable(ref ranges)) if ranges.len() == 1 => {
+                let range = &ranges[0];
+                Response::builder()
+                    .status(StatusCode::PARTIAL_CONTENT)
+                    .header(header::CONTENT_LENGTH, range.len())
+                    .header(header::CONTENT_TYPE, content_type.as_ref())
+                    .header(header::CONTENT_RANGE, &*range.content_range(len))
+                    .body(FileStream::new(
+                        file,
+                        buf_size,
+                        vec![Segment::file(range.start, range.len())],
+                    ))
+                    .unwrap()
+            }
+
+            Some(Ranges::Satisfiable(ranges)) => {
+                let boundary = generate_boundary();
+                let mut segments = Vec::with_capacity(ranges.len() * 2 + 1);
+                for (i, range) in ranges.iter().enumerate() {
+                    let part_headers = format!(
+                        "{}--{}\r\nContent-Type: {}\r\nContent-Range: {}\r\n\r\n",
+                        if i == 0 { "" } else { "\r\n" },
+                        boundary,
+                        content_type,
+                        range.content_range(len),
+                    );
+                    segments.push(Segment::Bytes(Bytes::from(part_headers)));
+                    segments.push(Segment::file(range.start, range.len()));
+                }
+                segments.push(Segment::Bytes(Bytes::from(format!(
+                    "\r\n--{}--\r\n",
+                    boundary
+                ))));
+                let content_length: u64 = segments.iter().map(Segment::len).sum();
+
+                Response::builder()
+                    .status(StatusCode::PARTIAL_CONTENT)
+                    .header(header::CONTENT_LENGTH, content_length)
+                    .header(
+                        header::CONTENT_TYPE,
+                        &*format!("multipart/byteranges; boundary={}", boundary),
+                    )
+                    .body(FileStream::new(file, buf_size, segments))
+                    .unwrap()
+            }
+        };
+
+        response
+            .headers_mut()
+            .insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+        validators.append_to(response.headers_mut());
+        if let Some(cache_control) = cache_control {
+            response
+                .headers_mut()
+                .insert(header::CACHE_CONTROL, cache_control);
+        }
+        if let Some(encoding) = encoding {
+            response
+                .headers_mut()
+                .insert(header::CONTENT_ENCODING, HeaderValue::from_static(encoding));
+        }
+        if vary_encoding {
+            response
+                .headers_mut()
+                .append(header::VARY, HeaderValue::from_static("accept-encoding"));
+        }
+        response
     }
 }
 
-#[allow(missing_docs)]
+// ==== Validators ====
+
+/// The validators of a file, used for the conditional requests.
+#[derive(Debug)]
+struct Validators {
+    etag: String,
+    last_modified: Option<SystemTime>,
+}
+
+impl Validators {
+    fn new(meta: &Metadata) -> Self {
+        // The modification time is truncated to seconds, which is
+        // the resolution of HTTP-date.
+        let mtime = meta
+            .modified()
+            .ok()
+            .and_then(|mtime| mtime.duration_since(UNIX_EPOCH).ok());
+        let (secs, nanos) = mtime.map_or((0, 0), |d| (d.as_secs(), d.subsec_nanos()));
+
+        Validators {
+            etag: format!(
+                "\"{:x}-{:x}-{:x}.{:x}\"",
+                get_inode(meta),
+                meta.len(),
+                secs,
+                nanos
+            ),
+            last_modified: mtime.map(|_| UNIX_EPOCH + Duration::from_secs(secs)),
+        }
+    }
+
+    fn append_to(&self, headers: &mut HeaderMap) {
+        headers.insert(
+            header::ETAG,
+            HeaderValue::from_str(&self.etag).expect("should be a valid header value"),
+        );
+        if let Some(last_modified) = self.last_modified {
+            headers.insert(
+                header::LAST_MODIFIED,
+                HeaderValue::from_str(&httpdate::fmt_http_date(last_modified))
+                    .expect("should be a valid header value"),
+            );
+        }
+    }
+
+    /// Evaluates `If-None-Match` and `If-Modified-Since`, and returns `true`
+    /// if the response should be `304 Not Modified`.
+    fn is_not_modified(&self, request: &Request<()>) -> bool {
+        let headers = request.headers();
+
+        // `If-Modified-Since` is ignored if `If-None-Match` is present.
+        if headers.contains_key(header::IF_NONE_MATCH) {
+            return headers
+                .get_all(header::IF_NONE_MATCH)
+                .iter()
+                .filter_map(|h| h.to_str().ok())
+                .flat_map(|h| h.split(','))
+                .map(str::trim)
+                .any(|tag| tag == "*" || weak_eq(tag, &self.etag));
+        }
+
+        match (self.last_modified, headers.get(header::IF_MODIFIED_SINCE)) {
+            (Some(last_modified), Some(since)) => since
+                .to_str()
+                .ok()
+                .and_then(|since| httpdate::parse_http_date(since).ok())
+                .map_or(false, |since| last_modified <= since),
+            _ => false,
+        }
+    }
+
+    /// Returns whether the range request is applicable to the current representation,
+    /// by evaluating `If-Range`.
+    ///
+    /// An entity tag in `If-Range` must match strongly, and an HTTP-date must be
+    /// exactly equal to the modification time.
+    fn if_range_matches(&self, request: &Request<()>) -> bool {
+        let if_range = match request.headers().get(header::IF_RANGE) {
+            Some(if_range) => if_range,
+            None => return true,
+        };
+        let if_range = match if_range.to_str() {
+            Ok(if_range) => if_range.trim(),
+            Err(..) => return false,
+        };
+
+        if if_range.starts_with('"') {
+            return if_range == self.etag;
+        }
+        if if_range.starts_with("W/") {
+            return false;
+        }
+        match (self.last_modified, httpdate::parse_http_date(if_range)) {
+            (Some(last_modified), Ok(date)) => last_modified == date,
+            _ => false,
+        }
+    }
+}
+
+/// Compares two entity tags with the weak comparison function.
+fn weak_eq(a: &str, b: &str) -> bool {
+    fn opaque(tag: &str) -> &str {
+        if tag.starts_with("W/") {
+            &tag[2..]
+        } else {
+            tag
+        }
+    }
+    opaque(a) == opaque(b)
+}
+
+#[cfg(unix)]
+fn get_inode(meta: &Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    meta.ino()
+}
+
+#[cfg(not(unix))]
+fn get_inode(_: &Metadata) -> u64 {
+    0
+}
+
+// ==== Range ====
+
+/// The maximum number of ranges accepted in a `Range` header field.
+///
+/// The requests with more ranges are served with the whole of the file.
+const MAX_RANGES: usize = 32;
+
+/// A satisfiable byte range, with the end position being exclusive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct ByteRange {
+    pub(crate) start: u64,
+    pub(crate) end: u64,
+}
+
+impl ByteRange {
+    pub(crate) fn len(&self) -> u64 {
+        self.end - self.start
+    }
+
+    pub(crate) fn content_range(&self, complete_len: u64) -> String {
+        format!("bytes {}-{}/{}", self.start, self.end - 1, complete_len)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum Ranges {
+    Satisfiable(Vec<ByteRange>),
+    Unsatisfiable,
+}
+
+/// Parses the value of `Range` against the representation of length `len`.
+///
+/// Returns `None` if the value is malformed or uses an unsupported unit,
+/// in which case the header field should be ignored.
+pub(crate) fn parse_range(value: &HeaderValue, len: u64) -> Option<Ranges> {
+    let value = value.to_str().ok()?.trim();
+    if value.len() < 6 || !value[..6].eq_ignore_ascii_case("bytes=") {
+        return None;
+    }
+
+    let mut ranges = vec![];
+    let mut num_specs = 0;
+    for spec in value[6..].split(',') {
+        let spec = spec.trim();
+        if spec.is_empty() {
+            continue;
+        }
+        num_specs += 1;
+        if num_specs > MAX_RANGES {
+            return None;
+        }
+
+        let pos = spec.find('-')?;
+        let (first, last) = (spec[..pos].trim(), spec[pos + 1..].trim());
+        let range = if first.is_empty() {
+            // suffix-byte-range-spec
+            let suffix_len: u64 = last.parse().ok()?;
+            if suffix_len == 0 || len == 0 {
+                continue;
+            }
+            ByteRange {
+                start: len.saturating_sub(suffix_len),
+                end: len,
+            }
+        } else {
+            let start: u64 = first.parse().ok()?;
+            let end = if last.is_empty() {
+                len
+            } else {
+                let last: u64 = last.parse().ok()?;
+                if last < start {
+                    return None;
+                }
+                cmp::min(last.saturating_add(1), len)
+            };
+            if start >= len {
+                continue;
+            }
+            ByteRange { start, end }
+        };
+        ranges.push(range);
+    }
+
+    if num_specs == 0 {
+        return None;
+    }
+    if ranges.is_empty() {
+        return Some(Ranges::Unsatisfiable);
+    }
+    Some(Ranges::Satisfiable(coalesce_ranges(ranges)))
+}
+
+/// Merges the ranges if some of them overlap or are adjacent.
+///
+/// The ranges are kept in the requested order otherwise. The total length of
+/// the returned ranges never exceeds the length of the representation.
+fn coalesce_ranges(mut ranges: Vec<ByteRange>) -> Vec<ByteRange> {
+    let mut sorted = ranges.clone();
+    sorted.sort_by_key(|range| range.start);
+    if sorted.windows(2).all(|w| w[0].end < w[1].start) {
+        return ranges;
+    }
+
+    ranges.clear();
+    for range in sorted {
+        match ranges.last_mut() {
+            Some(ref mut last) if range.start <= last.end => {
+                last.end = cmp::max(last.end, range.end);
+            }
+            _ => ranges.push(range),
+        }
+    }
+    ranges
+}
+
+pub(crate) fn generate_boundary() -> String {
+    let mut hasher = RandomState::new().build_hasher();
+    if let Ok(elapsed) = SystemTime::now().duration_since(UNIX_EPOCH) {
+        hasher.write_u64(elapsed.as_secs());
+        hasher.write_u32(elapsed.subsec_nanos());
+    }
+    format!("finchers-{:016x}", hasher.finish())
+}
+
+// ==== FileStream ====
+
+/// A part of the response body.
+#[derive(Debug)]
+enum Segment {
+    /// A chunk of bytes which is sent as it is.
+    Bytes(Bytes),
+
+    /// A slice of the file with the specified offset and length.
+    File { offset: u64, len: u64 },
+}
+
+impl Segment {
+    fn file(offset: u64, len: u64) -> Segment {
+        Segment::File { offset, len }
+    }
+
+    fn len(&self) -> u64 {
+        match *self {
+            Segment::Bytes(ref bytes) => bytes.len() as u64,
+            Segment::File { len, .. } => len,
+        }
+    }
+}
+
+/// The message body of `NamedFile`.
+///
+/// In addition to the implementation of `BufStream`, which reads the file
+/// into the buffers, the stream exposes the underlying file descriptor on
+/// Unix platforms so that the transport can send the content of file without
+/// copying it into the userspace. See `file_region` and `poll_write_to`.
 #[derive(Debug)]
 pub struct FileStream {
     file: File,
+    #[cfg(unix)]
+    fd: RawFd,
     buf: BytesMut,
     buf_size: usize,
-    len: u64,
+    segments: VecDeque<Segment>,
+    pos: u64,
+    #[cfg(unix)]
+    pending: Option<io::Cursor<Bytes>>,
+    #[cfg(target_os = "linux")]
+    zero_copy: bool,
 }
 
 impl FileStream {
-    fn new(file: File, meta: &Metadata) -> FileStream {
-        let buf_size = optimal_buf_size(&meta);
-        let len = meta.len();
+    #[cfg(unix)]
+    fn new(file: File, buf_size: usize, segments: Vec<Segment>) -> FileStream {
+        let file = file.into_std();
+        let fd = file.as_raw_fd();
+        FileStream {
+            file: File::from_std(file),
+            fd,
+            buf: BytesMut::new(),
+            buf_size: cmp::max(buf_size, 1),
+            segments: segments.into(),
+            pos: 0,
+            pending: None,
+            #[cfg(target_os = "linux")]
+            zero_copy: true,
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn new(file: File, buf_size: usize, segments: Vec<Segment>) -> FileStream {
         FileStream {
             file,
             buf: BytesMut::new(),
-            buf_size,
-            len,
+            buf_size: cmp::max(buf_size, 1),
+            segments: segments.into(),
+            pos: 0,
         }
     }
+
+    /// Returns the region of file to be sent next, if the next part of the
+    /// body is backed by the file.
+    ///
+    /// If this method returns `Some`, the transport may write the region
+    /// directly from the file descriptor and then report the number of
+    /// written bytes via `advance`. The transport must not change the position
+    /// of the file descriptor, that is, it has to pass the offset explicitly
+    /// as `sendfile(2)` and `pread(2)` do.
+    ///
+    /// If this method returns `None`, the transport should fall back to
+    /// `poll_buf` until the method returns `Some` again or the stream ends.
+    #[cfg(unix)]
+    pub fn file_region(&mut self) -> Option<FileRegion> {
+        loop {
+            match self.segments.front() {
+                Some(&Segment::File { len: 0, .. }) => {}
+                Some(&Segment::File { offset, len }) => {
+                    return Some(FileRegion {
+                        fd: self.fd,
+                        offset,
+                        len,
+                    });
+                }
+                _ => return None,
+            }
+            self.segments.pop_front();
+        }
+    }
+
+    /// Marks the first `n` bytes of the region returned by `file_region`
+    /// as sent.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the next part of the body is not backed by the
+    /// file or `n` exceeds the length of the region.
+    #[cfg(unix)]
+    pub fn advance(&mut self, n: u64) {
+        let finished = match self.segments.front_mut() {
+            Some(Segment::File { offset, len }) => {
+                assert!(n <= *len, "advanced beyond the end of the file region");
+                *offset += n;
+                *len -= n;
+                *len == 0
+            }
+            _ => panic!("the next part of the body is not backed by the file"),
+        };
+        if finished {
+            self.segments.pop_front();
+        }
+    }
+
+    /// Writes the whole body into `dst`.
+    ///
+    /// On Linux, the regions backed by the file are sent with `sendfile(2)`
+    /// without copying them into the userspace. The other parts of the body,
+    /// and the whole body on the other platforms, are read with `poll_buf`
+    /// and written with `AsyncWrite::poll_write`. When `dst` is not writable,
+    /// a buffered chunk is written instead so that the current task is
+    /// notified once `dst` becomes writable again.
+    ///
+    /// This method returns `Ready` once the body has been written completely.
+    /// It does not flush or shut down `dst`.
+    #[cfg(unix)]
+    pub fn poll_write_to<W>(&mut self, dst: &mut W) -> Poll<(), io::Error>
+    where
+        W: AsyncWrite + AsRawFd,
+    {
+        loop {
+            if let Some(ref mut chunk) = self.pending {
+                while chunk.has_remaining() {
+                    match try_ready!(dst.poll_write(chunk.bytes())) {
+                        0 => {
+                            return Err(io::Error::new(
+                                io::ErrorKind::WriteZero,
+                                "failed to write the body",
+                            ));
+                        }
+                        n => chunk.advance(n),
+                    }
+                }
+            }
+            self.pending = None;
+
+            #[cfg(target_os = "linux")]
+            {
+                if self.zero_copy {
+                    if let Some(region) = self.file_region() {
+                        match sendfile(dst.as_raw_fd(), region) {
+                            Ok(0) => {
+                                return Err(io::Error::new(
+                                    io::ErrorKind::UnexpectedEof,
+                                    "the file has been truncated",
+                                ));
+                            }
+                            Ok(n) => {
+                                self.advance(n);
+                                continue;
+                            }
+                            // Fall back to the buffered write, which registers
+                            // the current task for the writability of `dst`.
+                            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {}
+                            // `dst` or the file does not support `sendfile(2)`.
+                            Err(ref err)
+                                if err.raw_os_error() == Some(libc::EINVAL)
+                                    || err.raw_os_error() == Some(libc::ENOSYS) =>
+                            {
+                                self.zero_copy = false;
+                            }
+                            Err(err) => return Err(err),
+                        }
+                    }
+                }
+            }
+
+            match try_ready!(self.poll_buf()) {
+                Some(chunk) => self.pending = Some(chunk),
+                None => return Ok(Async::Ready(())),
+            }
+        }
+    }
+}
+
+/// A region of the file which forms the next part of the body of `FileStream`.
+#[cfg(unix)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileRegion {
+    /// The file descriptor of the opened file.
+    ///
+    /// The descriptor is valid as long as the `FileStream` is alive.
+    pub fd: RawFd,
+
+    /// The offset in the file where the region starts.
+    pub offset: u64,
+
+    /// The length of the region, in bytes.
+    pub len: u64,
+}
+
+/// Sends the region of file to `out_fd` and returns the number of sent bytes.
+#[cfg(target_os = "linux")]
+fn sendfile(out_fd: RawFd, region: FileRegion) -> io::Result<u64> {
+    // The maximum number of bytes transferred by a single call.
+    const MAX_COUNT: u64 = 0x7fff_f000;
+
+    let mut offset = region.offset as libc::off_t;
+    let count = cmp::min(region.len, MAX_COUNT) as usize;
+    let n = unsafe { libc::sendfile(out_fd, region.fd, &mut offset, count) };
+    if n < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(n as u64)
+    }
 }
 
 impl BufStream for FileStream {
@@ -129,28 +809,53 @@ impl BufStream for FileStream {
     type Error = io::Error;
 
     fn poll_buf(&mut self) -> Result<Async<Option<Self::Item>>, Self::Error> {
-        if self.len == 0 {
-            return Ok(Async::Ready(None));
-        }
+        loop {
+            let (offset, len) = match self.segments.front_mut() {
+                Some(Segment::File { offset, len }) => (offset, len),
+                Some(Segment::Bytes(..)) => match self.segments.pop_front() {
+                    Some(Segment::Bytes(bytes)) => {
+                        return Ok(Async::Ready(Some(io::Cursor::new(bytes))));
+                    }
+                    _ => unreachable!(),
+                },
+                None => return Ok(Async::Ready(None)),
+            };
 
-        if self.buf.remaining_mut() < self.buf_size {
-            self.buf.reserve(self.buf_size);
-        }
+            if *len == 0 {
+                self.segments.pop_front();
+                continue;
+            }
 
-        let n = match try_ready!(self.file.read_buf(&mut self.buf)) {
-            0 => return Ok(Async::Ready(None)),
-            n => n as u64,
-        };
+            if self.pos != *offset {
+                self.pos = try_ready!(self.file.poll_seek(SeekFrom::Start(*offset)));
+                continue;
+            }
 
-        let mut chunk = self.buf.take().freeze();
-        if n > self.len {
-            chunk = chunk.split_to(self.len as usize);
-            self.len = 0;
-        } else {
-            self.len = n;
-        }
+            if self.buf.remaining_mut() < self.buf_size {
+                self.buf.reserve(self.buf_size);
+            }
+
+            let n = match try_ready!(self.file.read_buf(&mut self.buf)) {
+                0 => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "the file has been truncated",
+                    ));
+                }
+                n => n as u64,
+            };
+            self.pos += n;
+
+            let mut chunk = self.buf.take().freeze();
+            if n > *len {
+                chunk = chunk.split_to(*len as usize);
+            }
+            let n = chunk.len() as u64;
+            *offset += n;
+            *len -= n;
 
-        Ok(Async::Ready(Some(io::Cursor::new(chunk))))
+            return Ok(Async::Ready(Some(io::Cursor::new(chunk))));
+        }
     }
 }
 
@@ -169,3 +874,171 @@ fn get_block_size(meta: &Metadata) -> usize {
 fn get_block_size(_: &Metadata) -> usize {
     8192
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &'static str, len: u64) -> Option<Ranges> {
+        parse_range(&HeaderValue::from_static(s), len)
+    }
+
+    fn satisfiable(ranges: &[(u64, u64)]) -> Option<Ranges> {
+        Some(Ranges::Satisfiable(
+            ranges
+                .iter()
+                .map(|&(start, end)| ByteRange { start, end })
+                .collect(),
+        ))
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse("bytes=0-499", 1000), satisfiable(&[(0, 500)]));
+        assert_eq!(parse("bytes=500-", 1000), satisfiable(&[(500, 1000)]));
+        assert_eq!(parse("bytes=-200", 1000), satisfiable(&[(800, 1000)]));
+        assert_eq!(parse("bytes=900-1999", 1000), satisfiable(&[(900, 1000)]));
+        assert_eq!(parse("bytes=-2000", 1000), satisfiable(&[(0, 1000)]));
+        assert_eq!(
+            parse("bytes=0-0, -1", 1000),
+            satisfiable(&[(0, 1), (999, 1000)])
+        );
+        assert_eq!(
+            parse("bytes=500-599, 0-99", 1000),
+            satisfiable(&[(500, 600), (0, 100)])
+        );
+    }
+
+    #[test]
+    fn test_parse_range_coalesced() {
+        assert_eq!(
+            parse("bytes=0-499, 250-749, -500", 1000),
+            satisfiable(&[(0, 1000)])
+        );
+        assert_eq!(
+            parse("bytes=100-199, 0-99, 300-399", 1000),
+            satisfiable(&[(0, 200), (300, 400)])
+        );
+        assert_eq!(
+            parse("bytes=0-, 0-, 0-, 0-", 1000),
+            satisfiable(&[(0, 1000)])
+        );
+    }
+
+    #[test]
+    fn test_parse_range_unsatisfiable() {
+        assert_eq!(parse("bytes=1000-", 1000), Some(Ranges::Unsatisfiable));
+        assert_eq!(parse("bytes=-0", 1000), Some(Ranges::Unsatisfiable));
+        assert_eq!(parse("bytes=0-", 0), Some(Ranges::Unsatisfiable));
+        assert_eq!(
+            parse("bytes=2000-2100, 1000-", 1000),
+            Some(Ranges::Unsatisfiable)
+        );
+    }
+
+    #[test]
+    fn test_parse_range_ignored() {
+        assert_eq!(parse("items=0-1", 1000), None);
+        assert_eq!(parse("bytes=", 1000), None);
+        assert_eq!(parse("bytes=5-1", 1000), None);
+        assert_eq!(parse("bytes=a-b", 1000), None);
+        assert_eq!(parse("bytes=0-1, foo", 1000), None);
+    }
+
+    #[cfg(unix)]
+    fn temp_file(name: &str, content: &[u8]) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("finchers-test-{}-{}", std::process::id(), name));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_region() {
+        let path = temp_file("file-region.txt", b"0123456789");
+        let file = std::fs::File::open(&path).unwrap();
+        let fd = file.as_raw_fd();
+
+        let mut stream = FileStream::new(
+            File::from_std(file),
+            4096,
+            vec![
+                Segment::Bytes(Bytes::from_static(b"--")),
+                Segment::file(0, 0),
+                Segment::file(2, 6),
+            ],
+        );
+
+        assert_eq!(stream.file_region(), None);
+        match stream.poll_buf() {
+            Ok(Async::Ready(Some(chunk))) => assert_eq!(chunk.into_inner(), "--"),
+            _ => panic!("expected a chunk of bytes"),
+        }
+        assert_eq!(
+            stream.file_region(),
+            Some(FileRegion {
+                fd,
+                offset: 2,
+                len: 6
+            })
+        );
+
+        stream.advance(4);
+        assert_eq!(
+            stream.file_region(),
+            Some(FileRegion {
+                fd,
+                offset: 6,
+                len: 2
+            })
+        );
+
+        stream.advance(2);
+        assert_eq!(stream.file_region(), None);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_poll_write_to() {
+        use std::io::Read;
+        use std::os::unix::net::UnixStream;
+
+        let content: Vec<u8> = (0..2_000_000u32).map(|i| (i % 251) as u8).collect();
+        let path = temp_file("write-to.bin", &content);
+
+        let (tx, mut rx) = UnixStream::pair().unwrap();
+        let reader = std::thread::spawn(move || {
+            let mut received = vec![];
+            rx.read_to_end(&mut received).unwrap();
+            received
+        });
+
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        let file = File::from_std(std::fs::File::open(&path).unwrap());
+        rt.block_on(futures::future::lazy(move || {
+            let mut tx = tokio::net::UnixStream::from_std(tx, &Default::default()).unwrap();
+            let mut stream = FileStream::new(
+                file,
+                4096,
+                vec![
+                    Segment::Bytes(Bytes::from_static(b"--")),
+                    Segment::file(10, 1_500_000),
+                    Segment::Bytes(Bytes::from_static(b"--")),
+                ],
+            );
+            futures::future::poll_fn(move || stream.poll_write_to(&mut tx))
+        }))
+        .unwrap();
+
+        let received = reader.join().unwrap();
+        assert_eq!(received.len(), 1_500_004);
+        assert_eq!(&received[..2], b"--");
+        assert_eq!(&received[2..1_500_002], &content[10..1_500_010]);
+        assert_eq!(&received[1_500_002..], b"--");
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
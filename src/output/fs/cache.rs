@@ -0,0 +1,162 @@
+use std::time::Duration;
+
+use http::header::HeaderValue;
+
+/// The value of `Cache-Control` used for the immutable files.
+const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+
+/// A set of rules that determines the value of `Cache-Control` for the files.
+///
+/// The rules are matched against the file name in the order of addition,
+/// and the first matching rule is applied. If none of them matches, the
+/// default value set by `max_age` is used.
+///
+/// # Example
+///
+/// ```
+/// use finchers::output::fs::CachePolicy;
+/// use std::time::Duration;
+///
+/// let policy = CachePolicy::new()
+///     .no_cache_html()
+///     .immutable("*.????????.js")
+///     .extension("woff2", "public, max-age=2592000")
+///     .max_age(Duration::from_secs(3600));
+/// # drop(policy);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CachePolicy {
+    rules: Vec<(Matcher, HeaderValue)>,
+    default: Option<HeaderValue>,
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    Extension(String),
+    Glob(String),
+}
+
+impl Matcher {
+    fn matches(&self, name: &str) -> bool {
+        match self {
+            Matcher::Extension(ext) => name
+                .rfind('.')
+                .map_or(false, |pos| name[pos + 1..].eq_ignore_ascii_case(ext)),
+            Matcher::Glob(pattern) => glob_match(pattern.as_bytes(), name.as_bytes()),
+        }
+    }
+}
+
+impl CachePolicy {
+    /// Create an empty `CachePolicy`.
+    pub fn new() -> CachePolicy {
+        CachePolicy::default()
+    }
+
+    /// Sets the default value to `public, max-age=<seconds>`.
+    pub fn max_age(self, max_age: Duration) -> Self {
+        let value = format!("public, max-age={}", max_age.as_secs());
+        CachePolicy {
+            default: Some(HeaderValue::from_str(&value).expect("should be a valid header value")),
+            ..self
+        }
+    }
+
+    /// Adds a rule that marks the files matching the glob pattern as immutable.
+    ///
+    /// This is intended for the fingerprinted file names such as `app.3f2a9c1b.js`.
+    pub fn immutable(self, pattern: &str) -> Self {
+        self.glob(pattern, IMMUTABLE)
+    }
+
+    /// Adds a rule that sets `no-cache` to the HTML files.
+    pub fn no_cache_html(self) -> Self {
+        self.extension("html", "no-cache")
+            .extension("htm", "no-cache")
+    }
+
+    /// Adds a rule applied to the files with the specified extension.
+    pub fn extension(mut self, extension: &str, value: &str) -> Self {
+        let value = HeaderValue::from_str(value).expect("invalid Cache-Control value");
+        let extension = extension.trim_start_matches('.').to_owned();
+        self.rules.push((Matcher::Extension(extension), value));
+        self
+    }
+
+    /// Adds a rule applied to the files whose name matches the glob pattern.
+    ///
+    /// The pattern may contain the wildcards `*` (any sequence of characters)
+    /// and `?` (any single character).
+    pub fn glob(mut self, pattern: &str, value: &str) -> Self {
+        let value = HeaderValue::from_str(value).expect("invalid Cache-Control value");
+        self.rules.push((Matcher::Glob(pattern.to_owned()), value));
+        self
+    }
+
+    /// Returns the value of `Cache-Control` for the specified file name.
+    pub fn cache_control(&self, name: &str) -> Option<&HeaderValue> {
+        self.rules
+            .iter()
+            .find(|(matcher, _)| matcher.matches(name))
+            .map(|(_, value)| value)
+            .or_else(|| self.default.as_ref())
+    }
+}
+
+/// Matches the name against a glob pattern with the wildcards `*` and `?`.
+fn glob_match(pattern: &[u8], name: &[u8]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == b'?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((bp, bn)) => {
+                    p = bp + 1;
+                    n = bn + 1;
+                    backtrack = Some((bp, bn + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match(b"*.js", b"app.js"));
+        assert!(glob_match(b"app.????????.js", b"app.3f2a9c1b.js"));
+        assert!(!glob_match(b"app.????????.js", b"app.js"));
+        assert!(glob_match(b"*.*.css", b"main.abc.css"));
+        assert!(!glob_match(b"*.css", b"main.css.map"));
+        assert!(glob_match(b"*", b""));
+    }
+
+    #[test]
+    fn test_cache_control() {
+        let policy = CachePolicy::new()
+            .no_cache_html()
+            .immutable("*.????????.js")
+            .extension(".css", "public, max-age=600")
+            .max_age(Duration::from_secs(60));
+
+        let value = |name| policy.cache_control(name).map(|h| h.to_str().unwrap());
+        assert_eq!(value("index.HTML"), Some("no-cache"));
+        assert_eq!(value("app.3f2a9c1b.js"), Some(IMMUTABLE));
+        assert_eq!(value("style.css"), Some("public, max-age=600"));
+        assert_eq!(value("app.js"), Some("public, max-age=60"));
+        assert_eq!(CachePolicy::new().cache_control("app.js"), None);
+    }
+}
@@ -0,0 +1,425 @@
+use std::cmp::Ordering;
+use std::fmt::Write as _Write;
+use std::fs::Metadata;
+use std::io;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use futures::{try_ready, Async, Future, Poll, Stream};
+use http::header::HeaderValue;
+use http::{header, Request, Response};
+use percent_encoding::{utf8_percent_encode, PATH_SEGMENT_ENCODE_SET};
+use tokio::fs::{read_dir, DirEntry, ReadDir, ReadDirFuture};
+
+use super::super::IntoResponse;
+use crate::endpoint::syntax::encoded::EncodedStr;
+use crate::endpoints::header::Accept;
+
+/// An instance of `Output` representing the list of entries in a directory.
+///
+/// The response is rendered as an HTML page, or a JSON array if the client
+/// prefers `application/json` in `Accept`.
+#[derive(Debug)]
+pub struct DirListing {
+    path: String,
+    base: String,
+    entries: Vec<DirEntryInfo>,
+}
+
+impl DirListing {
+    /// Create a future which reads the entries in the specified directory.
+    ///
+    /// `request_path` is the (percent-encoded) path of the request, which is
+    /// used as the base of the links to the entries. The entries are sorted
+    /// according to the query parameters `sort` (`name`, `size` or `modified`)
+    /// and `order` (`asc` or `desc`) in `query`.
+    pub fn read(
+        path: PathBuf,
+        request_path: &str,
+        query: Option<&str>,
+        hidden_files: bool,
+    ) -> ReadDirListing {
+        let mut base = request_path.to_owned();
+        if !base.ends_with('/') {
+            base.push('/');
+        }
+        let path_display = match EncodedStr::new(&base) {
+            Some(encoded) => encoded.percent_decode_lossy().into_owned(),
+            None => base.clone(),
+        };
+
+        ReadDirListing {
+            opening: Some(read_dir(path)),
+            read_dir: None,
+            pending: None,
+            hidden_files,
+            sort: SortOrder::from_query(query),
+            listing: Some(DirListing {
+                path: path_display,
+                base,
+                entries: vec![],
+            }),
+        }
+    }
+
+    /// Returns the decoded path of this directory, with a trailing slash.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the entries in this directory.
+    pub fn entries(&self) -> &[DirEntryInfo] {
+        &self.entries
+    }
+
+    fn parent(&self) -> Option<&str> {
+        let trimmed = self.base.trim_end_matches('/');
+        trimmed.rfind('/').map(|pos| &self.base[..=pos])
+    }
+
+    fn render_html(&self) -> String {
+        let mut html = String::new();
+        let title = escape_html(&self.path);
+        let _ = write!(
+            html,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>Index of {0}</title>\n</head>\n<body>\n<h1>Index of {0}</h1>\n\
+             <table>\n<tr><th>Name</th><th>Size</th><th>Last modified</th></tr>\n",
+            title
+        );
+        if let Some(parent) = self.parent() {
+            let _ = writeln!(
+                html,
+                "<tr><td><a href=\"{}\">../</a></td><td></td><td></td></tr>",
+                escape_html(parent)
+            );
+        }
+        for entry in &self.entries {
+            let name = if entry.is_dir() {
+                format!("{}/", entry.name)
+            } else {
+                entry.name.clone()
+            };
+            let size = if entry.is_dir() {
+                "-".to_owned()
+            } else {
+                entry.size.to_string()
+            };
+            let modified = entry
+                .modified
+                .map(httpdate::fmt_http_date)
+                .unwrap_or_else(|| "-".to_owned());
+            let _ = writeln!(
+                html,
+                "<tr><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td></tr>",
+                escape_html(&entry.href),
+                escape_html(&name),
+                size,
+                modified,
+            );
+        }
+        html.push_str("</table>\n</body>\n</html>\n");
+        html
+    }
+
+    fn render_json(&self) -> String {
+        let entries: Vec<_> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                serde_json::json!({
+                    "name": entry.name,
+                    "href": entry.href,
+                    "type": entry.entry_type.as_str(),
+                    "size": entry.size,
+                    "modified": entry.modified
+                        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                        .map(|d| d.as_secs()),
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "path": self.path,
+            "entries": entries,
+        })
+        .to_string()
+    }
+}
+
+impl IntoResponse for DirListing {
+    type Body = String;
+
+    fn into_response(self, request: &Request<()>) -> Response<Self::Body> {
+        let candidates = [mime::TEXT_HTML_UTF_8, mime::APPLICATION_JSON];
+        let is_json = Accept::from_headers(request.headers())
+            .ok()
+            .and_then(|accept| accept.preferred(&candidates).cloned())
+            .map_or(false, |mime| mime == mime::APPLICATION_JSON);
+
+        let (body, content_type) = if is_json {
+            (self.render_json(), "application/json")
+        } else {
+            (self.render_html(), "text/html; charset=utf-8")
+        };
+
+        let mut response = Response::new(body);
+        response
+            .headers_mut()
+            .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+        response
+            .headers_mut()
+            .insert(header::VARY, HeaderValue::from_static("accept"));
+        response
+    }
+}
+
+/// The type of an entry in `DirListing`.
+#[allow(missing_docs)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EntryType {
+    File,
+    Directory,
+    Symlink,
+    Other,
+}
+
+impl EntryType {
+    fn from_metadata(meta: &Metadata) -> EntryType {
+        let file_type = meta.file_type();
+        if file_type.is_dir() {
+            EntryType::Directory
+        } else if file_type.is_file() {
+            EntryType::File
+        } else if file_type.is_symlink() {
+            EntryType::Symlink
+        } else {
+            EntryType::Other
+        }
+    }
+
+    /// Returns the name of this type used in the JSON representation.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EntryType::File => "file",
+            EntryType::Directory => "directory",
+            EntryType::Symlink => "symlink",
+            EntryType::Other => "other",
+        }
+    }
+}
+
+/// An entry in `DirListing`.
+#[derive(Debug)]
+pub struct DirEntryInfo {
+    name: String,
+    href: String,
+    entry_type: EntryType,
+    size: u64,
+    modified: Option<SystemTime>,
+}
+
+impl DirEntryInfo {
+    /// Returns the name of this entry.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the percent-encoded link to this entry.
+    pub fn href(&self) -> &EncodedStr {
+        // `href` is built by percent-encoding each segment.
+        unsafe { EncodedStr::new_unchecked(&self.href) }
+    }
+
+    /// Returns the type of this entry.
+    pub fn entry_type(&self) -> EntryType {
+        self.entry_type
+    }
+
+    /// Returns the length of this entry in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns the last modification time of this entry, if available.
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+
+    fn is_dir(&self) -> bool {
+        self.entry_type == EntryType::Directory
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+enum SortKey {
+    Name,
+    Size,
+    Modified,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct SortOrder {
+    key: SortKey,
+    descending: bool,
+}
+
+impl SortOrder {
+    fn from_query(query: Option<&str>) -> SortOrder {
+        let mut order = SortOrder {
+            key: SortKey::Name,
+            descending: false,
+        };
+        for (name, value) in url::form_urlencoded::parse(query.unwrap_or("").as_bytes()) {
+            match (&*name, &*value) {
+                ("sort", "name") => order.key = SortKey::Name,
+                ("sort", "size") => order.key = SortKey::Size,
+                ("sort", "modified") => order.key = SortKey::Modified,
+                ("order", "asc") => order.descending = false,
+                ("order", "desc") => order.descending = true,
+                _ => {}
+            }
+        }
+        order
+    }
+
+    /// Compares two entries. The directories are always listed first.
+    fn compare(self, a: &DirEntryInfo, b: &DirEntryInfo) -> Ordering {
+        let ordering = match self.key {
+            SortKey::Name => a.name.cmp(&b.name),
+            SortKey::Size => a.size.cmp(&b.size).then_with(|| a.name.cmp(&b.name)),
+            SortKey::Modified => a
+                .modified
+                .cmp(&b.modified)
+                .then_with(|| a.name.cmp(&b.name)),
+        };
+        let ordering = if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        };
+        b.is_dir().cmp(&a.is_dir()).then(ordering)
+    }
+}
+
+/// A future which reads the entries of a directory and returns a `DirListing`.
+#[allow(missing_debug_implementations)]
+pub struct ReadDirListing {
+    opening: Option<ReadDirFuture<PathBuf>>,
+    read_dir: Option<ReadDir>,
+    pending: Option<DirEntry>,
+    hidden_files: bool,
+    sort: SortOrder,
+    listing: Option<DirListing>,
+}
+
+impl Future for ReadDirListing {
+    type Item = DirListing;
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        if let Some(ref mut opening) = self.opening {
+            self.read_dir = Some(try_ready!(opening.poll()));
+        }
+        self.opening = None;
+
+        loop {
+            if let Some(ref entry) = self.pending {
+                let meta = try_ready!(entry.poll_metadata());
+                let listing = self.listing.as_mut().expect("cannot poll twice");
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let mut href = listing.base.clone();
+                href.extend(utf8_percent_encode(&name, PATH_SEGMENT_ENCODE_SET));
+                let entry_type = EntryType::from_metadata(&meta);
+                if entry_type == EntryType::Directory {
+                    href.push('/');
+                }
+                listing.entries.push(DirEntryInfo {
+                    name,
+                    href,
+                    entry_type,
+                    size: meta.len(),
+                    modified: meta.modified().ok(),
+                });
+            }
+            self.pending = None;
+
+            let read_dir = self.read_dir.as_mut().expect("cannot poll twice");
+            match try_ready!(read_dir.poll()) {
+                Some(entry) => {
+                    let is_hidden = entry.file_name().to_string_lossy().starts_with('.');
+                    if self.hidden_files || !is_hidden {
+                        self.pending = Some(entry);
+                    }
+                }
+                None => {
+                    let mut listing = self.listing.take().expect("cannot poll twice");
+                    let sort = self.sort;
+                    listing.entries.sort_by(|a, b| sort.compare(a, b));
+                    return Ok(Async::Ready(listing));
+                }
+            }
+        }
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, entry_type: EntryType, size: u64) -> DirEntryInfo {
+        DirEntryInfo {
+            name: name.to_owned(),
+            href: format!("/{}", name),
+            entry_type,
+            size,
+            modified: None,
+        }
+    }
+
+    #[test]
+    fn test_sort_order() {
+        let mut entries = vec![
+            entry("b.txt", EntryType::File, 1),
+            entry("a.txt", EntryType::File, 2),
+            entry("z", EntryType::Directory, 0),
+        ];
+
+        let order = SortOrder::from_query(None);
+        entries.sort_by(|a, b| order.compare(a, b));
+        let names: Vec<_> = entries.iter().map(|e| e.name()).collect();
+        assert_eq!(names, vec!["z", "a.txt", "b.txt"]);
+
+        let order = SortOrder::from_query(Some("sort=size&order=desc"));
+        entries.sort_by(|a, b| order.compare(a, b));
+        let names: Vec<_> = entries.iter().map(|e| e.name()).collect();
+        assert_eq!(names, vec!["z", "a.txt", "b.txt"]);
+
+        let order = SortOrder::from_query(Some("sort=size"));
+        entries.sort_by(|a, b| order.compare(a, b));
+        let names: Vec<_> = entries.iter().map(|e| e.name()).collect();
+        assert_eq!(names, vec!["z", "b.txt", "a.txt"]);
+    }
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(
+            escape_html("<a href=\"x\">&'"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&#39;"
+        );
+    }
+}
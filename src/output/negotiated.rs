@@ -0,0 +1,93 @@
+use http::header::HeaderValue;
+use http::{header, Request, Response, StatusCode};
+use serde::Serialize;
+use serde_json::Value;
+
+use super::IntoResponse;
+use crate::endpoints::header::Accept;
+use crate::error;
+
+/// An instance of `Output` whose representation is chosen from the header
+/// field `Accept` in the request.
+///
+/// The supported media types are `application/json`,
+/// `application/x-www-form-urlencoded` and `text/plain`, in the order of
+/// preference. The plain text representation is the value itself if it is
+/// serialized as a string, or the pretty-printed JSON otherwise.
+///
+/// If none of them is acceptable, it replies `406 Not Acceptable`.
+#[derive(Debug)]
+pub struct Negotiated<T>(pub T);
+
+impl<T> From<T> for Negotiated<T> {
+    #[inline]
+    fn from(inner: T) -> Self {
+        Negotiated(inner)
+    }
+}
+
+impl<T: Serialize> IntoResponse for Negotiated<T> {
+    type Body = String;
+
+    fn into_response(self, request: &Request<()>) -> Response<Self::Body> {
+        let candidates = [
+            mime::APPLICATION_JSON,
+            mime::APPLICATION_WWW_FORM_URLENCODED,
+            mime::TEXT_PLAIN_UTF_8,
+        ];
+
+        let accept = match Accept::from_headers(request.headers()) {
+            Ok(accept) => accept,
+            Err(err) => return error::bad_request(err).into_response(request),
+        };
+
+        let mime = match accept.preferred(&candidates) {
+            Some(mime) => mime,
+            None => {
+                return error::err_msg(
+                    format!(
+                        "none of the available media types is acceptable: {}",
+                        candidates
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    StatusCode::NOT_ACCEPTABLE,
+                )
+                .into_response(request);
+            }
+        };
+
+        let body = if *mime == mime::APPLICATION_JSON {
+            serde_json::to_string(&self.0).map_err(error::internal_server_error)
+        } else if *mime == mime::APPLICATION_WWW_FORM_URLENCODED {
+            serde_qs::to_string(&self.0)
+                .map_err(|err| error::internal_server_error(err.to_string()))
+        } else {
+            match serde_json::to_value(&self.0) {
+                Ok(Value::String(s)) => Ok(s),
+                Ok(value) => {
+                    serde_json::to_string_pretty(&value).map_err(error::internal_server_error)
+                }
+                Err(err) => Err(error::internal_server_error(err)),
+            }
+        };
+
+        match body {
+            Ok(body) => {
+                let mut response = Response::new(body);
+                response.headers_mut().insert(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_str(&mime.to_string())
+                        .expect("should be a valid header value"),
+                );
+                response
+                    .headers_mut()
+                    .insert(header::VARY, HeaderValue::from_static("accept"));
+                response
+            }
+            Err(err) => err.into_response(request),
+        }
+    }
+}
@@ -26,6 +26,15 @@ impl Redirect {
             ..self
         }
     }
+
+    /// Sets the value of header field `Location` generated at runtime,
+    /// e.g. by the `to_path` generated by `#[derive(ExtractPath)]`.
+    pub fn location_value(self, location: HeaderValue) -> Redirect {
+        Redirect {
+            location: Some(location),
+            ..self
+        }
+    }
 }
 
 macro_rules! impl_constructors {
@@ -5,7 +5,10 @@
 use {
     crate::{
         action::{ActionContext, EndpointAction, Preflight, PreflightContext},
-        endpoint::{Endpoint, IsEndpoint},
+        endpoint::{
+            syntax::verb::{MethodNotAllowed, Verbs},
+            Endpoint, IsEndpoint,
+        },
         error::Error,
         output::IntoResponse,
     },
@@ -14,9 +17,10 @@ use {
     futures::{future, Async, Future, Poll},
     http::{
         header::{HeaderMap, HeaderValue},
-        Request, Response,
+        Method, Request, Response, StatusCode,
     },
     izanami_service::{MakeService, Service},
+    izanami_util::buf_stream::{BufStream, Either},
     std::{cell::Cell, io, marker::PhantomData, ptr::NonNull, sync::Arc},
 };
 
@@ -44,6 +48,7 @@ impl<E: IsEndpoint> EndpointServiceExt for E {
 #[derive(Debug)]
 pub struct App<E> {
     endpoint: Arc<E>,
+    config: Config,
 }
 
 impl<E> App<E> {
@@ -51,14 +56,45 @@ impl<E> App<E> {
     pub fn new(endpoint: E) -> Self {
         App {
             endpoint: Arc::new(endpoint),
+            config: Config::default(),
         }
     }
+
+    /// Sets whether to handle `HEAD` and `OPTIONS` requests automatically.
+    ///
+    /// When enabled, a `HEAD` request is served by the route which accepts
+    /// `GET` and the response body is discarded, keeping `Content-Length`.
+    /// An `OPTIONS` request to a path without explicit handler is answered
+    /// with `204 No Content` and the `Allow` header of the matched routes.
+    ///
+    /// The default value is `false`.
+    pub fn auto_head_and_options(mut self, enabled: bool) -> Self {
+        self.config.auto_head_and_options = enabled;
+        self
+    }
+
+    /// Sets the default maximum size of request body, in bytes.
+    ///
+    /// This value is used by the endpoints which buffer the request body, such as
+    /// `endpoints::body::json`, unless the limit is specified on the endpoint.
+    /// By default, the size is not limited.
+    pub fn body_limit(mut self, limit: u64) -> Self {
+        self.config.body_limit = Some(limit);
+        self
+    }
+
+    /// Wraps the services created by this `App` with the specified `Layer`.
+    pub fn with_layer<L>(self, layer: L) -> Layered<Self, L> {
+        Layered::new(self, layer)
+    }
 }
 
 impl<E, Ctx, Bd> MakeService<Ctx, Request<Bd>> for App<E>
 where
     E: Endpoint<Bd>,
     E::Output: IntoResponse,
+    ResponseBody<Bd, E>: BufStream,
+    <ResponseBody<Bd, E> as BufStream>::Error: Into<BoxedStdError>,
 {
     type Response = Response<ResponseBody<Bd, E>>;
     type Error = io::Error;
@@ -67,7 +103,7 @@ where
     type Future = future::FutureResult<Self::Service, Self::MakeError>;
 
     fn make_service(&self, _: Ctx) -> Self::Future {
-        future::ok(AppService::new(self.endpoint.clone()))
+        future::ok(AppService::new(self.endpoint.clone(), self.config.clone()))
     }
 }
 
@@ -75,6 +111,7 @@ where
 #[allow(missing_debug_implementations)]
 pub struct AppService<Bd, E: Endpoint<Bd>> {
     endpoint: E,
+    config: Config,
     _marker: PhantomData<fn(Bd)>,
 }
 
@@ -82,18 +119,21 @@ impl<Bd, E> AppService<Bd, E>
 where
     E: Endpoint<Bd>,
 {
-    pub(crate) fn new(endpoint: E) -> Self {
+    pub(crate) fn new(endpoint: E, config: Config) -> Self {
         AppService {
             endpoint,
+            config,
             _marker: PhantomData,
         }
     }
 
     pub(crate) fn dispatch(&self, request: Request<Bd>) -> AppFuture<Bd, E> {
         let (parts, body) = request.into_parts();
+        let mut context = Context::new(Request::from_parts(parts, ()));
+        context.config = self.config.clone();
         AppFuture {
             state: AppFutureState::Start(Some(self.endpoint.action())),
-            context: Context::new(Request::from_parts(parts, ())),
+            context,
             body: Some(body),
         }
     }
@@ -103,20 +143,24 @@ impl<Bd, E> Service<Request<Bd>> for AppService<Bd, E>
 where
     E: Endpoint<Bd> + Clone,
     E::Output: IntoResponse,
+    ResponseBody<Bd, E>: BufStream,
+    <ResponseBody<Bd, E> as BufStream>::Error: Into<BoxedStdError>,
 {
     type Response = Response<ResponseBody<Bd, E>>;
     type Error = io::Error;
-    type Future = AppFuture<Bd, E>;
+    type Future = ResponseFuture<Bd, E>;
 
     fn poll_ready(&mut self) -> Poll<(), Self::Error> {
         Ok(Async::Ready(()))
     }
 
     fn call(&mut self, request: Request<Bd>) -> Self::Future {
-        self.dispatch(request)
+        ResponseFuture::new(self.dispatch(request))
     }
 }
 
+type BoxedStdError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
 #[doc(hidden)]
 #[allow(missing_debug_implementations)]
 pub struct AppFuture<Bd, E: Endpoint<Bd>> {
@@ -155,6 +199,29 @@ where
             };
         }
     }
+
+    fn handle_error(&self, mut err: Error) -> Response<String> {
+        if !self.context.auto_head_and_options() {
+            return err.into_response(&self.context.request);
+        }
+
+        // Adds the implicitly handled methods to the header field `Allow`.
+        let allowed = match err.downcast_mut::<MethodNotAllowed>() {
+            Some(err) => err.allowed_mut(),
+            None => return err.into_response(&self.context.request),
+        };
+        *allowed |= Verbs::OPTIONS;
+        if allowed.contains(&Method::GET) {
+            *allowed |= Verbs::HEAD;
+        }
+
+        let mut response = err.into_response(&self.context.request);
+        if *self.context.method() == Method::OPTIONS {
+            *response.status_mut() = StatusCode::NO_CONTENT;
+            response.body_mut().clear();
+        }
+        response
+    }
 }
 
 impl<Bd, E> Future for AppFuture<Bd, E>
@@ -169,10 +236,8 @@ where
         let mut response = match ready!(self.poll_apply()) {
             Ok(output) => output
                 .into_response(&self.context.request)
-                .map(izanami_util::buf_stream::Either::Right),
-            Err(err) => err
-                .into_response(&self.context.request)
-                .map(izanami_util::buf_stream::Either::Left),
+                .map(Either::Right),
+            Err(err) => self.handle_error(err).map(Either::Left),
         };
 
         if let Some(cookies) = &self.context.cookies {
@@ -195,7 +260,162 @@ where
     }
 }
 
-pub type ResponseBody<Bd, E> = izanami_util::buf_stream::Either<
+/// A future which returns the HTTP response to the client.
+///
+/// If the automatic `HEAD` handling is enabled, this future discards the
+/// response body of `HEAD` requests without reading it. The header fields,
+/// including `Content-Length` if the response has it, are left unchanged.
+#[doc(hidden)]
+#[allow(missing_debug_implementations)]
+pub struct ResponseFuture<Bd, E: Endpoint<Bd>>
+where
+    E::Output: IntoResponse,
+{
+    future: AppFuture<Bd, E>,
+}
+
+impl<Bd, E> ResponseFuture<Bd, E>
+where
+    E: Endpoint<Bd>,
+    E::Output: IntoResponse,
+{
+    pub(crate) fn new(future: AppFuture<Bd, E>) -> Self {
+        ResponseFuture { future }
+    }
+}
+
+impl<Bd, E> Future for ResponseFuture<Bd, E>
+where
+    E: Endpoint<Bd>,
+    E::Output: IntoResponse,
+    ResponseBody<Bd, E>: BufStream,
+    <ResponseBody<Bd, E> as BufStream>::Error: Into<BoxedStdError>,
+{
+    type Item = Response<ResponseBody<Bd, E>>;
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let response = futures::try_ready!(self.future.poll());
+        let cx = &self.future.context;
+        if cx.auto_head_and_options() && *cx.method() == Method::HEAD {
+            return Ok(Async::Ready(response.map(|_| Either::Left(String::new()))));
+        }
+        Ok(Async::Ready(response))
+    }
+}
+
+// ==== Config ====
+
+/// A set of configuration values shared by the services created from an `App`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Config {
+    pub(crate) auto_head_and_options: bool,
+    pub(crate) body_limit: Option<u64>,
+}
+
+// ==== Layer ====
+
+/// A trait representing a middleware which wraps the HTTP services.
+///
+/// Unlike `Wrapper`, the layer can observe the raw HTTP requests and the final
+/// HTTP responses, including the ones converted from errors.
+pub trait Layer<S> {
+    /// The type of wrapped service.
+    type Service;
+
+    /// Wraps the specified service and returns a new service.
+    fn layer(&self, inner: S) -> Self::Service;
+}
+
+/// Create a `Layer` from the specified function.
+pub fn layer_fn<F>(f: F) -> LayerFn<F> {
+    LayerFn { f }
+}
+
+#[allow(missing_docs)]
+#[derive(Debug, Copy, Clone)]
+pub struct LayerFn<F> {
+    f: F,
+}
+
+impl<S, F, T> Layer<S> for LayerFn<F>
+where
+    F: Fn(S) -> T,
+{
+    type Service = T;
+
+    #[inline]
+    fn layer(&self, inner: S) -> Self::Service {
+        (self.f)(inner)
+    }
+}
+
+/// A `MakeService` which wraps the services created by the inner `MakeService`
+/// with a `Layer`.
+#[derive(Debug)]
+pub struct Layered<M, L> {
+    inner: M,
+    layer: Arc<L>,
+}
+
+impl<M, L> Layered<M, L> {
+    fn new(inner: M, layer: L) -> Self {
+        Layered {
+            inner,
+            layer: Arc::new(layer),
+        }
+    }
+
+    /// Wraps the services created by this value with the specified `Layer`.
+    pub fn with_layer<L2>(self, layer: L2) -> Layered<Self, L2> {
+        Layered::new(self, layer)
+    }
+}
+
+impl<M, L, Ctx, Bd> MakeService<Ctx, Request<Bd>> for Layered<M, L>
+where
+    M: MakeService<Ctx, Request<Bd>>,
+    L: Layer<M::Service>,
+    L::Service: Service<Request<Bd>>,
+{
+    type Response = <L::Service as Service<Request<Bd>>>::Response;
+    type Error = <L::Service as Service<Request<Bd>>>::Error;
+    type Service = L::Service;
+    type MakeError = M::MakeError;
+    type Future = LayeredFuture<M::Future, L>;
+
+    fn make_service(&self, cx: Ctx) -> Self::Future {
+        LayeredFuture {
+            future: self.inner.make_service(cx),
+            layer: self.layer.clone(),
+        }
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct LayeredFuture<F, L> {
+    future: F,
+    layer: Arc<L>,
+}
+
+impl<F, L> Future for LayeredFuture<F, L>
+where
+    F: Future,
+    L: Layer<F::Item>,
+{
+    type Item = L::Service;
+    type Error = F::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let inner = futures::try_ready!(self.future.poll());
+        Ok(Async::Ready(self.layer.layer(inner)))
+    }
+}
+
+// ==== ResponseBody ====
+
+pub type ResponseBody<Bd, E> = Either<
     String, //
     <<E as Endpoint<Bd>>::Output as IntoResponse>::Body,
 >;
@@ -251,6 +471,7 @@ pub struct Context {
     request: Request<()>,
     cookies: Option<CookieJar>,
     response_headers: Option<HeaderMap>,
+    config: Config,
 }
 
 impl Context {
@@ -259,9 +480,18 @@ impl Context {
             request,
             cookies: None,
             response_headers: None,
+            config: Config::default(),
         }
     }
 
+    pub(crate) fn auto_head_and_options(&self) -> bool {
+        self.config.auto_head_and_options
+    }
+
+    pub(crate) fn body_limit(&self) -> Option<u64> {
+        self.config.body_limit
+    }
+
     #[inline]
     pub fn set<R>(&mut self, f: impl FnOnce() -> R) -> R {
         set_task_cx(self, f)
@@ -21,17 +21,20 @@ use {
     crate::{
         endpoint::Endpoint,
         error::Error,
-        service::{AppFuture, AppService},
+        output::IntoResponse,
+        service::{AppFuture, AppService, Config, ResponseBody, ResponseFuture},
     },
-    bytes::Bytes,
+    bytes::{BufMut, Bytes},
+    cookie::Cookie,
     futures::{future, Poll},
     http::{
         header::{self, HeaderMap, HeaderName, HeaderValue},
-        Request, Uri,
+        Request, Response, Uri,
     },
     izanami_util::buf_stream::BufStream,
     mime::Mime,
-    std::io,
+    serde::de::DeserializeOwned,
+    std::{io, ops::Deref, str},
     tokio::runtime::current_thread::Runtime,
 };
 
@@ -87,6 +90,7 @@ pub struct TestRunner<E> {
     endpoint: E,
     rt: Runtime,
     default_headers: Option<HeaderMap>,
+    config: Config,
 }
 
 #[allow(clippy::new_ret_no_self)]
@@ -105,6 +109,7 @@ where
             endpoint,
             rt,
             default_headers: None,
+            config: Config::default(),
         }
     }
 
@@ -114,6 +119,22 @@ where
         self.default_headers.get_or_insert_with(Default::default)
     }
 
+    /// Sets whether to handle `HEAD` and `OPTIONS` requests automatically.
+    ///
+    /// See `App::auto_head_and_options` for details.
+    pub fn auto_head_and_options(&mut self, enabled: bool) -> &mut Self {
+        self.config.auto_head_and_options = enabled;
+        self
+    }
+
+    /// Sets the default maximum size of request body, in bytes.
+    ///
+    /// See `App::body_limit` for details.
+    pub fn body_limit(&mut self, limit: u64) -> &mut Self {
+        self.config.body_limit = Some(limit);
+        self
+    }
+
     /// Returns a reference to the instance of `Endpoint` owned by this runner.
     pub fn endpoint(&mut self) -> &mut E {
         &mut self.endpoint
@@ -151,7 +172,7 @@ where
             .prepare_request(request)
             .expect("failed to construct a request");
 
-        let future = AppService::new(&self.endpoint).dispatch(request);
+        let future = AppService::new(&self.endpoint, self.config.clone()).dispatch(request);
 
         f(future, &mut self.rt)
     }
@@ -175,6 +196,85 @@ where
             rt.block_on(future::poll_fn(|| future.poll_apply()))
         })
     }
+
+    /// Applies the given request to the inner endpoint and converts the result
+    /// into an HTTP response, as the actual server does.
+    ///
+    /// Unlike `apply`, the returned value contains the additional header fields
+    /// such as `Set-Cookie` and the whole of the response body.
+    pub fn perform(&mut self, request: impl TestRequest) -> io::Result<TestResponse>
+    where
+        E::Output: IntoResponse,
+        ResponseBody<ReqBody, E>: BufStream,
+        <ResponseBody<ReqBody, E> as BufStream>::Error:
+            Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+    {
+        self.apply_inner(request, |future, rt| {
+            let response = rt.block_on(ResponseFuture::new(future))?;
+            let (parts, mut body) = response.into_parts();
+
+            let mut data = Vec::new();
+            while let Some(chunk) = rt
+                .block_on(future::poll_fn(|| body.poll_buf()))
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
+            {
+                data.put(chunk);
+            }
+
+            Ok(TestResponse {
+                response: Response::from_parts(parts, Bytes::from(data)),
+            })
+        })
+    }
+}
+
+// ==== TestResponse ====
+
+/// An HTTP response returned from `TestRunner::perform`.
+///
+/// The message body has already been received and is stored as a `Bytes`.
+#[derive(Debug)]
+pub struct TestResponse {
+    response: Response<Bytes>,
+}
+
+impl TestResponse {
+    /// Returns the message body as an UTF-8 string.
+    pub fn text(&self) -> Result<&str, str::Utf8Error> {
+        str::from_utf8(self.response.body())
+    }
+
+    /// Deserializes the message body as a JSON value.
+    pub fn json<T>(&self) -> serde_json::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        serde_json::from_slice(self.response.body())
+    }
+
+    /// Parses the values of `Set-Cookie` contained in this response.
+    pub fn cookies(&self) -> failure::Fallible<Vec<Cookie<'static>>> {
+        let mut cookies = vec![];
+        for h in self.response.headers().get_all(header::SET_COOKIE) {
+            let cookie = Cookie::parse_encoded(h.to_str()?.to_owned())?;
+            cookies.push(cookie);
+        }
+        Ok(cookies)
+    }
+
+    /// Consumes itself and returns the inner `Response<Bytes>`.
+    pub fn into_inner(self) -> Response<Bytes> {
+        self.response
+    }
+}
+
+impl Deref for TestResponse {
+    type Target = Response<Bytes>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.response
+    }
 }
 
 mod imp {
@@ -331,7 +431,7 @@ mod imp {
 mod tests {
     use super::*;
 
-    use crate::endpoint;
+    use crate::endpoint::{self, EndpointExt};
     use matches::assert_matches;
 
     #[test]
@@ -429,4 +529,25 @@ mod tests {
 
         assert!(runner.apply_raw("/").is_ok());
     }
+
+    #[test]
+    fn test_perform() {
+        let mut runner = runner({
+            endpoint::unit().map(|| {
+                let mut response = Response::new("hello");
+                response
+                    .headers_mut()
+                    .insert(header::CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+                response
+            })
+        });
+
+        let response = runner.perform("/").unwrap();
+        assert_eq!(response.status().as_u16(), 200);
+        assert_matches!(
+            response.headers().get(header::CONTENT_TYPE),
+            Some(h) if h == "text/plain"
+        );
+        assert_eq!(response.text().ok(), Some("hello"));
+    }
 }
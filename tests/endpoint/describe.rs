@@ -0,0 +1,126 @@
+use finchers::endpoint::describe::{Description, Route};
+use finchers::endpoint::syntax::{self, path};
+use finchers::endpoint::IsEndpoint;
+use finchers::endpoints::{body, header, query};
+use finchers::prelude::*;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct Paging {
+    #[allow(dead_code)]
+    page: u32,
+}
+
+fn route_table(endpoint: &impl IsEndpoint) -> Vec<String> {
+    endpoint
+        .describe()
+        .routes()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn test_describe_path() {
+    let endpoint = path!(@get "/users/<id: u64>/files/<..std::path::PathBuf>");
+    assert_eq!(
+        endpoint.describe(),
+        Description::And(vec![
+            Description::Segment("users".into()),
+            Description::Param {
+                name: Some("id"),
+                ty: Some("u64"),
+            },
+            Description::Segment("files".into()),
+            Description::Remains {
+                name: None,
+                ty: Some("std::path::PathBuf"),
+            },
+            Description::Verbs(syntax::verb::Verbs::GET),
+        ])
+    );
+}
+
+#[test]
+fn test_describe_route_table() {
+    let endpoint = path!(@get "/posts")
+        .and(query::optional::<Paging>())
+        .map(|_: Option<Paging>| "list")
+        .or(path!(@get "/posts/<u32>").map(|_: u32| "show"))
+        .or(path!(@post "/posts")
+            .and(header::parse::<String>("authorization"))
+            .and(body::text())
+            .map(|_: String, _: String| "create"))
+        .or(syntax::segment("static")
+            .and(syntax::remains::<String>())
+            .map(|_: String| "static"));
+
+    assert_eq!(
+        route_table(&endpoint),
+        vec![
+            "GET /posts [query?]",
+            "GET /posts/<u32>",
+            "POST /posts [header:authorization, body:text]",
+            "* /static/<.._>",
+        ]
+    );
+}
+
+#[test]
+fn test_describe_boxed_and_opaque() {
+    let endpoint = path!(@get "/").boxed::<(), ()>();
+    assert_eq!(route_table(&endpoint), vec!["GET /"]);
+    assert_eq!(route_table(&endpoint::unit::<()>()), vec!["* /"]);
+}
+
+#[test]
+fn test_route_overlaps() {
+    let endpoint = path!(@get "/users/<u64>")
+        .or(path!(@get "/users/me"))
+        .or(path!(@post "/users/me"))
+        .or(path!(@get "/users"));
+    let routes: Vec<Route> = endpoint.describe().routes();
+
+    assert!(routes[0].overlaps(&routes[1]));
+    assert!(!routes[0].overlaps(&routes[2]));
+    assert!(!routes[0].overlaps(&routes[3]));
+    assert!(!routes[1].overlaps(&routes[3]));
+}
+
+#[test]
+fn test_describe_named_routes() {
+    let endpoint = path!(@get "/posts/<u32>")
+        .map(|_: u32| "show")
+        .named("show_post")
+        .or(path!(@get "/users/<String>/posts")
+            .map(|_: String| "list")
+            .named("user_posts"))
+        .or(path!(@get "/").map(|| "index").named("index"))
+        .or(path!(@get "/images/<String>/(png|jpg)")
+            .map(|_: String| "image")
+            .named("image"));
+    let description = endpoint.describe();
+
+    assert_eq!(
+        route_table(&endpoint),
+        vec![
+            "GET /posts/<u32> (show_post)",
+            "GET /users/<String>/posts (user_posts)",
+            "GET / (index)",
+            "GET /images/<String>/png (image)",
+            "GET /images/<String>/jpg (image)",
+        ]
+    );
+    assert_eq!(
+        description.url_for("show_post", &(42,)),
+        Some("/posts/42".into())
+    );
+    assert_eq!(
+        description.url_for("user_posts", &("John Doe".to_owned(),)),
+        Some("/users/John%20Doe/posts".into())
+    );
+    assert_eq!(description.url_for("index", &()), Some("/".into()));
+    assert_eq!(description.url_for("image", &("logo".to_owned(),)), None);
+    assert_eq!(description.url_for("show_post", &()), None);
+    assert_eq!(description.url_for("unknown", &()), None);
+}
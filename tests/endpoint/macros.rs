@@ -53,3 +53,213 @@ fn test_extract_path_catch_all_param() {
     assert_eq!(runner.apply("/foo/bar/baz").ok(), Some("bar/baz".into()));
     matches::assert_matches!(runner.apply("/"), Err(..));
 }
+
+#[test]
+fn test_extract_path_to_path() {
+    use finchers::endpoint::syntax::ExtractPath;
+    use std::path::PathBuf;
+
+    #[derive(ExtractPath)]
+    #[path = "/posts/<u64>/<String>"]
+    struct PostPath;
+
+    #[derive(ExtractPath)]
+    #[path = "/files/<..std::path::PathBuf>"]
+    struct FilePath;
+
+    #[derive(ExtractPath)]
+    #[path = "/"]
+    struct IndexPath;
+
+    assert_eq!(
+        PostPath::to_path(&(42, "a b/c%".into())),
+        "/posts/42/a%20b%2Fc%25"
+    );
+    assert_eq!(
+        FilePath::to_path(&(PathBuf::from("docs/read me.md"),)),
+        "/files/docs/read%20me.md"
+    );
+    assert_eq!(IndexPath::to_path(&()), "/");
+
+    let mut runner = finchers::test::runner(syntax::path::<PostPath>());
+    assert_eq!(
+        runner
+            .apply_raw(PostPath::to_path(&(42, "a b/c%".into())))
+            .ok(),
+        Some((42, "a b/c%".into()))
+    );
+}
+
+#[test]
+fn test_extract_path_encoded_static_segments() {
+    use finchers::endpoint::syntax::ExtractPath;
+
+    #[derive(ExtractPath)]
+    #[path = "/my docs/café/<u32>"]
+    struct DocPath;
+
+    assert_eq!(DocPath::to_path(&(1,)), "/my%20docs/caf%C3%A9/1");
+
+    let mut runner = finchers::test::runner(syntax::path::<DocPath>());
+    assert_eq!(runner.apply_raw(DocPath::to_path(&(1,))).ok(), Some((1,)));
+}
+
+#[test]
+fn test_extract_path_without_to_encoded_str() {
+    use finchers::endpoint::syntax::encoded::{EncodedStr, FromEncodedStr};
+    use finchers::error::Error;
+
+    // `to_path` is not required to be available for the parameter types.
+    #[derive(Debug, PartialEq)]
+    struct Slug(String);
+
+    impl FromEncodedStr for Slug {
+        type Error = Error;
+
+        fn from_encoded_str(s: &EncodedStr) -> Result<Self, Self::Error> {
+            Ok(Slug(s.percent_decode_lossy().into_owned()))
+        }
+    }
+
+    let mut runner = finchers::test::runner({ syntax::path!("/posts/<Slug>") });
+    assert_eq!(
+        runner.apply("/posts/hello").ok(),
+        Some(Slug("hello".into()))
+    );
+}
+
+#[test]
+fn test_extract_path_range_constraint() {
+    let mut runner = finchers::test::runner({ syntax::path!("/posts/<u32 | 1..=9999>") });
+
+    matches::assert_matches!(runner.apply("/posts/1"), Ok(1_u32));
+    matches::assert_matches!(runner.apply("/posts/9999"), Ok(9999_u32));
+    matches::assert_matches!(runner.apply("/posts/0"), Err(..));
+    matches::assert_matches!(runner.apply("/posts/10000"), Err(..));
+}
+
+#[test]
+fn test_extract_path_length_constraint() {
+    use finchers::prelude::*;
+
+    let mut runner = finchers::test::runner({
+        syntax::path!("/commits/<String # 7>")
+            .map(|id: String| format!("short:{}", id))
+            .or_strict(
+                syntax::path!("/commits/<String # 8..=40>")
+                    .map(|id: String| format!("long:{}", id)),
+            )
+    });
+
+    assert_eq!(
+        runner.apply("/commits/0a1b2c3").ok(),
+        Some("short:0a1b2c3".into())
+    );
+    assert_eq!(
+        runner.apply("/commits/0a1b2c3d").ok(),
+        Some("long:0a1b2c3d".into())
+    );
+    assert_eq!(
+        runner.apply("/commits/%C3%A9t%C3%A9s%20ok").ok(),
+        Some("short:étés ok".into())
+    );
+    matches::assert_matches!(runner.apply("/commits/0a1b2c"), Err(..));
+}
+
+#[cfg(feature = "path-regex")]
+#[test]
+fn test_extract_path_regex_constraint() {
+    let mut runner = finchers::test::runner({
+        syntax::path!(r#"/posts/<String ~ "^[a-z0-9-]+$">"#) //
+    });
+
+    assert_eq!(
+        runner.apply("/posts/hello-world").ok(),
+        Some("hello-world".into())
+    );
+    matches::assert_matches!(runner.apply("/posts/Hello%20World"), Err(..));
+}
+
+#[test]
+fn test_extract_path_alternation() {
+    let mut runner = finchers::test::runner({ syntax::path!("/images/<String>/(png|jpg)") });
+
+    assert_eq!(runner.apply("/images/logo/png").ok(), Some("logo".into()));
+    assert_eq!(runner.apply("/images/logo/jpg").ok(), Some("logo".into()));
+    matches::assert_matches!(runner.apply("/images/logo/gif"), Err(..));
+}
+
+#[cfg(feature = "path-regex")]
+#[test]
+fn test_extract_path_constraint_falls_through() {
+    use finchers::prelude::*;
+
+    let mut runner = finchers::test::runner({
+        syntax::path!("/posts/<u32 | ..100>")
+            .map(|id: u32| format!("small:{}", id))
+            .or_strict(
+                syntax::path!(r#"/posts/<String ~ "^\d+$">"#)
+                    .map(|id: String| format!("large:{}", id)),
+            )
+            .or_strict(
+                syntax::path!("/posts/<String>").map(|slug: String| format!("slug:{}", slug)),
+            )
+    });
+
+    assert_eq!(runner.apply("/posts/42").ok(), Some("small:42".into()));
+    assert_eq!(runner.apply("/posts/420").ok(), Some("large:420".into()));
+    assert_eq!(runner.apply("/posts/hello").ok(), Some("slug:hello".into()));
+}
+
+#[test]
+fn test_extract_path_named_fields() {
+    use finchers::endpoint::describe::Description;
+    use finchers::endpoint::syntax::ExtractPath;
+
+    #[derive(Debug, PartialEq, ExtractPath)]
+    #[path = "/users/<user_id>/posts/<post_id | 1..>/<..rest>"]
+    struct PostPath {
+        post_id: u32,
+        user_id: u64,
+        rest: String,
+    }
+
+    let mut runner = finchers::test::runner(syntax::path::<PostPath>());
+    assert_eq!(
+        runner.apply("/users/1/posts/42/comments/3").ok(),
+        Some(PostPath {
+            user_id: 1,
+            post_id: 42,
+            rest: "comments/3".into(),
+        })
+    );
+    matches::assert_matches!(runner.apply("/users/1/posts/0/"), Err(..));
+
+    let path = PostPath {
+        user_id: 1,
+        post_id: 42,
+        rest: "a b/c".into(),
+    };
+    assert_eq!(path.to_path(), "/users/1/posts/42/a%20b%2Fc");
+    assert_eq!(runner.apply_raw(path.to_path()).ok(), Some(path));
+
+    assert_eq!(
+        PostPath::describe(),
+        Description::And(vec![
+            Description::Segment("users".into()),
+            Description::Param {
+                name: Some("user_id"),
+                ty: Some("u64"),
+            },
+            Description::Segment("posts".into()),
+            Description::Param {
+                name: Some("post_id"),
+                ty: Some("u32"),
+            },
+            Description::Remains {
+                name: Some("rest"),
+                ty: Some("String"),
+            },
+        ])
+    );
+}
@@ -1,9 +1,11 @@
 mod and;
 mod and_then;
 mod boxed;
+mod describe;
 mod macros;
 mod map;
 mod or;
 mod or_strict;
 mod recover;
 mod syntax;
+mod wrap;
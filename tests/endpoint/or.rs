@@ -1,6 +1,7 @@
 use finchers::endpoint::syntax;
 use finchers::prelude::*;
 use finchers::test;
+use http::{Request, StatusCode};
 use matches::assert_matches;
 
 #[test]
@@ -30,3 +31,47 @@ fn test_or_choose_longer_segments() {
     assert_matches!(runner.apply("/foo"), Ok(..));
     assert_matches!(runner.apply("/foo/bar"), Ok(..));
 }
+
+#[test]
+fn test_or_method_not_allowed() {
+    let mut runner = test::runner({
+        let e1 = syntax::path!(@get "/foo").and(endpoint::value("get"));
+        let e2 = syntax::path!(@post "/foo").and(endpoint::value("post"));
+        let e3 = syntax::path!(@get "/bar").and(endpoint::value("bar"));
+        e1.or(e2).or(e3)
+    });
+
+    let response = runner.perform(Request::delete("/foo")).unwrap();
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_matches!(
+        response.headers().get("allow"),
+        Some(h) if h == "GET, POST"
+    );
+
+    let response = runner.perform(Request::post("/bar")).unwrap();
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_matches!(
+        response.headers().get("allow"),
+        Some(h) if h == "GET"
+    );
+
+    let response = runner.perform("/baz").unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert!(!response.headers().contains_key("allow"));
+}
+
+#[test]
+fn test_or_strict_method_not_allowed() {
+    let mut runner = test::runner({
+        let e1 = syntax::path!(@get "/foo").and(endpoint::value("get"));
+        let e2 = syntax::path!(@put "/foo").and(endpoint::value("put"));
+        e1.or_strict(e2)
+    });
+
+    let response = runner.perform(Request::post("/foo")).unwrap();
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_matches!(
+        response.headers().get("allow"),
+        Some(h) if h == "GET, PUT"
+    );
+}
@@ -30,6 +30,19 @@ fn test_match_encoded_path() {
     assert_matches!(runner.apply_raw("/foo/bar"), Err(..));
 }
 
+#[test]
+fn test_encoded_str_new() {
+    use finchers::endpoint::syntax::encoded::EncodedStr;
+
+    assert_matches!(
+        EncodedStr::new("/foo%2Fbar"),
+        Some(s) if s.percent_decode_lossy() == "/foo/bar"
+    );
+    assert!(EncodedStr::new("/foo%2").is_none());
+    assert!(EncodedStr::new("/foo%zz").is_none());
+    assert!(EncodedStr::new("/foo bar").is_none());
+}
+
 #[test]
 fn test_extract_integer() {
     let mut runner = test::runner(syntax::param::<i32>());
@@ -0,0 +1,39 @@
+use finchers::endpoint::wrapper::{wrapper_fn, Wrapper};
+use finchers::endpoint::{syntax, IsEndpoint};
+use finchers::prelude::*;
+use finchers::test;
+use matches::assert_matches;
+
+#[test]
+fn test_wrap_fn() {
+    let mut runner = test::runner({
+        syntax::segment("foo") //
+            .wrap(wrapper_fn(|e: syntax::MatchSegment| {
+                e.and(endpoint::value("foo"))
+            }))
+    });
+
+    assert_matches!(runner.apply("/foo"), Ok("foo"));
+    assert_matches!(runner.apply("/bar"), Err(..));
+}
+
+#[test]
+fn test_wrap_custom() {
+    struct Prefixed(&'static str);
+
+    impl<E: IsEndpoint> Wrapper<E> for Prefixed {
+        type Endpoint = endpoint::ext::And<syntax::MatchSegment, E>;
+
+        fn wrap(self, endpoint: E) -> Self::Endpoint {
+            syntax::segment(self.0).and(endpoint)
+        }
+    }
+
+    let mut runner = test::runner({
+        endpoint::value(42) //
+            .wrap(Prefixed("api"))
+    });
+
+    assert_matches!(runner.apply("/api"), Ok(42));
+    assert_matches!(runner.apply("/"), Err(..));
+}
@@ -107,3 +107,260 @@ fn test_body_urlencoded() {
         Err(..)
     );
 }
+
+const MULTIPART_BODY: &str = "\
+--boundary\r\n\
+Content-Disposition: form-data; name=\"title\"\r\n\
+\r\n\
+Hello, world\r\n\
+--boundary\r\n\
+Content-Disposition: form-data; name=\"attachment\"; filename=\"hello.txt\"\r\n\
+Content-Type: text/plain\r\n\
+\r\n\
+The quick brown fox\r\njumps over the lazy dog\r\n\
+--boundary--\r\n";
+
+fn multipart_request(body: &'static str) -> http::Result<Request<&'static str>> {
+    Request::post("/")
+        .header("content-type", "multipart/form-data; boundary=boundary")
+        .body(body)
+}
+
+#[test]
+fn test_body_multipart() {
+    use futures::{future, Stream};
+    use izanami_util::buf_stream::BufStream;
+
+    let mut runner = test::runner(body::multipart());
+
+    let parts = runner.apply(multipart_request(MULTIPART_BODY)).unwrap();
+    let parts: Vec<_> = parts
+        .and_then(|part| {
+            let name = part.name().map(ToOwned::to_owned);
+            let filename = part.filename().map(ToOwned::to_owned);
+            let mut body = part.into_body();
+            let mut data = vec![];
+            future::poll_fn(move || {
+                while let Some(chunk) = futures::try_ready!(body.poll_buf()) {
+                    data.extend_from_slice(chunk.get_ref());
+                }
+                Ok((
+                    name.clone(),
+                    filename.clone(),
+                    std::mem::replace(&mut data, vec![]),
+                )
+                    .into())
+            })
+        })
+        .collect()
+        .wait()
+        .unwrap();
+
+    assert_eq!(parts.len(), 2);
+    assert_eq!(parts[0].0, Some("title".into()));
+    assert_eq!(parts[0].1, None);
+    assert_eq!(parts[0].2, b"Hello, world");
+    assert_eq!(parts[1].0, Some("attachment".into()));
+    assert_eq!(parts[1].1, Some("hello.txt".into()));
+    assert_eq!(
+        parts[1].2,
+        &b"The quick brown fox\r\njumps over the lazy dog"[..]
+    );
+
+    // missing boundary
+    assert_matches!(
+        runner.apply(
+            Request::post("/")
+                .header("content-type", "multipart/form-data")
+                .body(MULTIPART_BODY)
+        ),
+        Err(..)
+    );
+}
+
+#[test]
+fn test_body_multipart_form() {
+    use finchers::endpoints::body::UploadedFile;
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Upload {
+        title: String,
+        attachment: UploadedFile,
+    }
+
+    let mut runner = test::runner(body::multipart_form::<Upload>());
+
+    let upload = runner.apply(multipart_request(MULTIPART_BODY)).unwrap();
+    assert_eq!(upload.title, "Hello, world");
+    assert_eq!(upload.attachment.filename(), "hello.txt");
+    assert_eq!(upload.attachment.content_type(), Some(&mime::TEXT_PLAIN));
+    assert_eq!(upload.attachment.size(), 44);
+    assert_eq!(
+        std::fs::read_to_string(upload.attachment.path()).unwrap(),
+        "The quick brown fox\r\njumps over the lazy dog"
+    );
+
+    let path = upload.attachment.path().to_owned();
+    drop(upload);
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_body_multipart_form_limits() {
+    use finchers::endpoints::body::UploadedFile;
+    use http::StatusCode;
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Upload {
+        title: String,
+        attachment: UploadedFile,
+    }
+
+    let mut runner = test::runner(body::multipart_form::<Upload>().max_file_size(16));
+    assert_matches!(
+        runner.apply(multipart_request(MULTIPART_BODY)),
+        Err(ref e) if e.status_code() == StatusCode::PAYLOAD_TOO_LARGE
+    );
+
+    let mut runner = test::runner(body::multipart_form::<Upload>().max_field_size(4));
+    assert_matches!(
+        runner.apply(multipart_request(MULTIPART_BODY)),
+        Err(ref e) if e.status_code() == StatusCode::PAYLOAD_TOO_LARGE
+    );
+
+    let mut runner = test::runner(body::multipart_form::<Upload>().max_fields(0));
+    assert_matches!(
+        runner.apply(multipart_request(MULTIPART_BODY)),
+        Err(ref e) if e.status_code() == StatusCode::PAYLOAD_TOO_LARGE
+    );
+
+    let mut runner = test::runner(body::multipart_form::<Upload>().max_total_field_size(4));
+    assert_matches!(
+        runner.apply(multipart_request(MULTIPART_BODY)),
+        Err(ref e) if e.status_code() == StatusCode::PAYLOAD_TOO_LARGE
+    );
+}
+
+#[test]
+fn test_body_multipart_form_forged_file() {
+    use finchers::endpoints::body::UploadedFile;
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Upload {
+        attachment: UploadedFile,
+    }
+
+    let mut runner = test::runner(body::multipart_form::<Upload>());
+
+    // The text fields cannot be deserialized as an `UploadedFile`.
+    assert_matches!(
+        runner.apply(multipart_request(
+            "--boundary\r\n\
+             Content-Disposition: form-data; name=\"attachment[path]\"\r\n\
+             \r\n\
+             /etc/passwd\r\n\
+             --boundary\r\n\
+             Content-Disposition: form-data; name=\"attachment[filename]\"\r\n\
+             \r\n\
+             passwd\r\n\
+             --boundary\r\n\
+             Content-Disposition: form-data; name=\"attachment[size]\"\r\n\
+             \r\n\
+             0\r\n\
+             --boundary--\r\n"
+        )),
+        Err(..)
+    );
+}
+
+#[test]
+fn test_body_limit() {
+    use http::StatusCode;
+
+    let mut runner = test::runner(body::text().limit(4));
+
+    assert_matches!(
+        runner.apply(Request::post("/").body("text")),
+        Ok(ref s) if s == "text"
+    );
+
+    // exceeds the limit while receiving
+    assert_matches!(
+        runner.apply(Request::post("/").body("The quick brown fox")),
+        Err(ref e) if e.status_code() == StatusCode::PAYLOAD_TOO_LARGE
+    );
+
+    // the declared size exceeds the limit
+    assert_matches!(
+        runner.apply(
+            Request::post("/")
+                .header("content-length", "1024")
+                .body("text")
+        ),
+        Err(ref e) if e.status_code() == StatusCode::PAYLOAD_TOO_LARGE
+    );
+}
+
+#[test]
+fn test_body_limit_default() {
+    use http::StatusCode;
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Param {
+        text: String,
+    }
+
+    let mut runner = test::runner(body::json::<Param>());
+    runner.body_limit(8);
+
+    assert_matches!(
+        runner.apply(
+            Request::post("/")
+                .header("content-type", "application/json")
+                .body(r#"{ "text": "TRPL2" }"#)
+        ),
+        Err(ref e) if e.status_code() == StatusCode::PAYLOAD_TOO_LARGE
+    );
+
+    // the limit specified on the endpoint takes precedence
+    let mut runner = test::runner(body::json::<Param>().limit(1024));
+    runner.body_limit(8);
+
+    assert_matches!(
+        runner.apply(
+            Request::post("/")
+                .header("content-type", "application/json")
+                .body(r#"{ "text": "TRPL2" }"#)
+        ),
+        Ok(ref param) if param.text == "TRPL2"
+    );
+}
+
+#[test]
+fn test_body_limit_multipart_form() {
+    use finchers::endpoints::body::UploadedFile;
+    use http::StatusCode;
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Upload {
+        title: String,
+        attachment: UploadedFile,
+    }
+
+    let mut runner = test::runner(body::multipart_form::<Upload>());
+    runner.body_limit(64);
+
+    assert_matches!(
+        runner.apply(multipart_request(MULTIPART_BODY)),
+        Err(ref e) if e.status_code() == StatusCode::PAYLOAD_TOO_LARGE
+    );
+
+    // the limit specified on the endpoint takes precedence
+    let mut runner = test::runner(body::multipart_form::<Upload>().limit(4096));
+    runner.body_limit(64);
+
+    assert_matches!(
+        runner.apply(multipart_request(MULTIPART_BODY)),
+        Ok(ref upload) if upload.title == "Hello, world"
+    );
+}
@@ -1,13 +1,23 @@
 use cookie::Cookie;
-use finchers::input::Cookies;
+use finchers::error::Error;
 use finchers::prelude::*;
+use finchers::service::Context;
 use finchers::test;
+use futures::future;
 use http::Request;
+use matches::assert_matches;
 
 #[test]
 fn test_cookies_get() {
     let mut runner = test::runner({
-        endpoints::cookie::cookies().map(|cookies: Cookies| cookies.get("session-id"))
+        endpoint::endpoint(|| {
+            future::lazy(|| {
+                Context::with(|cx| {
+                    let cookie = cx.cookies()?.get("session-id").cloned();
+                    Ok::<_, Error>((cookie,))
+                })
+            })
+        })
     });
 
     assert_matches!(
@@ -22,45 +32,43 @@ fn test_cookies_get() {
 #[test]
 fn test_cookies_add() {
     let mut runner = test::runner({
-        endpoints::cookie::cookies().map(|mut cookies: Cookies| {
-            cookies.add(Cookie::new("session-id", "xxxx"));
+        endpoint::endpoint(|| {
+            future::lazy(|| {
+                Context::with(|cx| {
+                    cx.cookies()?.add(Cookie::new("session-id", "xxxx"));
+                    Ok::<_, Error>(())
+                })
+            })
         })
     });
 
     let response = runner.perform("/").unwrap();
+    let cookies = response.cookies().expect("failed to parse Set-Cookie");
 
-    let h_str = response
-        .headers()
-        .get("set-cookie")
-        .expect("the header set-cookie is missing")
-        .to_str()
-        .unwrap();
-    let cookie = Cookie::parse_encoded(h_str).expect("failed to parse Set-Cookie");
-
-    assert_eq!(cookie.name(), "session-id");
-    assert_eq!(cookie.value(), "xxxx");
+    assert_eq!(cookies.len(), 1);
+    assert_eq!(cookies[0].name(), "session-id");
+    assert_eq!(cookies[0].value(), "xxxx");
 }
 
 #[test]
 fn test_cookies_remove() {
     let mut runner = test::runner({
-        endpoints::cookie::cookies().map(|mut cookies: Cookies| {
-            cookies.remove(Cookie::named("session-id"));
+        endpoint::endpoint(|| {
+            future::lazy(|| {
+                Context::with(|cx| {
+                    cx.cookies()?.remove(Cookie::named("session-id"));
+                    Ok::<_, Error>(())
+                })
+            })
         })
     });
 
     let response = runner
         .perform(Request::get("/").header("cookie", "session-id=xxxx"))
         .unwrap();
+    let cookies = response.cookies().expect("failed to parse Set-Cookie");
 
-    let h_str = response
-        .headers()
-        .get("set-cookie")
-        .expect("the header set-cookie is missing")
-        .to_str()
-        .unwrap();
-    let cookie = Cookie::parse_encoded(h_str).expect("failed to parse Set-Cookie");
-
-    assert_eq!(cookie.name(), "session-id");
-    assert_eq!(cookie.value(), "");
+    assert_eq!(cookies.len(), 1);
+    assert_eq!(cookies[0].name(), "session-id");
+    assert_eq!(cookies[0].value(), "");
 }
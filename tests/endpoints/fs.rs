@@ -0,0 +1,772 @@
+use finchers::endpoints::fs;
+use finchers::output::fs::CachePolicy;
+use finchers::output::NamedFile;
+use finchers::prelude::*;
+use finchers::test;
+use http::Request;
+use matches::assert_matches;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const CONTENT: &str = "0123456789abcdefghijklmnopqrstuvwxyz";
+
+fn create_file(name: &str) -> PathBuf {
+    let path =
+        std::env::temp_dir().join(format!("finchers-test-{}-{}.txt", std::process::id(), name));
+    std::fs::write(&path, CONTENT).unwrap();
+    path
+}
+
+#[test]
+fn test_file() {
+    let path = create_file("full");
+    let mut runner = test::runner(fs::file(&path));
+
+    let response = runner.perform("/").unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_matches!(
+        response.headers().get("accept-ranges"),
+        Some(h) if h == "bytes"
+    );
+    assert_matches!(
+        response.headers().get("content-length"),
+        Some(h) if h == "36"
+    );
+    assert_matches!(response.text(), Ok(CONTENT));
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_file_single_range() {
+    let path = create_file("single-range");
+    let mut runner = test::runner(fs::file(&path));
+
+    let response = runner
+        .perform(Request::get("/").header("range", "bytes=10-15"))
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 206);
+    assert_matches!(
+        response.headers().get("content-range"),
+        Some(h) if h == "bytes 10-15/36"
+    );
+    assert_matches!(
+        response.headers().get("content-length"),
+        Some(h) if h == "6"
+    );
+    assert_matches!(response.text(), Ok("abcdef"));
+
+    let response = runner
+        .perform(Request::get("/").header("range", "bytes=-3"))
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 206);
+    assert_matches!(response.text(), Ok("xyz"));
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_file_multiple_ranges() {
+    let path = create_file("multiple-ranges");
+    let mut runner = test::runner(fs::file(&path));
+
+    let response = runner
+        .perform(Request::get("/").header("range", "bytes=0-1, 34-"))
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 206);
+
+    let content_type = response.headers()["content-type"].to_str().unwrap();
+    assert!(content_type.starts_with("multipart/byteranges; boundary="));
+    let boundary = &content_type["multipart/byteranges; boundary=".len()..];
+
+    let expected = format!(
+        "--{0}\r\nContent-Type: text/plain\r\nContent-Range: bytes 0-1/36\r\n\r\n01\
+         \r\n--{0}\r\nContent-Type: text/plain\r\nContent-Range: bytes 34-35/36\r\n\r\nyz\
+         \r\n--{0}--\r\n",
+        boundary
+    );
+    assert_matches!(response.text(), Ok(s) if s == expected);
+    assert_matches!(
+        response.headers().get("content-length"),
+        Some(h) if h.to_str().unwrap() == expected.len().to_string()
+    );
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_file_unsatisfiable_range() {
+    let path = create_file("unsatisfiable-range");
+    let mut runner = test::runner(fs::file(&path));
+
+    let response = runner
+        .perform(Request::get("/").header("range", "bytes=100-"))
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 416);
+    assert_matches!(
+        response.headers().get("content-range"),
+        Some(h) if h == "bytes */36"
+    );
+    assert!(response.body().is_empty());
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_file_if_range_outdated() {
+    let path = create_file("if-range");
+    let mut runner = test::runner(fs::file(&path));
+
+    let response = runner
+        .perform(
+            Request::get("/")
+                .header("range", "bytes=0-1")
+                .header("if-range", "\"outdated\""),
+        )
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_matches!(response.text(), Ok(CONTENT));
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_file_if_range_matched() {
+    let path = create_file("if-range-matched");
+    let mut runner = test::runner(fs::file(&path));
+
+    let etag = runner.perform("/").unwrap().headers()["etag"].clone();
+    let response = runner
+        .perform(
+            Request::get("/")
+                .header("range", "bytes=0-1")
+                .header("if-range", etag),
+        )
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 206);
+    assert_matches!(response.text(), Ok("01"));
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_file_validators() {
+    let path = create_file("validators");
+    let mut runner = test::runner(fs::file(&path));
+
+    let response = runner.perform("/").unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    let etag = response.headers()["etag"].to_str().unwrap();
+    assert!(etag.starts_with('"') && etag.ends_with('"'));
+    assert!(response.headers().contains_key("last-modified"));
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_file_if_none_match() {
+    let path = create_file("if-none-match");
+    let mut runner = test::runner(fs::file(&path));
+
+    let etag = runner.perform("/").unwrap().headers()["etag"].clone();
+
+    let response = runner
+        .perform(Request::get("/").header("if-none-match", etag.clone()))
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 304);
+    assert_matches!(response.headers().get("etag"), Some(h) if *h == etag);
+    assert!(!response.headers().contains_key("content-length"));
+    assert!(response.body().is_empty());
+
+    let weak = format!("\"other\", W/{}", etag.to_str().unwrap());
+    let response = runner
+        .perform(Request::get("/").header("if-none-match", &*weak))
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 304);
+
+    let response = runner
+        .perform(Request::get("/").header("if-none-match", "\"other\""))
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_matches!(response.text(), Ok(CONTENT));
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_file_if_modified_since() {
+    let path = create_file("if-modified-since");
+    let mut runner = test::runner(fs::file(&path));
+
+    let last_modified = runner.perform("/").unwrap().headers()["last-modified"].clone();
+
+    let response = runner
+        .perform(Request::get("/").header("if-modified-since", last_modified.clone()))
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 304);
+
+    let response = runner
+        .perform(Request::get("/").header("if-modified-since", "Thu, 01 Jan 1970 00:00:00 GMT"))
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    // If-None-Match takes precedence over If-Modified-Since.
+    let response = runner
+        .perform(
+            Request::get("/")
+                .header("if-none-match", "\"other\"")
+                .header("if-modified-since", last_modified),
+        )
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+fn create_dir(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("finchers-test-{}-{}", std::process::id(), name));
+    std::fs::create_dir_all(root.join("public/sub")).unwrap();
+    std::fs::write(root.join("public/index.html"), "index").unwrap();
+    std::fs::write(root.join("public/sub/a.txt"), "a").unwrap();
+    std::fs::write(root.join("public/.secret"), "secret").unwrap();
+    std::fs::write(root.join("private.txt"), "private").unwrap();
+    root
+}
+
+#[test]
+fn test_dir() {
+    let root = create_dir("dir");
+    let mut runner = test::runner(fs::dir(root.join("public")));
+
+    let response = runner.perform("/").unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_matches!(response.text(), Ok("index"));
+
+    let response = runner.perform("/sub/a.txt").unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_matches!(response.text(), Ok("a"));
+
+    let response = runner.perform("/sub/./a.txt").unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = runner.perform("/missing.txt").unwrap();
+    assert_eq!(response.status().as_u16(), 404);
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_dir_traversal() {
+    let root = create_dir("dir-traversal");
+    let mut runner = test::runner(fs::dir(root.join("public")));
+
+    for path in &[
+        "/../private.txt",
+        "/sub/../../private.txt",
+        "/%2e%2e/private.txt",
+        "/sub%2f..%2f..%2fprivate.txt",
+    ] {
+        let response = runner.perform(*path).unwrap();
+        assert_eq!(response.status().as_u16(), 403, "path = {}", path);
+    }
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_dir_hidden_files() {
+    let root = create_dir("dir-hidden-files");
+
+    let mut runner = test::runner(fs::dir(root.join("public")));
+    let response = runner.perform("/.secret").unwrap();
+    assert_eq!(response.status().as_u16(), 404);
+
+    let mut runner = test::runner(fs::dir(root.join("public")).hidden_files(true));
+    let response = runner.perform("/.secret").unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_matches!(response.text(), Ok("secret"));
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_dir_restrict_symlinks() {
+    let root = create_dir("dir-restrict-symlinks");
+    std::os::unix::fs::symlink(root.join("private.txt"), root.join("public/link.txt")).unwrap();
+
+    let mut runner = test::runner(fs::dir(root.join("public")));
+    let response = runner.perform("/link.txt").unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    let mut runner = test::runner(fs::dir(root.join("public")).restrict_symlinks(true));
+    let response = runner.perform("/link.txt").unwrap();
+    assert_eq!(response.status().as_u16(), 403);
+    let response = runner.perform("/sub/a.txt").unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    // dangling symbolic links
+    std::os::unix::fs::symlink(root.join("missing.txt"), root.join("public/dangling.txt")).unwrap();
+    let response = runner.perform("/dangling.txt").unwrap();
+    assert_eq!(response.status().as_u16(), 404);
+
+    // the SPA fallback is also checked.
+    let mut runner = test::runner(
+        fs::dir(root.join("public"))
+            .spa_fallback("link.txt")
+            .restrict_symlinks(true),
+    );
+    let response = runner
+        .perform(Request::get("/posts/1").header("accept", "text/html"))
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 403);
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_dir_listing() {
+    let root = create_dir("dir-listing");
+    std::fs::write(root.join("public/sub/b c.txt"), "bc").unwrap();
+    std::fs::create_dir_all(root.join("public/sub/nested")).unwrap();
+
+    let mut runner = test::runner(fs::dir(root.join("public")));
+    let response = runner.perform("/sub/").unwrap();
+    assert_eq!(response.status().as_u16(), 404);
+
+    let mut runner = test::runner(fs::dir(root.join("public")).listing(false));
+    let response = runner.perform("/sub/").unwrap();
+    assert_eq!(response.status().as_u16(), 404);
+
+    let mut runner = test::runner(fs::dir(root.join("public")).listing(true));
+
+    // the directory with index.html
+    let response = runner.perform("/").unwrap();
+    assert_matches!(response.text(), Ok("index"));
+
+    let response = runner.perform("/sub/").unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_matches!(
+        response.headers().get("content-type"),
+        Some(h) if h == "text/html; charset=utf-8"
+    );
+    let html = response.text().unwrap();
+    assert!(html.contains("<a href=\"/sub/nested/\">nested/</a>"));
+    assert!(html.contains("<a href=\"/sub/a.txt\">a.txt</a>"));
+    assert!(html.contains("<a href=\"/sub/b%20c.txt\">b c.txt</a>"));
+    assert!(html.contains("<a href=\"/\">../</a>"));
+
+    let response = runner
+        .perform(Request::get("/sub?sort=size&order=desc").header("accept", "application/json"))
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_matches!(
+        response.headers().get("content-type"),
+        Some(h) if h == "application/json"
+    );
+    let listing: serde_json::Value = response.json().unwrap();
+    assert_eq!(listing["path"], "/sub/");
+    let entries: Vec<_> = listing["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| {
+            (
+                e["name"].as_str().unwrap().to_owned(),
+                e["type"].as_str().unwrap().to_owned(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        entries,
+        vec![
+            ("nested".to_owned(), "directory".to_owned()),
+            ("b c.txt".to_owned(), "file".to_owned()),
+            ("a.txt".to_owned(), "file".to_owned()),
+        ]
+    );
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_dir_listing_hidden_files() {
+    let root = create_dir("dir-listing-hidden-files");
+    std::fs::remove_file(root.join("public/index.html")).unwrap();
+
+    let mut runner = test::runner(fs::dir(root.join("public")).listing(true));
+    let response = runner.perform("/").unwrap();
+    let html = response.text().unwrap();
+    assert!(html.contains("sub/"));
+    assert!(!html.contains(".secret"));
+    assert!(!html.contains("../"));
+
+    // the options can be set in any order.
+    let mut runner = test::runner(
+        fs::dir(root.join("public"))
+            .listing(true)
+            .hidden_files(true),
+    );
+    let response = runner.perform("/").unwrap();
+    let html = response.text().unwrap();
+    assert!(html.contains(".secret"));
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_dir_precompressed() {
+    let root = create_dir("dir-precompressed");
+    std::fs::write(root.join("public/app.js"), "original").unwrap();
+    std::fs::write(root.join("public/app.js.gz"), "gzipped").unwrap();
+    std::fs::write(root.join("public/app.js.br"), "brotli").unwrap();
+    std::fs::write(root.join("public/sub/a.txt.gz"), "gzipped").unwrap();
+
+    let mut runner = test::runner(fs::dir(root.join("public")).precompressed(true));
+
+    let response = runner
+        .perform(Request::get("/app.js").header("accept-encoding", "gzip, br"))
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_matches!(response.text(), Ok("brotli"));
+    assert_matches!(
+        response.headers().get("content-encoding"),
+        Some(h) if h == "br"
+    );
+    assert_matches!(
+        response.headers().get("content-type"),
+        Some(h) if h == "application/javascript"
+    );
+    assert_matches!(
+        response.headers().get("vary"),
+        Some(h) if h == "accept-encoding"
+    );
+
+    let response = runner
+        .perform(Request::get("/app.js").header("accept-encoding", "gzip, br;q=0.5"))
+        .unwrap();
+    assert_matches!(response.text(), Ok("gzipped"));
+    assert_matches!(
+        response.headers().get("content-encoding"),
+        Some(h) if h == "gzip"
+    );
+
+    let response = runner.perform("/app.js").unwrap();
+    assert_matches!(response.text(), Ok("original"));
+    assert!(!response.headers().contains_key("content-encoding"));
+    assert_matches!(
+        response.headers().get("vary"),
+        Some(h) if h == "accept-encoding"
+    );
+
+    let response = runner
+        .perform(Request::get("/sub/a.txt").header("accept-encoding", "br"))
+        .unwrap();
+    assert_matches!(response.text(), Ok("a"));
+    assert!(!response.headers().contains_key("content-encoding"));
+
+    // disabled by default
+    let mut runner = test::runner(fs::dir(root.join("public")));
+    let response = runner
+        .perform(Request::get("/app.js").header("accept-encoding", "gzip, br"))
+        .unwrap();
+    assert_matches!(response.text(), Ok("original"));
+    assert!(!response.headers().contains_key("vary"));
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_file_precompressed() {
+    let path = create_file("file-precompressed");
+    let mut encoded_path = path.clone().into_os_string();
+    encoded_path.push(".gz");
+    std::fs::write(&encoded_path, "gzipped").unwrap();
+
+    let mut runner = test::runner(fs::file(&path).precompressed(true));
+    let response = runner
+        .perform(Request::get("/").header("accept-encoding", "gzip"))
+        .unwrap();
+    assert_matches!(response.text(), Ok("gzipped"));
+    assert_matches!(
+        response.headers().get("content-encoding"),
+        Some(h) if h == "gzip"
+    );
+    assert_matches!(
+        response.headers().get("content-type"),
+        Some(h) if h == "text/plain"
+    );
+
+    std::fs::remove_file(path).unwrap();
+    std::fs::remove_file(encoded_path).unwrap();
+}
+
+#[test]
+fn test_static_dir() {
+    let mut runner = test::runner(fs::static_dir!("tests/assets/static"));
+
+    let response = runner.perform("/").unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_matches!(response.text(), Ok("<h1>index</h1>\n"));
+    assert_matches!(
+        response.headers().get("content-type"),
+        Some(h) if h == "text/html"
+    );
+
+    // the entity tag is derived from the content, so it is stable across builds.
+    let mut crc = flate2::Crc::new();
+    crc.update(b"<h1>index</h1>\n");
+    let etag = format!("\"{:08x}-f\"", crc.sum());
+    assert_matches!(
+        response.headers().get("etag"),
+        Some(h) if *h == *etag
+    );
+
+    let response = runner.perform("/sub").unwrap();
+    assert_matches!(response.text(), Ok("nested\n"));
+
+    let response = runner.perform("/style.css").unwrap();
+    assert_matches!(
+        response.headers().get("content-type"),
+        Some(h) if h == "text/css"
+    );
+    assert_eq!(response.body().len(), 1280);
+
+    let response = runner.perform("/.hidden").unwrap();
+    assert_eq!(response.status().as_u16(), 404);
+
+    let response = runner.perform("/missing.txt").unwrap();
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[test]
+fn test_static_dir_conditional_and_gzip() {
+    let mut runner = test::runner(fs::static_dir!("tests/assets/static"));
+
+    let response = runner
+        .perform(Request::get("/style.css").header("accept-encoding", "gzip"))
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_matches!(
+        response.headers().get("content-encoding"),
+        Some(h) if h == "gzip"
+    );
+    assert_matches!(
+        response.headers().get("vary"),
+        Some(h) if h == "accept-encoding"
+    );
+    assert!(response.body().len() < 1280);
+    let etag = response.headers()["etag"].clone();
+
+    let response = runner
+        .perform(
+            Request::get("/style.css")
+                .header("accept-encoding", "gzip")
+                .header("if-none-match", etag.clone()),
+        )
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 304);
+    assert!(response.body().is_empty());
+
+    // the validator of the identity representation differs from the gzipped one.
+    let response = runner
+        .perform(Request::get("/style.css").header("if-none-match", etag))
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.body().len(), 1280);
+}
+
+#[test]
+fn test_static_dir_range_and_head() {
+    let mut runner = test::runner(fs::static_dir!("tests/assets/static"));
+
+    let response = runner
+        .perform(Request::get("/style.css").header("range", "bytes=0-5"))
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 206);
+    assert_matches!(
+        response.headers().get("content-range"),
+        Some(h) if h == "bytes 0-5/1280"
+    );
+    assert_matches!(
+        response.headers().get("content-length"),
+        Some(h) if h == "6"
+    );
+    assert_matches!(response.text(), Ok("body {"));
+    let etag = response.headers()["etag"].clone();
+
+    let response = runner
+        .perform(Request::get("/style.css").header("range", "bytes=2000-"))
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 416);
+    assert_matches!(
+        response.headers().get("content-range"),
+        Some(h) if h == "bytes */1280"
+    );
+
+    // the range is ignored if the validator in If-Range does not match.
+    let response = runner
+        .perform(
+            Request::get("/style.css")
+                .header("range", "bytes=0-5")
+                .header("if-range", "\"outdated\""),
+        )
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.body().len(), 1280);
+
+    let response = runner
+        .perform(
+            Request::get("/style.css")
+                .header("range", "bytes=0-5")
+                .header("if-range", etag),
+        )
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 206);
+
+    let response = runner.perform(Request::head("/style.css")).unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_matches!(
+        response.headers().get("content-length"),
+        Some(h) if h == "1280"
+    );
+    assert!(response.body().is_empty());
+}
+
+#[cfg(feature = "brotli")]
+#[test]
+fn test_static_dir_brotli() {
+    let mut runner = test::runner(fs::static_dir!("tests/assets/static"));
+
+    let response = runner
+        .perform(Request::get("/style.css").header("accept-encoding", "gzip, br"))
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_matches!(
+        response.headers().get("content-encoding"),
+        Some(h) if h == "br"
+    );
+    assert!(response.body().len() < 1280);
+
+    let response = runner
+        .perform(Request::get("/style.css").header("accept-encoding", "gzip, br;q=0.5"))
+        .unwrap();
+    assert_matches!(
+        response.headers().get("content-encoding"),
+        Some(h) if h == "gzip"
+    );
+}
+
+#[test]
+fn test_dir_spa_fallback() {
+    let root = create_dir("dir-spa-fallback");
+    let mut runner = test::runner(fs::dir(root.join("public")).spa_fallback("index.html"));
+
+    let response = runner
+        .perform(Request::get("/users/42").header("accept", "text/html,*/*;q=0.8"))
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_matches!(response.text(), Ok("index"));
+
+    // existing files are served as usual
+    let response = runner
+        .perform(Request::get("/sub/a.txt").header("accept", "text/html"))
+        .unwrap();
+    assert_matches!(response.text(), Ok("a"));
+
+    // missing assets
+    let response = runner
+        .perform(Request::get("/missing.js").header("accept", "text/html"))
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 404);
+
+    // not accepting HTML
+    let response = runner
+        .perform(Request::get("/users/42").header("accept", "application/json"))
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 404);
+
+    // not GET or HEAD
+    let response = runner
+        .perform(Request::post("/users/42").header("accept", "text/html"))
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 404);
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_file_cache_policy() {
+    let path = create_file("cache-policy");
+    let policy = CachePolicy::new().max_age(Duration::from_secs(60));
+    let mut runner = test::runner(fs::file(&path).cache_policy(policy));
+
+    let response = runner.perform("/").unwrap();
+    assert_matches!(
+        response.headers().get("cache-control"),
+        Some(h) if h == "public, max-age=60"
+    );
+
+    let etag = response.headers()["etag"].clone();
+    let response = runner
+        .perform(Request::get("/").header("if-none-match", etag))
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 304);
+    assert_matches!(
+        response.headers().get("cache-control"),
+        Some(h) if h == "public, max-age=60"
+    );
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_dir_cache_policy() {
+    let root = create_dir("dir-cache-policy");
+    std::fs::write(root.join("public/app.3f2a9c1b.js"), "app").unwrap();
+    let policy = CachePolicy::new()
+        .no_cache_html()
+        .immutable("*.????????.js")
+        .max_age(Duration::from_secs(60));
+    let mut runner = test::runner(fs::dir(root.join("public")).cache_policy(policy));
+
+    let cache_control = |runner: &mut test::TestRunner<_>, path: &str| {
+        let response = runner.perform(path).unwrap();
+        assert_eq!(response.status().as_u16(), 200);
+        response.headers().get("cache-control").cloned()
+    };
+    assert_matches!(cache_control(&mut runner, "/"), Some(ref h) if h == "no-cache");
+    assert_matches!(
+        cache_control(&mut runner, "/app.3f2a9c1b.js"),
+        Some(ref h) if h == "public, max-age=31536000, immutable"
+    );
+    assert_matches!(
+        cache_control(&mut runner, "/sub/a.txt"),
+        Some(ref h) if h == "public, max-age=60"
+    );
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_file_as_attachment() {
+    let path = create_file("as-attachment");
+    let mut runner = test::runner(fs::file(&path).map(|file: NamedFile| {
+        file.as_attachment("données.txt")
+            .content_type(mime::APPLICATION_OCTET_STREAM)
+    }));
+
+    let response = runner.perform("/").unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_matches!(
+        response.headers().get("content-disposition"),
+        Some(h) if h == "attachment; filename=\"donn_es.txt\"; filename*=UTF-8''donn%C3%A9es.txt"
+    );
+    assert_matches!(
+        response.headers().get("content-type"),
+        Some(h) if h == "application/octet-stream"
+    );
+    assert_matches!(response.text(), Ok(CONTENT));
+
+    std::fs::remove_file(path).unwrap();
+}
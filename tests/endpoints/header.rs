@@ -49,3 +49,23 @@ fn test_header_optional() {
 
     assert_matches!(runner.apply(Request::new(())), Ok(None));
 }
+
+#[test]
+fn test_header_accept() {
+    let mut runner = test::runner(endpoints::header::accept());
+
+    assert_matches!(
+        runner.apply(Request::get("/")
+            .header("accept", "text/plain;q=0.5")
+            .header("accept", "application/json")),
+        Ok(ref accept)
+            if accept.iter().map(|r| r.mime().subtype().as_str()).eq(vec!["json", "plain"])
+    );
+
+    assert_matches!(runner.apply(Request::new(())), Ok(ref accept) if accept.is_empty());
+
+    assert_matches!(
+        runner.apply(Request::get("/").header("accept", "text/plain;q=foo")),
+        Err(..)
+    );
+}
@@ -1,5 +1,6 @@
 mod body;
-//mod cookie;
+mod cookie;
+mod fs;
 mod header;
 mod query;
 //mod upgrade;
@@ -0,0 +1,145 @@
+use finchers::endpoint::syntax;
+use finchers::middleware::compress::compress;
+use finchers::prelude::*;
+use finchers::test;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use http::{Request, Response};
+use matches::assert_matches;
+use std::io::Read;
+
+fn long_text() -> String {
+    "Lorem ipsum dolor sit amet. ".repeat(100)
+}
+
+#[test]
+fn test_compress_gzip() {
+    let mut runner = test::runner({ syntax::path!(@get "/").map(long_text).wrap(compress()) });
+
+    let response = runner
+        .perform(Request::get("/").header("accept-encoding", "gzip, deflate"))
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_matches!(
+        response.headers().get("content-encoding"),
+        Some(h) if h == "gzip"
+    );
+    assert_matches!(
+        response.headers().get("vary"),
+        Some(h) if h == "accept-encoding"
+    );
+    assert!(response.body().len() < long_text().len());
+
+    let mut decoded = String::new();
+    GzDecoder::new(&response.body()[..])
+        .read_to_string(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, long_text());
+}
+
+#[test]
+fn test_compress_weakens_etag() {
+    let mut runner = test::runner({
+        syntax::path!(@get "/")
+            .map(|| {
+                Response::builder()
+                    .header("content-type", "text/plain")
+                    .header("etag", "\"abc\"")
+                    .body(long_text())
+                    .unwrap()
+            })
+            .wrap(compress())
+    });
+
+    let response = runner
+        .perform(Request::get("/").header("accept-encoding", "gzip"))
+        .unwrap();
+    assert_matches!(
+        response.headers().get("etag"),
+        Some(h) if h == "W/\"abc\""
+    );
+
+    // The entity tag is not changed if the body is sent as is.
+    let response = runner.perform(Request::get("/")).unwrap();
+    assert_matches!(
+        response.headers().get("etag"),
+        Some(h) if h == "\"abc\""
+    );
+}
+
+#[test]
+fn test_compress_deflate() {
+    let mut runner = test::runner({ syntax::path!(@get "/").map(long_text).wrap(compress()) });
+
+    let response = runner
+        .perform(Request::get("/").header("accept-encoding", "gzip;q=0.5, deflate"))
+        .unwrap();
+    assert_matches!(
+        response.headers().get("content-encoding"),
+        Some(h) if h == "deflate"
+    );
+
+    let mut decoded = String::new();
+    ZlibDecoder::new(&response.body()[..])
+        .read_to_string(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, long_text());
+}
+
+#[test]
+fn test_compress_not_accepted() {
+    let mut runner = test::runner({ syntax::path!(@get "/").map(long_text).wrap(compress()) });
+
+    let response = runner.perform("/").unwrap();
+    assert!(!response.headers().contains_key("content-encoding"));
+    assert_matches!(
+        response.headers().get("vary"),
+        Some(h) if h == "accept-encoding"
+    );
+    assert_matches!(response.text(), Ok(s) if s == long_text());
+}
+
+#[test]
+fn test_compress_below_min_size() {
+    let mut runner = test::runner({
+        syntax::path!(@get "/")
+            .map(|| {
+                Response::builder()
+                    .header("content-type", "text/plain")
+                    .header("content-length", "5")
+                    .body("hello")
+                    .unwrap()
+            })
+            .wrap(compress().min_size(16))
+    });
+
+    let response = runner
+        .perform(Request::get("/").header("accept-encoding", "gzip"))
+        .unwrap();
+    assert!(!response.headers().contains_key("content-encoding"));
+    assert_matches!(
+        response.headers().get("content-length"),
+        Some(h) if h == "5"
+    );
+    assert_matches!(response.text(), Ok("hello"));
+}
+
+#[test]
+fn test_compress_disallowed_content_type() {
+    let mut runner = test::runner({
+        syntax::path!(@get "/")
+            .map(|| {
+                Response::builder()
+                    .header("content-type", "image/png")
+                    .body(vec![0u8; 2048])
+                    .unwrap()
+            })
+            .wrap(compress())
+    });
+
+    let response = runner
+        .perform(Request::get("/").header("accept-encoding", "gzip"))
+        .unwrap();
+    assert!(!response.headers().contains_key("content-encoding"));
+    assert!(!response.headers().contains_key("vary"));
+    assert_eq!(response.body().len(), 2048);
+}
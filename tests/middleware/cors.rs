@@ -0,0 +1,193 @@
+use finchers::endpoint::syntax::{self, verb::Verbs};
+use finchers::middleware::cors::cors;
+use finchers::prelude::*;
+use finchers::test;
+use http::Request;
+use matches::assert_matches;
+
+#[test]
+fn test_cors_preflight() {
+    let mut runner = test::runner({
+        syntax::path!(@post "/posts").map(|| "created").wrap(
+            cors()
+                .allow_origin("https://example.com")
+                .allow_methods(Verbs::GET | Verbs::POST)
+                .allow_header("x-api-key"),
+        )
+    });
+
+    let response = runner
+        .perform(
+            Request::options("/posts")
+                .header("origin", "https://example.com")
+                .header("access-control-request-method", "POST")
+                .header("access-control-request-headers", "X-Api-Key"),
+        )
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 204);
+    assert_matches!(
+        response.headers().get("access-control-allow-origin"),
+        Some(h) if h == "https://example.com"
+    );
+    // only the methods allowed by the matched route are advertised.
+    assert_matches!(
+        response.headers().get("access-control-allow-methods"),
+        Some(h) if h == "POST"
+    );
+    assert_matches!(
+        response.headers().get("access-control-allow-headers"),
+        Some(h) if h == "x-api-key"
+    );
+
+    // disallowed method
+    let response = runner
+        .perform(
+            Request::options("/posts")
+                .header("origin", "https://example.com")
+                .header("access-control-request-method", "DELETE"),
+        )
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 403);
+}
+
+#[test]
+fn test_cors_actual_request() {
+    let mut runner = test::runner({
+        syntax::path!(@get "/posts")
+            .map(|| "posts")
+            .wrap(cors().expose_header("x-request-id"))
+    });
+
+    let response = runner
+        .perform(Request::get("/posts").header("origin", "https://example.com"))
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.text().ok(), Some("posts"));
+    assert_matches!(
+        response.headers().get("access-control-allow-origin"),
+        Some(h) if h == "*"
+    );
+    assert_matches!(
+        response.headers().get("access-control-expose-headers"),
+        Some(h) if h == "x-request-id"
+    );
+
+    // not a CORS request
+    let response = runner.perform("/posts").unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(!response
+        .headers()
+        .contains_key("access-control-allow-origin"));
+}
+
+#[test]
+fn test_cors_disallowed_origin() {
+    let mut runner = test::runner({
+        syntax::path!(@get "/posts")
+            .map(|| "posts")
+            .wrap(cors().allow_origin("https://example.com"))
+    });
+
+    // the actual request is processed without the CORS headers.
+    let response = runner
+        .perform(Request::get("/posts").header("origin", "https://evil.example.com"))
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.text().ok(), Some("posts"));
+    assert!(!response
+        .headers()
+        .contains_key("access-control-allow-origin"));
+
+    let response = runner
+        .perform(Request::get("/users").header("origin", "https://evil.example.com"))
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 404);
+
+    // the preflight request is rejected only if the route matches.
+    let response = runner
+        .perform(
+            Request::options("/posts")
+                .header("origin", "https://evil.example.com")
+                .header("access-control-request-method", "GET"),
+        )
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 403);
+
+    let response = runner
+        .perform(
+            Request::options("/users")
+                .header("origin", "https://evil.example.com")
+                .header("access-control-request-method", "GET"),
+        )
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[test]
+fn test_cors_preflight_unknown_path() {
+    let mut runner = test::runner({
+        syntax::path!(@post "/posts")
+            .map(|| "created")
+            .wrap(cors().allow_methods(Verbs::GET | Verbs::POST))
+    });
+
+    let response = runner
+        .perform(
+            Request::options("/users")
+                .header("origin", "https://example.com")
+                .header("access-control-request-method", "POST"),
+        )
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[test]
+fn test_cors_preflight_falls_through_to_siblings() {
+    let mut runner = test::runner({
+        syntax::path!(@post "/posts")
+            .map(|| "created")
+            .wrap(cors().allow_methods(Verbs::GET | Verbs::POST))
+            .or(syntax::path!(@options "/users").map(|| "users"))
+    });
+
+    let response = runner
+        .perform(
+            Request::options("/users")
+                .header("origin", "https://example.com")
+                .header("access-control-request-method", "POST"),
+        )
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.text().ok(), Some("users"));
+}
+
+#[test]
+fn test_cors_credentials() {
+    let mut runner = test::runner({
+        syntax::path!(@get "/posts").map(|| "posts").wrap(
+            cors()
+                .allow_origin("https://example.com")
+                .allow_credentials(true),
+        )
+    });
+
+    let response = runner
+        .perform(Request::get("/posts").header("origin", "https://example.com"))
+        .unwrap();
+    assert_matches!(
+        response.headers().get("access-control-allow-origin"),
+        Some(h) if h == "https://example.com"
+    );
+    assert_matches!(
+        response.headers().get("access-control-allow-credentials"),
+        Some(h) if h == "true"
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_cors_credentials_requires_origins() {
+    let _ = syntax::path!(@get "/posts")
+        .map(|| "posts")
+        .wrap(cors().allow_credentials(true));
+}
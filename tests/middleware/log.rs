@@ -0,0 +1,96 @@
+use finchers::endpoint::syntax;
+use finchers::middleware::log::{log, stdlog, Format, JsonLine, Logger, Logging, Record};
+use finchers::prelude::*;
+use finchers::service::App;
+use futures::{future, Future};
+use http::{Request, Response, StatusCode};
+use izanami_service::{MakeService, Service};
+use izanami_util::buf_stream::BufStream;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Default, Clone)]
+struct Recorder(Arc<Mutex<Vec<(StatusCode, u64)>>>);
+
+impl Logger for Recorder {
+    type Instance = Recorder;
+
+    fn start<T>(&self, _: &Request<T>) -> Self::Instance {
+        self.clone()
+    }
+}
+
+impl Logging for Recorder {
+    fn finish(self, response: &Response<()>, body_size: u64) {
+        self.0.lock().unwrap().push((response.status(), body_size));
+    }
+}
+
+/// Sends a request to the service and receives the whole of response body.
+fn call<S, Bd>(service: &mut S, request: Request<()>) -> (StatusCode, Vec<u8>)
+where
+    S: Service<Request<()>, Response = Response<Bd>>,
+    S::Error: std::fmt::Debug,
+    Bd: BufStream,
+    Bd::Error: std::fmt::Debug,
+{
+    let response = service.call(request).wait().unwrap();
+    let status = response.status();
+    let mut body = response.into_body();
+    let mut data = vec![];
+    while let Some(chunk) = future::poll_fn(|| body.poll_buf()).wait().unwrap() {
+        data.extend_from_slice(bytes::Buf::bytes(&chunk));
+    }
+    (status, data)
+}
+
+#[test]
+fn test_log_service() {
+    let recorder = Recorder::default();
+    let app = App::new(syntax::path!(@get "/").map(|| "Hello")).with_layer(log(recorder.clone()));
+    let mut service = app.make_service(()).wait().unwrap();
+
+    let (status, body) = call(&mut service, Request::get("/").body(()).unwrap());
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, b"Hello");
+
+    let (status, _) = call(&mut service, Request::get("/missing").body(()).unwrap());
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let records = recorder.0.lock().unwrap();
+    assert_eq!(records[0], (StatusCode::OK, 5));
+    assert_eq!(records[1].0, StatusCode::NOT_FOUND);
+}
+
+#[test]
+fn test_log_service_json_line() {
+    let lines = Arc::new(Mutex::new(vec![]));
+    let format = {
+        let lines = lines.clone();
+        move |record: &Record<'_>| {
+            let line = JsonLine.format(record);
+            lines.lock().unwrap().push(line.clone());
+            line
+        }
+    };
+    let app = App::new(syntax::path!(@get "/").map(|| "Hello")).with_layer(log(stdlog(format)));
+    let mut service = app.make_service(()).wait().unwrap();
+
+    let request = Request::get("/?q=1")
+        .header("user-agent", "curl/7.61.0")
+        .body(())
+        .unwrap();
+    let (status, _) = call(&mut service, request);
+    assert_eq!(status, StatusCode::OK);
+
+    let lines = lines.lock().unwrap();
+    assert_eq!(lines.len(), 1);
+    let record: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+    assert_eq!(record["method"], "GET");
+    assert_eq!(record["uri"], "/?q=1");
+    assert_eq!(record["version"], "HTTP/1.1");
+    assert_eq!(record["status"], 200);
+    assert_eq!(record["body_size"], 5);
+    assert_eq!(record["referer"], serde_json::Value::Null);
+    assert_eq!(record["user_agent"], "curl/7.61.0");
+    assert!(record["elapsed_ms"].is_number());
+}
@@ -0,0 +1,3 @@
+mod compress;
+mod cors;
+mod log;
@@ -0,0 +1,265 @@
+use finchers::endpoint::syntax::path;
+use finchers::endpoints::{body, header, query};
+use finchers::openapi::{self, OpenApi, Schema};
+use finchers::output::Json;
+use finchers::prelude::*;
+use finchers::test;
+use matches::assert_matches;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Schema)]
+struct Post {
+    id: u64,
+    title: String,
+    tags: Option<Vec<String>>,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+enum Status {
+    Draft,
+    Published,
+}
+
+#[test]
+fn test_derive_schema() {
+    assert_eq!(
+        Post::schema(),
+        json!({
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer", "format": "int64", "minimum": 0 },
+                "title": { "type": "string" },
+                "tags": { "type": "array", "items": { "type": "string" } }
+            },
+            "required": ["id", "title"]
+        })
+    );
+    assert_eq!(
+        Status::schema(),
+        json!({ "type": "string", "enum": ["Draft", "Published"] })
+    );
+}
+
+#[derive(Deserialize, Serialize, Schema)]
+#[allow(dead_code)]
+struct Paging {
+    page: u32,
+    per_page: Option<u32>,
+}
+
+#[derive(Serialize, Schema)]
+#[serde(rename = "BlogArticle", rename_all = "camelCase")]
+struct Article {
+    #[serde(rename = "ID")]
+    id: u64,
+    created_at: String,
+    #[serde(skip)]
+    #[allow(dead_code)]
+    secret: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(flatten)]
+    paging: Paging,
+}
+
+#[derive(Serialize, Schema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[allow(dead_code)]
+enum Visibility {
+    PublicPost,
+    #[serde(rename = "hidden")]
+    Private,
+    #[serde(skip)]
+    Internal,
+}
+
+#[test]
+fn test_derive_schema_serde_attributes() {
+    assert_eq!(Article::name(), Some("BlogArticle"));
+    assert_eq!(
+        Article::schema(),
+        json!({
+            "type": "object",
+            "properties": {
+                "ID": { "type": "integer", "format": "int64", "minimum": 0 },
+                "createdAt": { "type": "string" },
+                "tags": { "type": "array", "items": { "type": "string" } },
+                "page": { "type": "integer", "format": "int32", "minimum": 0 },
+                "per_page": { "type": "integer", "format": "int32", "minimum": 0 }
+            },
+            "required": ["ID", "createdAt", "page"]
+        })
+    );
+    let article = Article {
+        id: 1,
+        created_at: "2018-10-01".into(),
+        secret: "".into(),
+        tags: vec![],
+        paging: Paging {
+            page: 1,
+            per_page: None,
+        },
+    };
+    assert_eq!(
+        serde_json::to_value(&article).unwrap(),
+        json!({ "ID": 1, "createdAt": "2018-10-01", "page": 1, "per_page": null })
+    );
+
+    assert_eq!(
+        Visibility::schema(),
+        json!({ "type": "string", "enum": ["PUBLIC_POST", "hidden"] })
+    );
+}
+
+#[test]
+fn test_openapi_document() {
+    let endpoint =
+        openapi::json_output::<Post, _>(path!(@get "/posts/<id: u64>").map(|id: u64| {
+            Json(Post {
+                id,
+                title: "Hello".into(),
+                tags: None,
+            })
+        }))
+        .or(path!(@get "/posts")
+            .and(query::optional::<Paging>().with_schema())
+            .map(|_: Option<Paging>| "list"))
+        .or(path!(@post "/posts")
+            .and(header::parse::<String>("authorization"))
+            .and(body::json::<serde_json::Value>().with_schema())
+            .map(|_: String, _: serde_json::Value| "create"))
+        .or(path!("/health").map(|| "ok"));
+
+    let document = OpenApi::new("Blog API", "1.0.0")
+        .routes(&endpoint)
+        .schema::<serde_json::Value>("Any")
+        .to_json();
+
+    assert_eq!(document["openapi"], "3.0.2");
+    assert_eq!(document["info"]["title"], "Blog API");
+
+    let show = &document["paths"]["/posts/{id}"]["get"];
+    assert_eq!(
+        show["parameters"],
+        json!([{
+            "name": "id",
+            "in": "path",
+            "required": true,
+            "schema": { "type": "integer", "format": "int64", "minimum": 0 }
+        }])
+    );
+    assert_eq!(
+        show["responses"]["200"]["content"]["application/json"]["schema"],
+        json!({ "$ref": "#/components/schemas/Post" })
+    );
+
+    let list = &document["paths"]["/posts"]["get"];
+    assert_eq!(
+        list["parameters"],
+        json!([
+            {
+                "name": "page",
+                "in": "query",
+                "required": false,
+                "schema": { "type": "integer", "format": "int32", "minimum": 0 }
+            },
+            {
+                "name": "per_page",
+                "in": "query",
+                "required": false,
+                "schema": { "type": "integer", "format": "int32", "minimum": 0 }
+            }
+        ])
+    );
+    assert!(list["responses"]["default"].is_object());
+
+    let create = &document["paths"]["/posts"]["post"];
+    assert_eq!(create["parameters"][0]["name"], "authorization");
+    assert_eq!(create["parameters"][0]["in"], "header");
+    assert_eq!(
+        create["requestBody"]["content"]["application/json"]["schema"],
+        json!({ "$ref": "#/components/schemas/Any" })
+    );
+
+    // the routes without method are not documented.
+    assert!(document["paths"].get("/health").is_none());
+
+    assert_eq!(document["components"]["schemas"]["Post"], Post::schema());
+    assert_eq!(document["components"]["schemas"]["Any"], json!({}));
+}
+
+#[test]
+fn test_openapi_unnamed_params() {
+    let document = OpenApi::new("Blog API", "1.0.0")
+        .routes(&path!(@get "/users/<u64>/posts/<u32>"))
+        .to_json();
+    let parameters = &document["paths"]["/users/{param0}/posts/{param1}"]["get"]["parameters"];
+    assert_eq!(parameters[0]["name"], "param0");
+    assert_eq!(parameters[1]["name"], "param1");
+}
+
+#[test]
+fn test_openapi_yaml() {
+    let document = OpenApi::new("Blog API", "1.0.0").routes(&path!(@get "/posts/<id: u32>"));
+    assert_eq!(
+        document.to_yaml(),
+        r#"info:
+  title: "Blog API"
+  version: "1.0.0"
+openapi: "3.0.2"
+paths:
+  "/posts/{id}":
+    get:
+      parameters:
+        -
+          in: "path"
+          name: "id"
+          required: true
+          schema:
+            format: "int32"
+            minimum: 0
+            type: "integer"
+      responses:
+        default:
+          description: "The response of the endpoint."
+"#
+    );
+}
+
+#[test]
+fn test_openapi_endpoints() {
+    let document = OpenApi::new("Blog API", "1.0.0").routes(&path!(@get "/posts"));
+    let mut runner = test::runner(
+        path!(@get "/openapi.json")
+            .and(document.clone().into_endpoint())
+            .or(path!(@get "/openapi.yaml").and(document.into_yaml_endpoint()))
+            .or(path!(@get "/docs").and(openapi::swagger_ui("/openapi.json", "/assets/"))),
+    );
+
+    let response = runner.perform("/openapi.json").unwrap();
+    assert_matches!(
+        response.headers().get("content-type"),
+        Some(h) if h == "application/json"
+    );
+    let document: serde_json::Value = response.json().unwrap();
+    assert!(document["paths"]["/posts"]["get"].is_object());
+
+    let response = runner.perform("/openapi.yaml").unwrap();
+    assert_matches!(
+        response.headers().get("content-type"),
+        Some(h) if h == "application/x-yaml"
+    );
+    assert!(response.text().unwrap().contains("\nopenapi: \"3.0.2\"\n"));
+
+    let response = runner.perform("/docs").unwrap();
+    assert_matches!(
+        response.headers().get("content-type"),
+        Some(h) if h == "text/html; charset=utf-8"
+    );
+    let html = response.text().unwrap();
+    assert!(html.contains("url: \"/openapi.json\""));
+    assert!(html.contains("<script src=\"/assets/swagger-ui-bundle.js\">"));
+    assert!(!html.contains(openapi::SWAGGER_UI_CDN));
+}
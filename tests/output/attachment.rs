@@ -0,0 +1,39 @@
+use finchers::output::Attachment;
+use finchers::prelude::*;
+use finchers::test;
+use http::StatusCode;
+use matches::assert_matches;
+
+#[test]
+fn test_attachment() {
+    let mut runner =
+        test::runner(endpoint::unit().map(|| Attachment::new(b"hello".to_vec(), "hello.bin")));
+    let response = runner.perform("/").unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_matches!(
+        response.headers().get("content-disposition"),
+        Some(h) if h == "attachment; filename=\"hello.bin\""
+    );
+    assert_matches!(
+        response.headers().get("content-type"),
+        Some(h) if h == "application/octet-stream"
+    );
+    assert_matches!(response.text(), Ok("hello"));
+}
+
+#[test]
+fn test_attachment_content_type_and_utf8_filename() {
+    let mut runner =
+        test::runner(endpoint::unit().map(|| {
+            Attachment::new(b"id,name\n".to_vec(), "名簿.csv").content_type(mime::TEXT_CSV)
+        }));
+    let response = runner.perform("/").unwrap();
+    assert_matches!(
+        response.headers().get("content-disposition"),
+        Some(h) if h == "attachment; filename=\"__.csv\"; filename*=UTF-8''%E5%90%8D%E7%B0%BF.csv"
+    );
+    assert_matches!(
+        response.headers().get("content-type"),
+        Some(h) if h == "text/csv"
+    );
+}
@@ -0,0 +1,3 @@
+mod attachment;
+mod negotiated;
+mod redirect;
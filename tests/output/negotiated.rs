@@ -0,0 +1,79 @@
+use finchers::output::Negotiated;
+use finchers::prelude::*;
+use finchers::test;
+use http::{Request, StatusCode};
+use matches::assert_matches;
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+struct Message {
+    id: u32,
+    text: &'static str,
+}
+
+fn runner() -> test::TestRunner<impl Endpoint<test::ReqBody, Output = (Negotiated<Message>,)>> {
+    test::runner(endpoint::unit().map(|| {
+        Negotiated(Message {
+            id: 1,
+            text: "hello",
+        })
+    }))
+}
+
+#[test]
+fn test_negotiated_json() {
+    let response = runner()
+        .perform(Request::get("/").header("accept", "application/json"))
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_matches!(
+        response.headers().get("content-type"),
+        Some(h) if h == "application/json"
+    );
+    assert_eq!(response.text().unwrap(), r#"{"id":1,"text":"hello"}"#);
+}
+
+#[test]
+fn test_negotiated_urlencoded() {
+    let response = runner()
+        .perform(Request::get("/").header(
+            "accept",
+            "application/json;q=0.5, application/x-www-form-urlencoded",
+        ))
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_matches!(
+        response.headers().get("content-type"),
+        Some(h) if h == "application/x-www-form-urlencoded"
+    );
+    assert_eq!(response.text().unwrap(), "id=1&text=hello");
+}
+
+#[test]
+fn test_negotiated_text() {
+    let response = runner()
+        .perform(Request::get("/").header("accept", "text/*"))
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_matches!(
+        response.headers().get("content-type"),
+        Some(h) if h == "text/plain; charset=utf-8"
+    );
+}
+
+#[test]
+fn test_negotiated_default() {
+    let response = runner().perform("/").unwrap();
+    assert_matches!(
+        response.headers().get("content-type"),
+        Some(h) if h == "application/json"
+    );
+}
+
+#[test]
+fn test_negotiated_not_acceptable() {
+    let response = runner()
+        .perform(Request::get("/").header("accept", "image/png"))
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE);
+}
@@ -0,0 +1,25 @@
+use finchers::endpoint::syntax::{self, ExtractPath};
+use finchers::output::Redirect;
+use finchers::prelude::*;
+use finchers::test;
+use http::header::HeaderValue;
+use http::StatusCode;
+use matches::assert_matches;
+
+#[derive(ExtractPath)]
+#[path = "/posts/<u64>"]
+struct PostPath;
+
+#[test]
+fn test_redirect_to_generated_path() {
+    let mut runner = test::runner(syntax::path!(@post "/posts").map(|| {
+        let location = HeaderValue::from_str(&PostPath::to_path(&(42,))).unwrap();
+        Redirect::new(StatusCode::SEE_OTHER).location_value(location)
+    }));
+    let response = runner.perform(http::Request::post("/posts")).unwrap();
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    assert_matches!(
+        response.headers().get("location"),
+        Some(h) if h == "/posts/42"
+    );
+}
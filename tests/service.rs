@@ -0,0 +1,57 @@
+use finchers::endpoint::syntax;
+use finchers::prelude::*;
+use finchers::test;
+use http::{Request, StatusCode};
+use matches::assert_matches;
+
+#[test]
+fn test_auto_head() {
+    let mut runner = test::runner(syntax::path!(@get "/foo").and(endpoint::value("Hello")));
+    runner.auto_head_and_options(true);
+
+    let response = runner.perform(Request::head("/foo")).unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_matches!(
+        response.headers().get("content-type"),
+        Some(h) if h == "text/plain; charset=utf-8"
+    );
+    // The body is discarded without being read.
+    assert!(!response.headers().contains_key("content-length"));
+    assert!(response.body().is_empty());
+}
+
+#[test]
+fn test_auto_head_disabled() {
+    let mut runner = test::runner(syntax::path!(@get "/foo").and(endpoint::value("Hello")));
+
+    let response = runner.perform(Request::head("/foo")).unwrap();
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+}
+
+#[test]
+fn test_auto_options() {
+    let mut runner = test::runner({
+        let e1 = syntax::path!(@get "/foo").and(endpoint::value("get"));
+        let e2 = syntax::path!(@post "/foo").and(endpoint::value("post"));
+        e1.or(e2)
+    });
+    runner.auto_head_and_options(true);
+
+    let response = runner.perform(Request::options("/foo")).unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_matches!(
+        response.headers().get("allow"),
+        Some(h) if h == "GET, POST, HEAD, OPTIONS"
+    );
+    assert!(response.body().is_empty());
+
+    let response = runner.perform(Request::delete("/foo")).unwrap();
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_matches!(
+        response.headers().get("allow"),
+        Some(h) if h == "GET, POST, HEAD, OPTIONS"
+    );
+
+    let response = runner.perform(Request::options("/bar")).unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
@@ -1,5 +1,9 @@
 mod endpoint;
 mod endpoints;
+mod middleware;
+mod openapi;
+mod output;
+mod service;
 
 #[test]
 fn version_sync() {